@@ -0,0 +1,53 @@
+use crate::generate::{
+    generate_puzzle, parse_difficulty, parse_game, parse_seeds, region_constraints,
+};
+use crate::Options;
+use lopuzz_logic::booklet::{render_booklet, BookletOptions};
+use lopuzz_logic::packs::Pack;
+use lopuzz_logic::puzzle_file::PuzzleFile;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// This function lays out a PDF booklet of puzzles from a pack, or freshly generated from seeds.
+/// - options: the command line options.
+pub fn run(options: &Options) -> Result<ExitCode, String> {
+    let out = options.require("out")?;
+    let mut booklet_options = BookletOptions::default();
+    if let Some(per_page) = options.number("per-page")? {
+        booklet_options.puzzles_per_page = per_page;
+    }
+    if let Some(palette) = options.get("palette") {
+        booklet_options.palette = palette.split(',').map(|c| c.trim().to_string()).collect();
+    }
+
+    let puzzles: Vec<PuzzleFile> = match options.get("pack") {
+        Some(directory) => {
+            let pack = Pack::load(Path::new(directory))?;
+            booklet_options.title = pack.manifest.title.clone();
+            (0..pack.manifest.puzzles.len())
+                .map(|index| pack.puzzle(index))
+                .collect::<Result<_, String>>()?
+        }
+        None => {
+            let game = parse_game(options.require("game")?)?;
+            let size: u32 = options.number("size")?.ok_or("missing --size")?;
+            let difficulty = parse_difficulty(options, game)?;
+            let constraints = region_constraints(options)?;
+            parse_seeds(options)?
+                .map(|seed| {
+                    generate_puzzle(game, size, difficulty, &constraints, seed)
+                        .map_err(|e| format!("seed {}: {}", seed, e))
+                })
+                .collect::<Result<_, String>>()?
+        }
+    };
+    if let Some(title) = options.get("title") {
+        booklet_options.title = title.to_string();
+    }
+
+    let pdf = render_booklet(&puzzles, &booklet_options)?;
+    fs::write(out, pdf).map_err(|e| format!("{}: {}", out, e))?;
+    println!("wrote {} puzzles to {}", puzzles.len(), out);
+    return Ok(ExitCode::SUCCESS);
+}
@@ -0,0 +1,244 @@
+use crate::Options;
+use lopuzz_logic::numbers::{generate_numbers_grid, remove_ruled_values, NumbersRules};
+use lopuzz_logic::puzzle_file::{save_puzzle, PuzzleFile};
+use lopuzz_logic::queens::{find_queens_solution, generate_queens_game, QueensVariant};
+use lopuzz_logic::regions::RegionConstraints;
+use lopuzz_logic::utilities::GameKind;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// This struct is the outcome of generating the puzzle for one seed.
+struct Generated {
+    seed: u64,
+    time: Duration,
+    result: Result<PathBuf, String>,
+}
+
+/// This function generates a puzzle for each seed in the range, spread over several threads,
+/// writes them as puzzle files and prints a summary.
+/// - options: the command line options.
+pub fn run(options: &Options) -> Result<ExitCode, String> {
+    let game = parse_game(options.require("game")?)?;
+    let size: u32 = options.number("size")?.ok_or("missing --size")?;
+    let difficulty = parse_difficulty(options, game)?;
+    let seeds = parse_seeds(options)?;
+    let constraints = region_constraints(options)?;
+    let out = PathBuf::from(options.get("out").unwrap_or("puzzles"));
+    let default_jobs = thread::available_parallelism().map_or(1, |n| n.get());
+    let jobs: usize = options.number("jobs")?.unwrap_or(default_jobs).max(1);
+    fs::create_dir_all(&out).map_err(|e| format!("{}: {}", out.display(), e))?;
+
+    let started = Instant::now();
+    // each thread takes the next seed until they run out
+    let next_seed = AtomicU64::new(seeds.start);
+    let (sender, receiver) = mpsc::channel::<Generated>();
+    let mut results: Vec<Generated> = Vec::new();
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let next_seed = &next_seed;
+            let out = &out;
+            let seeds = &seeds;
+            let constraints = &constraints;
+            scope.spawn(move || loop {
+                let seed = next_seed.fetch_add(1, Ordering::Relaxed);
+                if seed >= seeds.end {
+                    return;
+                }
+                let puzzle_started = Instant::now();
+                let result = generate_puzzle(game, size, difficulty, constraints, seed)
+                    .and_then(|puzzle| write_puzzle(out, &puzzle, seed));
+                let generated = Generated {
+                    seed,
+                    time: puzzle_started.elapsed(),
+                    result,
+                };
+                if sender.send(generated).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(sender);
+        for generated in receiver {
+            match &generated.result {
+                Ok(path) => println!(
+                    "seed {}: {} ({} ms)",
+                    generated.seed,
+                    path.display(),
+                    generated.time.as_millis()
+                ),
+                Err(e) => println!("seed {}: FAILED {}", generated.seed, e),
+            }
+            results.push(generated);
+        }
+    });
+
+    print_summary(&results, started.elapsed());
+    if results.iter().any(|generated| generated.result.is_err()) {
+        return Ok(ExitCode::FAILURE);
+    }
+    return Ok(ExitCode::SUCCESS);
+}
+
+/// This function reads the name of a game.
+/// - name: the name given on the command line.
+pub fn parse_game(name: &str) -> Result<GameKind, String> {
+    match name {
+        "queens" => Ok(GameKind::Queens),
+        "numbers" => Ok(GameKind::Numbers),
+        _ => Err(format!("unknown game {}", name)),
+    }
+}
+
+/// This function reads `--difficulty` for games that have a difficulty setting.
+/// Queens boards have no setting, so giving one for queens is an error.
+/// - options: the command line options.
+/// - game: the game being generated.
+pub fn parse_difficulty(options: &Options, game: GameKind) -> Result<Option<u32>, String> {
+    let difficulty: Option<u32> = options.number("difficulty")?;
+    if difficulty.is_some() && game == GameKind::Queens {
+        return Err("--difficulty only applies to numbers puzzles".to_string());
+    }
+    if difficulty.is_some_and(|difficulty| difficulty > 2) {
+        return Err("--difficulty must be 0 (easy), 1 (medium) or 2 (hard)".to_string());
+    }
+    return Ok(difficulty);
+}
+
+/// This function reads the limits on queens region sizes from `--min-region`, `--max-region`
+/// and `--allow-single`.
+/// - options: the command line options.
+pub fn region_constraints(options: &Options) -> Result<RegionConstraints, String> {
+    let mut constraints = RegionConstraints::default();
+    if let Some(min) = options.number("min-region")? {
+        constraints.min_region_size = min;
+    }
+    constraints.max_region_size = options.number("max-region")?;
+    constraints.allow_single_cells = options.get("allow-single").is_some();
+    return Ok(constraints);
+}
+
+/// This function reads the seeds to generate from `--seeds A..B`, or `--count N` for seeds `0..N`.
+/// - options: the command line options.
+pub fn parse_seeds(options: &Options) -> Result<Range<u64>, String> {
+    if let Some(range) = options.get("seeds") {
+        let Some((start, end)) = range.split_once("..") else {
+            return Err(format!(
+                "--seeds expects a range like 0..10 but got {}",
+                range
+            ));
+        };
+        let start: u64 = start.parse().map_err(|_| format!("bad seed {}", start))?;
+        let end: u64 = end.parse().map_err(|_| format!("bad seed {}", end))?;
+        return Ok(start..end);
+    }
+    let count: u64 = options.number("count")?.unwrap_or(10);
+    return Ok(0..count);
+}
+
+/// This function generates a single puzzle from a seed. The same seed always gives the same puzzle.
+/// - game: the game to generate.
+/// - size: the size of the grid.
+/// - difficulty: the difficulty setting, for numbers. Ignored for queens.
+/// - constraints: the limits on region sizes, for queens.
+/// - seed: the seed for the random number generator.
+pub fn generate_puzzle(
+    game: GameKind,
+    size: u32,
+    difficulty: Option<u32>,
+    constraints: &RegionConstraints,
+    seed: u64,
+) -> Result<PuzzleFile, String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut puzzle = match game {
+        GameKind::Queens => {
+            let variant = QueensVariant {
+                constraints: *constraints,
+                ..QueensVariant::queens(size)
+            };
+            let colour_grid = generate_queens_game(&variant, &mut rng)?;
+            let mut puzzle = PuzzleFile::queens(colour_grid, size);
+            puzzle.solution = find_queens_solution(&puzzle.grid, &variant);
+            puzzle
+        }
+        GameKind::Numbers => {
+            let difficulty = difficulty.unwrap_or(0);
+            let solution = generate_numbers_grid(size, &mut rng);
+            let givens = remove_ruled_values(
+                &solution,
+                difficulty,
+                size,
+                &NumbersRules::default(),
+                &mut rng,
+            );
+            let mut puzzle = PuzzleFile::numbers(givens, size);
+            puzzle.solution = Some(solution);
+            puzzle.difficulty = Some(difficulty);
+            puzzle
+        }
+    };
+    puzzle.seed = Some(seed);
+    puzzle.validate()?;
+    return Ok(puzzle);
+}
+
+/// This function writes a generated puzzle into the output directory.
+/// - out: the output directory.
+/// - puzzle: the puzzle to write.
+/// - seed: the seed the puzzle was generated from.
+fn write_puzzle(out: &Path, puzzle: &PuzzleFile, seed: u64) -> Result<PathBuf, String> {
+    let path = out.join(format!(
+        "{}-{}x{}-{}.json",
+        puzzle.game.name(),
+        puzzle.size,
+        puzzle.size,
+        seed
+    ));
+    save_puzzle(&path, puzzle)?;
+    return Ok(path);
+}
+
+/// This function prints how many puzzles were generated, how long they took and which seeds failed.
+/// - results: the outcome for each seed.
+/// - total: the wall-clock time of the whole run.
+fn print_summary(results: &[Generated], total: Duration) {
+    let times: Vec<Duration> = results
+        .iter()
+        .filter(|generated| generated.result.is_ok())
+        .map(|generated| generated.time)
+        .collect();
+    let mut failed: Vec<u64> = results
+        .iter()
+        .filter(|generated| generated.result.is_err())
+        .map(|generated| generated.seed)
+        .collect();
+    failed.sort();
+
+    println!();
+    println!(
+        "generated {} of {} puzzles in {:.2}s",
+        times.len(),
+        results.len(),
+        total.as_secs_f64()
+    );
+    if let (Some(min), Some(max)) = (times.iter().min(), times.iter().max()) {
+        let mean = times.iter().sum::<Duration>() / times.len() as u32;
+        println!(
+            "time per puzzle: min {} ms, mean {} ms, max {} ms",
+            min.as_millis(),
+            mean.as_millis(),
+            max.as_millis()
+        );
+    }
+    if !failed.is_empty() {
+        println!("failed seeds: {:?}", failed);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::Options;
+use lopuzz_logic::layout::parse_queens_layout;
+use lopuzz_logic::puzzle_file::{save_puzzle, PuzzleFile};
+use lopuzz_logic::queens::{find_queens_solution, BoardShape, QueensVariant};
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// This function checks a hand-drawn queens layout, and writes it as a puzzle file if `--out` is given.
+/// Rectangular layouts and layouts with void cells are written with their shape.
+/// Exits with 1 if the layout isn't a valid board.
+/// - options: the command line options.
+pub fn run(options: &Options) -> Result<ExitCode, String> {
+    let path = options.require("file")?;
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let layout = match parse_queens_layout(&text) {
+        Ok(layout) => layout,
+        Err(e) => {
+            println!("{}: {}", path, e);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    println!(
+        "{}: valid {}x{} board with regions {}",
+        path,
+        layout.rows,
+        layout.cols,
+        layout.labels.iter().collect::<String>()
+    );
+    if let Some(out) = options.get("out") {
+        let variant = QueensVariant {
+            shape: BoardShape::of_grid(&layout.colour_grid, layout.rows, layout.cols),
+            ..QueensVariant::queens(layout.rows)
+        };
+        let mut puzzle = PuzzleFile::queens_variant_board(layout.colour_grid, &variant);
+        puzzle.solution = find_queens_solution(&puzzle.grid, &variant);
+        if let Some(title) = options.get("title") {
+            puzzle.metadata.title = Some(title.to_string());
+        }
+        save_puzzle(Path::new(out), &puzzle)?;
+        println!("wrote {}", out);
+    }
+    return Ok(ExitCode::SUCCESS);
+}
@@ -0,0 +1,134 @@
+//! Command line tools for working with puzzles outside of the app.
+//!
+//! Run `lopuzz-cli help` for the list of commands.
+
+mod booklet;
+mod generate;
+mod layout;
+mod png;
+mod render;
+mod solve;
+mod svg;
+
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: lopuzz-cli <command> [options]
+
+commands:
+  generate   generate puzzles into JSON puzzle files
+             --game queens|numbers  --size N  [--difficulty 0|1|2 (numbers only)]
+             [--seeds A..B | --count N]  [--out DIR]  [--jobs N]
+             [--min-region N]  [--max-region N]  [--allow-single]
+  solve      count the solutions of a puzzle, rate it and show how to solve it
+             --file PATH (.json puzzle file, or text with one row per line)
+             [--game queens|numbers (text files only)]  [--trace]  [--ascii]
+  layout     check a hand-drawn queens layout of letters, one row per line
+             --file PATH  [--out FILE.json]  [--title TEXT]
+  render     draw a puzzle as text
+             --file PATH  [--game queens|numbers]  [--solution]  [--ascii]
+  svg        draw a puzzle as SVG
+             --file PATH  [--game queens|numbers]  [--solution]  [--out FILE]
+             [--cell-size N]  [--palette #hex,#hex,...]
+  png        draw a puzzle as a PNG image
+             --file PATH  --out FILE  [--game queens|numbers]  [--solution]
+             [--cell-size N]  [--palette #hex,#hex,...]
+  booklet    lay out puzzles as a printable PDF with the solutions at the back
+             --out FILE  (--pack DIR | --game queens|numbers --size N
+             [--difficulty 0|1|2 (numbers only)] [--seeds A..B | --count N]
+             [--min-region N] [--max-region N] [--allow-single])
+             [--title TEXT]  [--per-page N]  [--palette #hex,#hex,...]
+  help       show this message";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(command) = args.first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    let options = match parse_options(&args[1..]) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let result = match command.as_str() {
+        "generate" => generate::run(&options),
+        "solve" => solve::run(&options),
+        "layout" => layout::run(&options),
+        "render" => render::run(&options),
+        "svg" => svg::run(&options),
+        "png" => png::run(&options),
+        "booklet" => booklet::run(&options),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprintln!("unknown command {}\n\n{}", command, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(code) => return code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::from(2);
+        }
+    }
+}
+
+/// This struct holds the `--name value` options given on the command line.
+pub struct Options {
+    values: HashMap<String, String>,
+}
+
+impl Options {
+    /// This function gets an option's value as text.
+    /// - name: the name of the option, without the dashes.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        return self.values.get(name).map(|value| value.as_str());
+    }
+
+    /// This function gets an option that must be given.
+    /// - name: the name of the option, without the dashes.
+    pub fn require(&self, name: &str) -> Result<&str, String> {
+        return self.get(name).ok_or_else(|| format!("missing --{}", name));
+    }
+
+    /// This function gets an option's value as a number, if it was given.
+    /// - name: the name of the option, without the dashes.
+    pub fn number<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        let Some(value) = self.get(name) else {
+            return Ok(None);
+        };
+        return value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("--{} expects a number but got {}", name, value));
+    }
+}
+
+/// This function reads `--name value` pairs. A flag with no value is given the value "true".
+/// - args: the arguments after the command.
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut values = HashMap::new();
+    let mut i = 0;
+    while i < args.len() {
+        let Some(name) = args[i].strip_prefix("--") else {
+            return Err(format!("unexpected argument {}", args[i]));
+        };
+        match args.get(i + 1) {
+            Some(value) if !value.starts_with("--") => {
+                values.insert(name.to_string(), value.clone());
+                i += 2;
+            }
+            _ => {
+                values.insert(name.to_string(), "true".to_string());
+                i += 1;
+            }
+        }
+    }
+    return Ok(Options { values });
+}
@@ -0,0 +1,24 @@
+use crate::solve::read_puzzle;
+use crate::Options;
+use lopuzz_logic::raster::{render_puzzle_png, PngOptions};
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// This function draws a puzzle from a file as a PNG image and writes it to `--out`.
+/// - options: the command line options.
+pub fn run(options: &Options) -> Result<ExitCode, String> {
+    let path = Path::new(options.require("file")?);
+    let out = options.require("out")?;
+    let puzzle = read_puzzle(path, options.get("game"))?;
+    let mut png_options = PngOptions::default();
+    if let Some(cell_size) = options.number("cell-size")? {
+        png_options.cell_size = cell_size;
+    }
+    if let Some(palette) = options.get("palette") {
+        png_options.palette = palette.split(',').map(|c| c.trim().to_string()).collect();
+    }
+    let png = render_puzzle_png(&puzzle, options.get("solution").is_some(), &png_options)?;
+    fs::write(out, png).map_err(|e| format!("{}: {}", out, e))?;
+    return Ok(ExitCode::SUCCESS);
+}
@@ -0,0 +1,61 @@
+use crate::solve::read_puzzle;
+use crate::Options;
+use lopuzz_logic::render::{render_numbers, render_queens, TextStyle};
+use lopuzz_logic::utilities::GameKind;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// This function draws a puzzle from a file, optionally with its solution filled in.
+/// - options: the command line options.
+pub fn run(options: &Options) -> Result<ExitCode, String> {
+    let path = Path::new(options.require("file")?);
+    let puzzle = read_puzzle(path, options.get("game"))?;
+    puzzle.check_drawable()?;
+    let style = text_style(options);
+    let mut solution = None;
+    if options.get("solution").is_some() {
+        solution = puzzle.find_solution();
+        if solution.is_none() {
+            return Err("the puzzle has no solution".to_string());
+        }
+    }
+    print!(
+        "{}",
+        render_board(
+            puzzle.game,
+            puzzle.board(),
+            solution.as_ref(),
+            puzzle.size,
+            style
+        )?
+    );
+    return Ok(ExitCode::SUCCESS);
+}
+
+/// This function picks the text style from the `--ascii` flag.
+/// - options: the command line options.
+pub fn text_style(options: &Options) -> TextStyle {
+    if options.get("ascii").is_some() {
+        return TextStyle::Ascii;
+    }
+    return TextStyle::Unicode;
+}
+
+/// This function draws a board of either game.
+/// - game: the game.
+/// - grid: the colour grid for queens, or the grid of givens for numbers.
+/// - solution: the solution to draw on top, if any.
+/// - size: the size of the grid.
+/// - style: the characters to draw with.
+pub fn render_board(
+    game: GameKind,
+    grid: &Vec<u32>,
+    solution: Option<&Vec<u32>>,
+    size: u32,
+    style: TextStyle,
+) -> Result<String, String> {
+    match game {
+        GameKind::Queens => return render_queens(grid, solution, size, style),
+        GameKind::Numbers => return render_numbers(grid, solution, size, style),
+    }
+}
@@ -0,0 +1,180 @@
+use crate::generate::parse_game;
+use crate::render::{render_board, text_style};
+use crate::Options;
+use lopuzz_logic::layout::format_queens_layout;
+use lopuzz_logic::numbers::validate_ruled_numbers_clues;
+use lopuzz_logic::puzzle_file::{parse_puzzle_json, PuzzleFile};
+use lopuzz_logic::queens::split_board_regions;
+use lopuzz_logic::rating::difficulty_name;
+use lopuzz_logic::regions::region_metrics;
+use lopuzz_logic::utilities::GameKind;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// This function solves a puzzle from a file under its rules and reports how many solutions it has,
+/// how hard it is and, with `--trace`, the steps taken to solve it.
+/// Exits with failure if the puzzle is invalid or doesn't have exactly one solution.
+/// - options: the command line options.
+pub fn run(options: &Options) -> Result<ExitCode, String> {
+    let path = Path::new(options.require("file")?);
+    let puzzle = read_puzzle(path, options.get("game"))?;
+    let (game, size) = (puzzle.game, puzzle.size);
+    let (rows, cols) = puzzle.dimensions();
+    println!("game: {} {}x{}", game.name(), rows, cols);
+
+    if let Err(e) = check_board(&puzzle) {
+        println!("invalid: {}", e);
+        return Ok(ExitCode::FAILURE);
+    }
+
+    // the region metrics only know full square boards
+    if game == GameKind::Queens && puzzle.shape.is_none() {
+        let metrics = region_metrics(&puzzle.grid, size)?;
+        println!(
+            "regions: sizes {:?}, size variance {:.2}, mean compactness {:.2}",
+            metrics.sizes, metrics.size_variance, metrics.mean_compactness
+        );
+    }
+
+    let n_solutions = puzzle.count_solutions(2);
+    match n_solutions {
+        0 => println!("solutions: 0"),
+        1 => println!("solutions: 1"),
+        _ => println!("solutions: many"),
+    }
+
+    match puzzle.rate() {
+        Some(report) => {
+            println!(
+                "difficulty: {} ({})",
+                report.difficulty,
+                difficulty_name(report.difficulty)
+            );
+            if options.get("trace").is_some() {
+                println!("trace:");
+                for (i, step) in report.steps.iter().enumerate() {
+                    println!("  {}. {}", i + 1, step.description);
+                }
+                if !report.solved {
+                    println!("  stuck: the rest needs trial and error");
+                }
+            }
+        }
+        None => println!("difficulty: unrated (only the standard rules can be rated)"),
+    }
+
+    if n_solutions != 1 {
+        return Ok(ExitCode::FAILURE);
+    }
+    if let Some(solution) = puzzle.find_solution() {
+        println!("solution:");
+        if puzzle.check_drawable().is_err() {
+            print!("{}", format_stars(&puzzle.grid, &solution, cols));
+            return Ok(ExitCode::SUCCESS);
+        }
+        print!(
+            "{}",
+            render_board(
+                game,
+                puzzle.board(),
+                Some(&solution),
+                size,
+                text_style(options)
+            )?
+        );
+    }
+    return Ok(ExitCode::SUCCESS);
+}
+
+/// This function reads a puzzle from a JSON puzzle file, or from a text file of numbers
+/// with one row per line. Text files are always played with the standard rules.
+/// - path: the path of the file.
+/// - game: the game given on the command line. Required for text files.
+pub fn read_puzzle(path: &Path, game: Option<&str>) -> Result<PuzzleFile, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        let puzzle = parse_puzzle_json(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        return Ok(puzzle);
+    }
+    let Some(game) = game else {
+        return Err("--game is needed for text files".to_string());
+    };
+    let game = parse_game(game)?;
+    let (grid, size) = read_text_grid(&text)?;
+    return Ok(PuzzleFile::from_board(game, grid, size));
+}
+
+/// This function reads a square grid of numbers separated by whitespace, one row per line.
+/// - text: the text to read.
+fn read_text_grid(text: &str) -> Result<(Vec<u32>, u32), String> {
+    let mut grid: Vec<u32> = Vec::new();
+    let mut n_rows = 0;
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        for token in line.split_whitespace() {
+            let value: u32 = token
+                .parse()
+                .map_err(|_| format!("'{}' is not a number", token))?;
+            grid.push(value);
+        }
+        n_rows += 1;
+    }
+    if n_rows == 0 || grid.len() != n_rows * n_rows {
+        return Err(format!(
+            "expected a square grid but found {} cells in {} rows",
+            grid.len(),
+            n_rows
+        ));
+    }
+    return Ok((grid, n_rows as u32));
+}
+
+/// This function checks the parts of a board that don't need solving.
+/// - puzzle: the puzzle.
+fn check_board(puzzle: &PuzzleFile) -> Result<(), String> {
+    let (grid, size) = (puzzle.board(), puzzle.size);
+    let (rows, cols) = puzzle.dimensions();
+    puzzle.check_form()?;
+    match puzzle.game {
+        GameKind::Queens => {
+            let n_regions = puzzle.queens_variant().check()?;
+            let colours: HashSet<u32> = grid.iter().copied().filter(|&c| c != 0).collect();
+            if colours.len() != n_regions as usize {
+                return Err(format!(
+                    "expected {} regions but found {}",
+                    n_regions,
+                    colours.len()
+                ));
+            }
+            if !split_board_regions(grid, rows, cols).is_empty() {
+                return Err("every region must be connected".to_string());
+            }
+            return Ok(());
+        }
+        GameKind::Numbers => {
+            return validate_ruled_numbers_clues(grid, size, &puzzle.rules.numbers);
+        }
+    }
+}
+
+/// This function writes a queens board of any shape as a letter layout with `*` for each star,
+/// for boards that can't be drawn.
+/// - colour_grid: the grid of colours, with 0 for void cells.
+/// - solution: the solved grid, with 1 for each star.
+/// - cols: the number of columns.
+fn format_stars(colour_grid: &Vec<u32>, solution: &[u32], cols: u32) -> String {
+    let layout = format_queens_layout(colour_grid, cols);
+    let mut cells = solution.iter();
+    return layout
+        .chars()
+        .map(|label| match label {
+            '\n' => label,
+            _ if cells.next() == Some(&1) => '*',
+            _ => label,
+        })
+        .collect();
+}
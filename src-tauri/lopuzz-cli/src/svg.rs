@@ -0,0 +1,34 @@
+use crate::solve::read_puzzle;
+use crate::Options;
+use lopuzz_logic::svg::{render_puzzle_svg, SvgOptions};
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// This function draws a puzzle from a file as SVG, writing it to `--out` or printing it.
+/// - options: the command line options.
+pub fn run(options: &Options) -> Result<ExitCode, String> {
+    let path = Path::new(options.require("file")?);
+    let puzzle = read_puzzle(path, options.get("game"))?;
+    let svg_options = svg_options(options)?;
+    let svg = render_puzzle_svg(&puzzle, options.get("solution").is_some(), &svg_options)?;
+    match options.get("out") {
+        Some(out) => fs::write(out, svg).map_err(|e| format!("{}: {}", out, e))?,
+        None => print!("{}", svg),
+    }
+    return Ok(ExitCode::SUCCESS);
+}
+
+/// This function reads the drawing settings from `--cell-size` and `--palette`.
+/// The palette is a comma separated list of colours.
+/// - options: the command line options.
+pub fn svg_options(options: &Options) -> Result<SvgOptions, String> {
+    let mut svg_options = SvgOptions::default();
+    if let Some(cell_size) = options.number("cell-size")? {
+        svg_options.cell_size = cell_size;
+    }
+    if let Some(palette) = options.get("palette") {
+        svg_options.palette = palette.split(',').map(|c| c.trim().to_string()).collect();
+    }
+    return Ok(svg_options);
+}
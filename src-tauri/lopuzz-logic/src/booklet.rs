@@ -0,0 +1,430 @@
+use crate::drawing::crown;
+use crate::pdf::{text_width, write_pdf, Font, Page, A4_HEIGHT, A4_WIDTH};
+use crate::puzzle_file::PuzzleFile;
+use crate::rating::difficulty_name;
+use crate::svg::{parse_hex_colour, DEFAULT_PALETTE};
+use crate::utilities::GameKind;
+
+/// The number of answers drawn on each page of the solutions section.
+pub const SOLUTIONS_PER_PAGE: u32 = 9;
+
+/// The space left around the edge of every page, in points.
+const MARGIN: f32 = 48.0;
+/// The space kept at the top of every page for its heading.
+const HEADER_HEIGHT: f32 = 36.0;
+
+const BLACK: (f32, f32, f32) = (0.0, 0.0, 0.0);
+const GREY: (f32, f32, f32) = (0.55, 0.55, 0.55);
+const WHITE: (f32, f32, f32) = (1.0, 1.0, 1.0);
+/// The colour of solution values, matching the SVG export.
+const ANSWER_BLUE: (f32, f32, f32) = (0.227, 0.357, 0.749);
+
+/// This struct holds the settings for laying out a booklet.
+/// - title: the heading printed at the top of every puzzle page.
+/// - puzzles_per_page: how many puzzles go on each page, from 1 to 12.
+/// - palette: the fill for each queens region colour, reused in order if there are more colours than fills.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookletOptions {
+    pub title: String,
+    pub puzzles_per_page: u32,
+    pub palette: Vec<String>,
+}
+
+impl Default for BookletOptions {
+    fn default() -> Self {
+        return BookletOptions {
+            title: "LoPuzz puzzles".to_string(),
+            puzzles_per_page: 4,
+            palette: DEFAULT_PALETTE.iter().map(|c| c.to_string()).collect(),
+        };
+    }
+}
+
+/// This struct is where a board is drawn on the page.
+/// - x, top: the top left corner of the board.
+/// - side: the width of the board.
+struct Frame {
+    x: f32,
+    top: f32,
+    side: f32,
+}
+
+/// This struct is a puzzle ready to be laid out, with everything needed for its page and its answer.
+/// The difficulty is `None` for variants, which can't be rated.
+struct Entry<'a> {
+    puzzle: &'a PuzzleFile,
+    solution: Vec<u32>,
+    difficulty: Option<u32>,
+}
+
+/// This struct is the space on a page given to one puzzle.
+/// - x, top: the left and top edges.
+/// - width, height: the size of the space.
+struct Slot {
+    x: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+}
+
+/// This function lays out puzzles as a printable PDF booklet. The puzzles come first, numbered
+/// and labelled with their difficulty, followed by a section with the answer to each.
+/// - puzzles: the puzzles, in the order they are printed.
+/// - options: the layout settings.
+pub fn render_booklet(puzzles: &[PuzzleFile], options: &BookletOptions) -> Result<Vec<u8>, String> {
+    if puzzles.is_empty() {
+        return Err("there are no puzzles to put in the booklet".to_string());
+    }
+    if !(1..=12).contains(&options.puzzles_per_page) {
+        return Err(format!(
+            "puzzles per page must be from 1 to 12 but got {}",
+            options.puzzles_per_page
+        ));
+    }
+    let palette: Vec<(f32, f32, f32)> = options
+        .palette
+        .iter()
+        .map(|colour| {
+            let (r, g, b) = parse_hex_colour(colour)?;
+            return Ok((r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0));
+        })
+        .collect::<Result<_, String>>()?;
+
+    let mut entries: Vec<Entry> = Vec::new();
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        puzzle
+            .validate()
+            .map_err(|e| format!("puzzle {}: {}", i + 1, e))?;
+        puzzle
+            .check_drawable()
+            .map_err(|e| format!("puzzle {}: {}", i + 1, e))?;
+        let solution = puzzle.solution.clone().or_else(|| puzzle.find_solution());
+        let Some(solution) = solution else {
+            return Err(format!("puzzle {} has no solution", i + 1));
+        };
+        entries.push(Entry {
+            puzzle,
+            solution,
+            difficulty: puzzle.rate().map(|report| report.difficulty),
+        });
+    }
+
+    let mut pages: Vec<Page> = Vec::new();
+    for (page_index, chunk) in entries
+        .chunks(options.puzzles_per_page as usize)
+        .enumerate()
+    {
+        let mut page = Page::default();
+        draw_heading(&mut page, &options.title);
+        let slots = slots(options.puzzles_per_page);
+        for (i, entry) in chunk.iter().enumerate() {
+            let number = page_index * options.puzzles_per_page as usize + i + 1;
+            let name = match &entry.puzzle.metadata.title {
+                Some(title) => title.clone(),
+                None => format!(
+                    "{} {}x{}",
+                    capitalise(entry.puzzle.game.name()),
+                    entry.puzzle.size,
+                    entry.puzzle.size
+                ),
+            };
+            let label = format!("{}. {}", number, name);
+            let detail = match entry.difficulty {
+                Some(difficulty) => format!("Difficulty: {}", difficulty_name(difficulty)),
+                None => "Difficulty: unrated".to_string(),
+            };
+            draw_entry(
+                &mut page,
+                &slots[i],
+                entry,
+                &label,
+                Some(&detail),
+                false,
+                &palette,
+            );
+        }
+        pages.push(page);
+    }
+
+    let slots = slots(SOLUTIONS_PER_PAGE);
+    for (page_index, chunk) in entries.chunks(SOLUTIONS_PER_PAGE as usize).enumerate() {
+        let mut page = Page::default();
+        draw_heading(&mut page, "Solutions");
+        for (i, entry) in chunk.iter().enumerate() {
+            let number = page_index * SOLUTIONS_PER_PAGE as usize + i + 1;
+            let label = format!("{}.", number);
+            draw_entry(&mut page, &slots[i], entry, &label, None, true, &palette);
+        }
+        pages.push(page);
+    }
+
+    let n_pages = pages.len();
+    for (i, page) in pages.iter_mut().enumerate() {
+        let footer = format!("{} / {}", i + 1, n_pages);
+        let x = (A4_WIDTH - text_width(&footer, 10.0)) / 2.0;
+        page.fill_colour(GREY);
+        page.text(x, MARGIN / 2.0, Font::Helvetica, 10.0, &footer);
+    }
+    return Ok(write_pdf(&pages, A4_WIDTH, A4_HEIGHT));
+}
+
+/// This function splits the space on a page into a slot for each puzzle, filling rows from the top.
+/// Pages are taller than they are wide, so there are at least as many rows as columns.
+/// - per_page: the number of puzzles on each page.
+fn slots(per_page: u32) -> Vec<Slot> {
+    let mut rows = 1;
+    while rows * rows < per_page {
+        rows += 1;
+    }
+    let cols = per_page.div_ceil(rows);
+    let width = (A4_WIDTH - 2.0 * MARGIN) / cols as f32;
+    let height = (A4_HEIGHT - 2.0 * MARGIN - HEADER_HEIGHT) / rows as f32;
+    let mut slots = Vec::new();
+    for i in 0..per_page {
+        slots.push(Slot {
+            x: MARGIN + (i % cols) as f32 * width,
+            top: A4_HEIGHT - MARGIN - HEADER_HEIGHT - (i / cols) as f32 * height,
+            width,
+            height,
+        });
+    }
+    return slots;
+}
+
+/// This function writes the heading at the top of a page, with a rule underneath.
+/// - page: the page.
+/// - heading: the text of the heading.
+fn draw_heading(page: &mut Page, heading: &str) {
+    let baseline = A4_HEIGHT - MARGIN - 16.0;
+    page.fill_colour(BLACK);
+    page.text(MARGIN, baseline, Font::HelveticaBold, 18.0, heading);
+    page.stroke_style(BLACK, 1.0);
+    page.line(MARGIN, baseline - 8.0, A4_WIDTH - MARGIN, baseline - 8.0);
+}
+
+/// This function draws one puzzle, or its answer, in its slot with its label above.
+/// - page: the page.
+/// - slot: the space for the puzzle.
+/// - entry: the puzzle.
+/// - label: the puzzle's number and name.
+/// - detail: a smaller line under the label, if any.
+/// - answer: whether to draw the solution.
+/// - palette: the fill for each queens region colour.
+fn draw_entry(
+    page: &mut Page,
+    slot: &Slot,
+    entry: &Entry,
+    label: &str,
+    detail: Option<&str>,
+    answer: bool,
+    palette: &[(f32, f32, f32)],
+) {
+    let padding = 10.0;
+    let label_height = if detail.is_some() { 34.0 } else { 20.0 };
+    let side = (slot.width - 2.0 * padding).min(slot.height - label_height - 2.0 * padding);
+    let frame = Frame {
+        x: slot.x + (slot.width - side) / 2.0,
+        top: slot.top - padding - label_height,
+        side,
+    };
+
+    page.fill_colour(BLACK);
+    page.text(
+        frame.x,
+        slot.top - padding - 12.0,
+        Font::HelveticaBold,
+        12.0,
+        label,
+    );
+    if let Some(detail) = detail {
+        page.fill_colour(GREY);
+        page.text(
+            frame.x,
+            slot.top - padding - 26.0,
+            Font::Helvetica,
+            10.0,
+            detail,
+        );
+    }
+    let solution = if answer { Some(&entry.solution) } else { None };
+    match entry.puzzle.game {
+        GameKind::Queens => draw_queens(
+            page,
+            &frame,
+            &entry.puzzle.grid,
+            solution,
+            entry.puzzle.size,
+            palette,
+        ),
+        GameKind::Numbers => draw_numbers(
+            page,
+            &frame,
+            &entry.puzzle.givens,
+            solution,
+            entry.puzzle.size,
+        ),
+    }
+}
+
+/// This function draws a queens board with its regions filled and outlined, like the SVG export.
+/// - page: the page.
+/// - frame: where the board goes.
+/// - colour_grid: the grid of colours.
+/// - solution: the grid with 1 where each queen goes, to draw the queens, if any.
+/// - size: the size of the grid.
+/// - palette: the fill for each region colour.
+fn draw_queens(
+    page: &mut Page,
+    frame: &Frame,
+    colour_grid: &Vec<u32>,
+    solution: Option<&Vec<u32>>,
+    size: u32,
+    palette: &[(f32, f32, f32)],
+) {
+    let (x, top, side) = (frame.x, frame.top, frame.side);
+    let cell = side / size as f32;
+    let colour = |row: u32, col: u32| colour_grid[(row * size + col) as usize];
+    for row in 0..size {
+        for col in 0..size {
+            let fill = if palette.is_empty() {
+                WHITE
+            } else {
+                palette[(colour(row, col).max(1) - 1) as usize % palette.len()]
+            };
+            page.fill_colour(fill);
+            page.fill_rect(
+                x + col as f32 * cell,
+                top - (row + 1) as f32 * cell,
+                cell,
+                cell,
+            );
+        }
+    }
+    draw_thin_lines(page, frame, size);
+
+    // thick lines wherever neighbouring cells are in different regions
+    page.stroke_style(BLACK, (cell * 0.06).max(1.5));
+    for row in 0..size {
+        for col in 0..size {
+            let left = x + col as f32 * cell;
+            let bottom = top - (row + 1) as f32 * cell;
+            if col + 1 < size && colour(row, col) != colour(row, col + 1) {
+                page.line(left + cell, bottom, left + cell, bottom + cell);
+            }
+            if row + 1 < size && colour(row, col) != colour(row + 1, col) {
+                page.line(left, bottom, left + cell, bottom);
+            }
+        }
+    }
+    page.stroke_style(BLACK, (cell * 0.08).max(2.0));
+    page.stroke_rect(x, top - side, side, side);
+
+    if let Some(solution) = solution {
+        page.fill_colour(BLACK);
+        for index in 0..(size * size) {
+            if solution[index as usize] == 1 {
+                let left = x + (index % size) as f32 * cell;
+                let bottom = top - (index / size + 1) as f32 * cell;
+                // the standard PDF fonts have no chess pieces, so the queen is drawn as a shape
+                page.fill_polygon(&crown(left, bottom, cell, 1.0));
+            }
+        }
+    }
+}
+
+/// This function draws a numbers board. Givens are bold and solution values are blue, like the SVG export.
+/// - page: the page.
+/// - frame: where the board goes.
+/// - givens: the grid of givens, with 0 for empty cells.
+/// - solution: the solved grid, to fill in the empty cells, if any.
+/// - size: the size of the grid.
+fn draw_numbers(
+    page: &mut Page,
+    frame: &Frame,
+    givens: &Vec<u32>,
+    solution: Option<&Vec<u32>>,
+    size: u32,
+) {
+    let (x, top, side) = (frame.x, frame.top, frame.side);
+    let cell = side / size as f32;
+    page.fill_colour(WHITE);
+    page.fill_rect(x, top - side, side, side);
+    draw_thin_lines(page, frame, size);
+    page.stroke_style(BLACK, (cell * 0.08).max(2.0));
+    page.stroke_rect(x, top - side, side, side);
+
+    let font_size = cell * 0.55;
+    for index in 0..(size * size) as usize {
+        let (text, font, colour) = if givens[index] != 0 {
+            (givens[index].to_string(), Font::HelveticaBold, BLACK)
+        } else if let Some(solution) = solution {
+            (solution[index].to_string(), Font::Helvetica, ANSWER_BLUE)
+        } else {
+            continue;
+        };
+        let centre_x = x + ((index as u32 % size) as f32 + 0.5) * cell;
+        let centre_y = top - ((index as u32 / size) as f32 + 0.5) * cell;
+        page.fill_colour(colour);
+        page.text(
+            centre_x - text_width(&text, font_size) / 2.0,
+            // digits sit about 0.36 of the font size above the middle of their baseline
+            centre_y - font_size * 0.36,
+            font,
+            font_size,
+            &text,
+        );
+    }
+}
+
+/// This function draws a thin line along every edge between cells.
+/// - page: the page.
+/// - frame: where the board goes.
+/// - size: the size of the grid.
+fn draw_thin_lines(page: &mut Page, frame: &Frame, size: u32) {
+    let (x, top, side) = (frame.x, frame.top, frame.side);
+    let cell = side / size as f32;
+    page.stroke_style(GREY, 0.5);
+    for i in 1..size {
+        let offset = i as f32 * cell;
+        page.line(x + offset, top, x + offset, top - side);
+        page.line(x, top - offset, x + side, top - offset);
+    }
+}
+
+/// This function capitalises the first letter of a word.
+/// - word: the word.
+fn capitalise(word: &str) -> String {
+    let mut chars = word.chars();
+    return match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_booklet_pages() {
+        let colour_grid: Vec<u32> = vec![
+            5, 5, 5, 2, 2, //
+            5, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 4, 4, 1, 2,
+        ];
+        let puzzles = vec![PuzzleFile::queens(colour_grid, 5); 5];
+        let options = BookletOptions {
+            puzzles_per_page: 2,
+            ..BookletOptions::default()
+        };
+        let pdf = String::from_utf8(render_booklet(&puzzles, &options).unwrap()).unwrap();
+        // three pages of puzzles, then one page holds all five answers
+        assert!(pdf.contains("/Count 4"));
+        assert!(pdf.contains("(5. Queens 5x5) Tj"));
+        assert!(pdf.contains("(Solutions) Tj"));
+        assert!(pdf.contains("(4 / 4) Tj"));
+        assert!(render_booklet(&[], &options).is_err());
+    }
+}
@@ -0,0 +1,457 @@
+use crate::numbers::{check_solution_ruled_numbers, generate_ruled_numbers_grid, NumbersRules};
+use crate::queens::BoardShape;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The sizes a new cage can grow to, with the more common sizes listed more often.
+const CAGE_SIZES: [usize; 7] = [1, 2, 2, 2, 3, 3, 4];
+
+/// This enum is the arithmetic a cage's values must give its target.
+/// - Add: the values sum to the target. Single cell cages are just their value.
+/// - Subtract: the larger of two values take the smaller is the target.
+/// - Multiply: the values multiply to the target.
+/// - Divide: the larger of two values over the smaller is the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operation {
+    /// This function gives the symbol shown next to a cage's target.
+    pub fn symbol(&self) -> char {
+        match self {
+            Operation::Add => return '+',
+            Operation::Subtract => return '−',
+            Operation::Multiply => return '×',
+            Operation::Divide => return '÷',
+        }
+    }
+}
+
+/// This struct is a cage of a KenKen-style numbers game.
+/// - cells: the indices of the cells in the cage.
+/// - operation: the arithmetic the values must give the target with.
+/// - target: the number the values must give.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cage {
+    pub cells: Vec<u32>,
+    pub operation: Operation,
+    pub target: u32,
+}
+
+impl Cage {
+    /// This function makes the clue for a group of cells from the values of a full grid, picking
+    /// an operation that suits the values.
+    /// - cells: the indices of the cells in the cage.
+    /// - grid: the full grid.
+    /// - rng: the random number generator to use.
+    pub fn from_grid<R: Rng>(cells: Vec<u32>, grid: &Vec<u32>, rng: &mut R) -> Cage {
+        let values: Vec<u32> = cells.iter().map(|&cell| grid[cell as usize]).collect();
+        let operation = match values.len() {
+            1 => Operation::Add,
+            2 => {
+                let (big, small) = (values[0].max(values[1]), values[0].min(values[1]));
+                if big.is_multiple_of(small) && rng.random_bool(0.5) {
+                    Operation::Divide
+                } else {
+                    *[Operation::Add, Operation::Subtract, Operation::Multiply]
+                        .choose(rng)
+                        .unwrap()
+                }
+            }
+            _ => {
+                if rng.random_bool(0.5) {
+                    Operation::Add
+                } else {
+                    Operation::Multiply
+                }
+            }
+        };
+        let target = match operation {
+            Operation::Add => values.iter().sum(),
+            Operation::Subtract => values[0].abs_diff(values[1]),
+            Operation::Multiply => values.iter().product(),
+            Operation::Divide => values[0].max(values[1]) / values[0].min(values[1]),
+        };
+        return Cage {
+            cells,
+            operation,
+            target,
+        };
+    }
+
+    /// This function checks if the full set of values in the cage gives the target.
+    /// - values: the value of each cell of the cage.
+    pub fn holds(&self, values: &[u32]) -> bool {
+        match self.operation {
+            Operation::Add => return values.iter().sum::<u32>() == self.target,
+            Operation::Multiply => return values.iter().product::<u32>() == self.target,
+            Operation::Subtract => {
+                return values.len() == 2 && values[0].abs_diff(values[1]) == self.target
+            }
+            Operation::Divide => {
+                if values.len() != 2 {
+                    return false;
+                }
+                let (big, small) = (values[0].max(values[1]), values[0].min(values[1]));
+                return small != 0 && big == small * self.target;
+            }
+        }
+    }
+
+    /// This function checks if a partly filled cage could still give the target.
+    /// - values: the value of each cell of the cage, with 0 for empty cells.
+    /// - size: the size of the grid, which is the largest value allowed.
+    pub fn could_hold(&self, values: &[u32], size: u32) -> bool {
+        let filled: Vec<u32> = values.iter().copied().filter(|&value| value != 0).collect();
+        let empty = (values.len() - filled.len()) as u32;
+        if empty == 0 {
+            return self.holds(values);
+        }
+        match self.operation {
+            Operation::Add => {
+                let sum: u32 = filled.iter().sum();
+                return sum + empty <= self.target && sum + empty * size >= self.target;
+            }
+            Operation::Multiply => {
+                return self.target.is_multiple_of(filled.iter().product::<u32>())
+            }
+            // two cell cages can't be ruled out until both are filled
+            Operation::Subtract | Operation::Divide => return true,
+        }
+    }
+}
+
+/// This function splits a grid into random connected cages of one to four cells.
+/// - size: the size of the grid.
+/// - rng: the random number generator to use.
+pub fn partition_cages<R: Rng>(size: u32, rng: &mut R) -> Vec<Vec<u32>> {
+    let shape = BoardShape::square(size);
+    let mut cells: Vec<usize> = (0..(size * size) as usize).collect();
+    cells.shuffle(rng);
+    let mut taken = vec![false; cells.len()];
+    let mut cages: Vec<Vec<u32>> = Vec::new();
+    for start in cells {
+        if taken[start] {
+            continue;
+        }
+        let cage_size = *CAGE_SIZES.choose(rng).unwrap();
+        taken[start] = true;
+        let mut cage = vec![start];
+        while cage.len() < cage_size {
+            let frontier: Vec<usize> = cage
+                .iter()
+                .flat_map(|&cell| shape.neighbours(cell))
+                .filter(|&next| !taken[next])
+                .collect();
+            let Some(&next) = frontier.choose(rng) else {
+                break;
+            };
+            taken[next] = true;
+            cage.push(next);
+        }
+        cages.push(cage.into_iter().map(|cell| cell as u32).collect());
+    }
+    return cages;
+}
+
+/// This function generates a KenKen-style numbers game: a full grid under the rules, split into
+/// cages with arithmetic clues that have exactly one solution. Cages are broken up until the
+/// solution is unique.
+/// - size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value.
+/// - rng: the random number generator to use.
+pub fn generate_cage_game<R: Rng>(
+    size: u32,
+    rules: &NumbersRules,
+    rng: &mut R,
+) -> Result<Vec<Cage>, String> {
+    rules.validate(size)?;
+    let grid = generate_ruled_numbers_grid(size, rules, rng);
+    if grid.contains(&0) {
+        return Err(format!(
+            "there is no {}x{} grid that meets these rules",
+            size, size
+        ));
+    }
+    let mut cages: Vec<Cage> = partition_cages(size, rng)
+        .into_iter()
+        .map(|cells| Cage::from_grid(cells, &grid, rng))
+        .collect();
+    loop {
+        let solutions = cage_solutions(&cages, size, rules, 2);
+        // the grid itself is always a solution, so any other one shows where the cages fall short
+        let Some(other) = solutions.iter().find(|&solution| *solution != grid) else {
+            return Ok(cages);
+        };
+        let cell = (0..grid.len())
+            .find(|&index| other[index] != grid[index])
+            .unwrap() as u32;
+        let position = cages
+            .iter()
+            .position(|cage| cage.cells.contains(&cell))
+            .unwrap();
+        let cage = cages.swap_remove(position);
+        let rest: Vec<u32> = cage
+            .cells
+            .into_iter()
+            .filter(|&other| other != cell)
+            .collect();
+        for part in connected_parts(&rest, size) {
+            cages.push(Cage::from_grid(part, &grid, rng));
+        }
+        cages.push(Cage::from_grid(vec![cell], &grid, rng));
+    }
+}
+
+/// This function splits a group of cells into the parts that are connected to one another.
+/// - cells: the indices of the cells.
+/// - size: the size of the grid.
+fn connected_parts(cells: &Vec<u32>, size: u32) -> Vec<Vec<u32>> {
+    let shape = BoardShape::square(size);
+    let mut left: Vec<u32> = cells.clone();
+    let mut parts: Vec<Vec<u32>> = Vec::new();
+    while let Some(start) = left.pop() {
+        let mut part = vec![start];
+        let mut next = 0;
+        while next < part.len() {
+            for neighbour in shape.neighbours(part[next] as usize) {
+                if let Some(position) = left.iter().position(|&cell| cell as usize == neighbour) {
+                    part.push(left.swap_remove(position));
+                }
+            }
+            next += 1;
+        }
+        parts.push(part);
+    }
+    return parts;
+}
+
+/// This function checks that the cages cover every cell of the grid once, are connected, and
+/// have clues that can be met.
+/// - cages: the cages of the game.
+/// - size: the size of the grid.
+pub fn validate_cages(cages: &Vec<Cage>, size: u32) -> Result<(), String> {
+    let mut seen = vec![false; (size * size) as usize];
+    for (number, cage) in cages.iter().enumerate() {
+        if cage.cells.is_empty() || cage.target == 0 {
+            return Err(format!("cage {} has no cells or no target", number + 1));
+        }
+        let pairs_only = matches!(cage.operation, Operation::Subtract | Operation::Divide);
+        if pairs_only && cage.cells.len() != 2 {
+            return Err(format!(
+                "cage {} uses {} but has {} cells instead of 2",
+                number + 1,
+                cage.operation.symbol(),
+                cage.cells.len()
+            ));
+        }
+        for &cell in &cage.cells {
+            if cell >= size * size || seen[cell as usize] {
+                return Err(format!(
+                    "cell {} is outside the grid or in more than one cage",
+                    cell
+                ));
+            }
+            seen[cell as usize] = true;
+        }
+        if connected_parts(&cage.cells, size).len() != 1 {
+            return Err(format!("cage {} must be connected", number + 1));
+        }
+    }
+    if let Some(cell) = seen.iter().position(|&seen| !seen) {
+        return Err(format!("cell {} isn't in a cage", cell));
+    }
+    return Ok(());
+}
+
+/// This function counts the ways the cages can be filled under the rules, stopping once the
+/// limit is reached.
+/// - cages: the cages of the game.
+/// - size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value.
+/// - limit: the number of solutions after which we stop looking.
+pub fn count_cage_solutions(cages: &Vec<Cage>, size: u32, rules: &NumbersRules, limit: u32) -> u32 {
+    return cage_solutions(cages, size, rules, limit).len() as u32;
+}
+
+/// This function finds a way the cages can be filled under the rules.
+/// - cages: the cages of the game.
+/// - size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value.
+pub fn find_cage_solution(cages: &Vec<Cage>, size: u32, rules: &NumbersRules) -> Option<Vec<u32>> {
+    return cage_solutions(cages, size, rules, 1).pop();
+}
+
+/// This function finds up to `limit` ways the cages can be filled under the rules.
+/// - cages: the cages of the game.
+/// - size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value.
+/// - limit: the number of solutions after which we stop looking.
+fn cage_solutions(cages: &Vec<Cage>, size: u32, rules: &NumbersRules, limit: u32) -> Vec<Vec<u32>> {
+    if rules.validate(size).is_err() {
+        return Vec::new();
+    }
+    let mut cage_of = vec![0; (size * size) as usize];
+    for (number, cage) in cages.iter().enumerate() {
+        for &cell in &cage.cells {
+            cage_of[cell as usize] = number;
+        }
+    }
+    let mut search = CageSearch {
+        size: size as usize,
+        peers: rules.peers(size as usize),
+        cages,
+        cage_of,
+        grid: vec![0; (size * size) as usize],
+        limit: limit as usize,
+        solutions: Vec::new(),
+    };
+    search.search();
+    return search.solutions;
+}
+
+/// This struct holds the state of a search for the ways to fill a grid of cages.
+/// - size: the size of the grid.
+/// - peers: the cells linked to each cell.
+/// - cages: the cages of the game.
+/// - cage_of: the cage each cell is in.
+/// - grid: the values placed so far, with 0 for empty cells.
+/// - limit: the number of solutions after which we stop looking.
+/// - solutions: the full grids found so far.
+struct CageSearch<'a> {
+    size: usize,
+    peers: Vec<Vec<usize>>,
+    cages: &'a Vec<Cage>,
+    cage_of: Vec<usize>,
+    grid: Vec<u32>,
+    limit: usize,
+    solutions: Vec<Vec<u32>>,
+}
+
+impl CageSearch<'_> {
+    /// This function finds the values a cell can take without repeating along a line or breaking
+    /// its cage.
+    /// - index: the cell.
+    fn options(&mut self, index: usize) -> Vec<u32> {
+        let mut options: Vec<u32> = Vec::new();
+        let cage = &self.cages[self.cage_of[index]];
+        for value in 1..=self.size as u32 {
+            if self.peers[index]
+                .iter()
+                .any(|&other| self.grid[other] == value)
+            {
+                continue;
+            }
+            self.grid[index] = value;
+            let values: Vec<u32> = cage
+                .cells
+                .iter()
+                .map(|&cell| self.grid[cell as usize])
+                .collect();
+            if cage.could_hold(&values, self.size as u32) {
+                options.push(value);
+            }
+        }
+        self.grid[index] = 0;
+        return options;
+    }
+
+    /// This function recursively fills the empty cell with the fewest options.
+    fn search(&mut self) {
+        if self.solutions.len() >= self.limit {
+            return;
+        }
+        let mut best: Option<(usize, Vec<u32>)> = None;
+        for index in 0..self.grid.len() {
+            if self.grid[index] != 0 {
+                continue;
+            }
+            let options = self.options(index);
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_options)| options.len() < best_options.len())
+            {
+                let dead_end = options.is_empty();
+                best = Some((index, options));
+                if dead_end {
+                    break;
+                }
+            }
+        }
+        let Some((index, options)) = best else {
+            self.solutions.push(self.grid.clone());
+            return;
+        };
+        for value in options {
+            self.grid[index] = value;
+            self.search();
+        }
+        self.grid[index] = 0;
+    }
+}
+
+/// This function checks if a full grid is a solution to a KenKen-style numbers game.
+/// - numbers_grid: the grid of numbers.
+/// - cages: the cages of the game.
+/// - size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value.
+pub fn check_cage_solution(
+    numbers_grid: &[u32],
+    cages: &Vec<Cage>,
+    size: u32,
+    rules: &NumbersRules,
+) -> bool {
+    if validate_cages(cages, size).is_err()
+        || !check_solution_ruled_numbers(numbers_grid, size, rules)
+    {
+        return false;
+    }
+    return cages.iter().all(|cage| {
+        let values: Vec<u32> = cage
+            .cells
+            .iter()
+            .map(|&cell| numbers_grid[cell as usize])
+            .collect();
+        return cage.holds(&values);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_cage_clues() {
+        let cage = |operation, target| Cage {
+            cells: vec![0, 1],
+            operation,
+            target,
+        };
+        assert!(cage(Operation::Subtract, 2).holds(&[1, 3]));
+        assert!(cage(Operation::Divide, 3).holds(&[6, 2]));
+        assert!(!cage(Operation::Divide, 3).holds(&[5, 2]));
+        assert!(cage(Operation::Add, 9).could_hold(&[4, 0], 5));
+        assert!(!cage(Operation::Add, 9).could_hold(&[3, 0], 5));
+        assert!(!cage(Operation::Multiply, 10).could_hold(&[3, 0], 5));
+    }
+
+    #[test]
+    fn test_generate_cage_game() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for (size, rules) in [(5, NumbersRules::default()), (6, NumbersRules::Latin)] {
+            let cages = generate_cage_game(size, &rules, &mut rng).unwrap();
+            assert_eq!(validate_cages(&cages, size), Ok(()));
+            let solutions = cage_solutions(&cages, size, &rules, 2);
+            assert_eq!(solutions.len(), 1);
+            assert!(check_cage_solution(&solutions[0], &cages, size, &rules));
+        }
+    }
+}
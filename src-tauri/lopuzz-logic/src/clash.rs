@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+/// This enum is the set of cells a queen stops other queens from going in, on top of its own
+/// row, column and region.
+/// - King: the eight cells around it, as in the usual queens puzzle.
+/// - Queen: every cell along its diagonals, like a chess queen.
+/// - Knight: the cells a knight's move away, instead of the cells around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Attack {
+    #[default]
+    King,
+    Queen,
+    Knight,
+}
+
+/// This struct holds the rules for which queens clash with one another on a square board.
+/// Two queens always clash if they share a region, and unless `free_lines` is set, a row or column.
+/// - attack: the cells each queen attacks besides its row and column.
+/// - wrap: whether the board wraps around at the edges, so the last row is next to the first
+///   and the last column is next to the first.
+/// - free_lines: whether queens may share a row or column, so only regions and attacks keep
+///   them apart. There is still one queen in each region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClashRules {
+    pub attack: Attack,
+    pub wrap: bool,
+    pub free_lines: bool,
+}
+
+impl ClashRules {
+    /// This function checks if a queen on one cell attacks a queen on another, leaving aside
+    /// rows, columns and regions.
+    /// - cell1: the first cell (row, col).
+    /// - cell2: the second cell (row, col).
+    /// - size: the size of the grid.
+    pub fn attacks(&self, cell1: (u32, u32), cell2: (u32, u32), size: u32) -> bool {
+        let mut rows_apart = cell1.0.abs_diff(cell2.0);
+        let mut cols_apart = cell1.1.abs_diff(cell2.1);
+        if self.wrap {
+            // going round the other way may be shorter
+            rows_apart = rows_apart.min(size - rows_apart);
+            cols_apart = cols_apart.min(size - cols_apart);
+        }
+        match self.attack {
+            Attack::King => return rows_apart <= 1 && cols_apart <= 1,
+            Attack::Queen => return rows_apart == cols_apart,
+            Attack::Knight => {
+                return (rows_apart == 1 && cols_apart == 2) || (rows_apart == 2 && cols_apart == 1)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clash_rules() {
+        let king = ClashRules::default();
+        assert!(king.attacks((2, 2), (3, 3), 6));
+        assert!(!king.attacks((2, 2), (4, 4), 6));
+        let queen = ClashRules {
+            attack: Attack::Queen,
+            ..king
+        };
+        assert!(queen.attacks((2, 2), (5, 5), 6));
+        assert!(!queen.attacks((2, 2), (3, 4), 6));
+        let knight = ClashRules {
+            attack: Attack::Knight,
+            ..king
+        };
+        // a knight's move clashes, but touching diagonally doesn't
+        assert!(knight.attacks((2, 2), (3, 4), 6));
+        assert!(!knight.attacks((2, 2), (3, 3), 6));
+        // with wrapping, the corners of the board touch
+        let torus = ClashRules { wrap: true, ..king };
+        assert!(torus.attacks((0, 0), (5, 5), 6));
+        assert!(!king.attacks((0, 0), (5, 5), 6));
+        // freeing the rows and columns doesn't change what a queen attacks
+        let free = ClashRules {
+            free_lines: true,
+            ..king
+        };
+        assert!(!free.attacks((0, 0), (0, 2), 6));
+    }
+}
@@ -0,0 +1,23 @@
+/// The corners of the crown drawn for a queen where there is no queen character to use,
+/// in a unit cell with y going up, going round from the bottom left.
+pub const CROWN_OUTLINE: [(f32, f32); 7] = [
+    (0.22, 0.25),
+    (0.78, 0.25),
+    (0.82, 0.72),
+    (0.65, 0.5),
+    (0.5, 0.78),
+    (0.35, 0.5),
+    (0.18, 0.72),
+];
+
+/// This function gives the outline of a crown filling the middle of a cell, used to mark a queen.
+/// - left: the left edge of the cell.
+/// - base: the edge of the cell the crown stands on.
+/// - cell: the width of the cell.
+/// - up: the way y goes from the base, 1.0 for pages with y going up and -1.0 for images.
+pub fn crown(left: f32, base: f32, cell: f32, up: f32) -> Vec<(f32, f32)> {
+    return CROWN_OUTLINE
+        .iter()
+        .map(|(u, v)| (left + u * cell, base + up * v * cell))
+        .collect();
+}
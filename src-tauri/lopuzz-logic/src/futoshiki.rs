@@ -0,0 +1,394 @@
+use crate::numbers::{check_solution_ruled_numbers, generate_ruled_numbers_grid, NumbersRules};
+use crate::queens::BoardShape;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The most cells the uniqueness check may fill when deciding if a clue can be taken out. Past
+/// this the clue is kept.
+const MAX_REMOVAL_STEPS: u32 = 5_000;
+
+/// This struct is a `<` clue between two cells next to each other.
+/// - smaller: the index of the cell with the smaller value.
+/// - larger: the index of the cell with the larger value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Inequality {
+    pub smaller: u32,
+    pub larger: u32,
+}
+
+/// This struct is a Futoshiki puzzle: a numbers game with inequality clues between cells.
+/// - givens: the grid of givens, with 0 for empty cells.
+/// - inequalities: the inequality clues.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FutoshikiBoard {
+    pub givens: Vec<u32>,
+    pub inequalities: Vec<Inequality>,
+}
+
+/// This function generates a Futoshiki puzzle with a unique solution. It starts from every
+/// inequality of a full grid, then takes out each inequality and given that isn't needed, so
+/// none of those left can be taken out on its own.
+/// - size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value.
+/// - rng: the random number generator to use.
+pub fn generate_futoshiki<R: Rng>(
+    size: u32,
+    rules: &NumbersRules,
+    rng: &mut R,
+) -> Result<FutoshikiBoard, String> {
+    rules.validate(size)?;
+    let grid = generate_ruled_numbers_grid(size, rules, rng);
+    if grid.contains(&0) {
+        return Err(format!(
+            "there is no {}x{} grid that meets these rules",
+            size, size
+        ));
+    }
+    let peers = rules.peers(size as usize);
+    let mut board = FutoshikiBoard {
+        givens: vec![0; grid.len()],
+        inequalities: all_inequalities(&grid, size),
+    };
+    // every inequality may still leave some freedom, so give the cells that differ
+    loop {
+        let solutions = futoshiki_solutions(&board, size, &peers, 2, u32::MAX).0;
+        let Some(other) = solutions.iter().find(|&solution| *solution != grid) else {
+            break;
+        };
+        let cell = (0..grid.len())
+            .find(|&index| other[index] != grid[index])
+            .unwrap();
+        board.givens[cell] = grid[cell];
+    }
+
+    board.inequalities.shuffle(rng);
+    let mut index = 0;
+    while index < board.inequalities.len() {
+        let inequality = board.inequalities.remove(index);
+        if !is_unique(&board, size, &peers) {
+            board.inequalities.insert(index, inequality);
+            index += 1;
+        }
+    }
+    let mut cells: Vec<usize> = (0..grid.len())
+        .filter(|&cell| board.givens[cell] != 0)
+        .collect();
+    cells.shuffle(rng);
+    for cell in cells {
+        board.givens[cell] = 0;
+        if !is_unique(&board, size, &peers) {
+            board.givens[cell] = grid[cell];
+        }
+    }
+    return Ok(board);
+}
+
+/// This function finds the inequality between every pair of cells next to each other.
+/// - grid: the full grid.
+/// - size: the size of the grid.
+fn all_inequalities(grid: &Vec<u32>, size: u32) -> Vec<Inequality> {
+    let mut inequalities: Vec<Inequality> = Vec::new();
+    for (index, other) in BoardShape::square(size).edges() {
+        let (smaller, larger) = if grid[index as usize] < grid[other as usize] {
+            (index, other)
+        } else {
+            (other, index)
+        };
+        inequalities.push(Inequality { smaller, larger });
+    }
+    return inequalities;
+}
+
+/// This function checks if the puzzle can be shown to have exactly one solution within the
+/// removal step budget.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - peers: the cells linked to each cell.
+fn is_unique(board: &FutoshikiBoard, size: u32, peers: &Vec<Vec<usize>>) -> bool {
+    let (solutions, finished) = futoshiki_solutions(board, size, peers, 2, MAX_REMOVAL_STEPS);
+    return finished && solutions.len() == 1;
+}
+
+/// This function counts the ways the puzzle can be completed under the rules, stopping once the
+/// limit is reached.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value.
+/// - limit: the number of solutions after which we stop looking.
+pub fn count_futoshiki_solutions(
+    board: &FutoshikiBoard,
+    size: u32,
+    rules: &NumbersRules,
+    limit: u32,
+) -> u32 {
+    if rules.validate(size).is_err() {
+        return 0;
+    }
+    let peers = rules.peers(size as usize);
+    return futoshiki_solutions(board, size, &peers, limit, u32::MAX)
+        .0
+        .len() as u32;
+}
+
+/// This function finds a way the puzzle can be completed under the rules.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value.
+pub fn find_futoshiki_solution(
+    board: &FutoshikiBoard,
+    size: u32,
+    rules: &NumbersRules,
+) -> Option<Vec<u32>> {
+    if rules.validate(size).is_err() {
+        return None;
+    }
+    let peers = rules.peers(size as usize);
+    return futoshiki_solutions(board, size, &peers, 1, u32::MAX)
+        .0
+        .pop();
+}
+
+/// This function finds up to `limit` ways the puzzle can be completed, and whether the search
+/// finished within the steps it was given.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - peers: the cells linked to each cell.
+/// - limit: the number of solutions after which we stop looking.
+/// - steps: the most cells the search may fill.
+fn futoshiki_solutions(
+    board: &FutoshikiBoard,
+    size: u32,
+    peers: &Vec<Vec<usize>>,
+    limit: u32,
+    steps: u32,
+) -> (Vec<Vec<u32>>, bool) {
+    let cells = board.givens.len();
+    let mut below: Vec<Vec<usize>> = vec![Vec::new(); cells];
+    let mut above: Vec<Vec<usize>> = vec![Vec::new(); cells];
+    for inequality in &board.inequalities {
+        below[inequality.larger as usize].push(inequality.smaller as usize);
+        above[inequality.smaller as usize].push(inequality.larger as usize);
+    }
+    // a cell at the top of a chain of k cells must be at least k, and one at the bottom at most
+    // size - k + 1. Capping the bounds means a loop of inequalities ends with no options.
+    let (mut lowest, mut highest) = (vec![1; cells], vec![size; cells]);
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for inequality in &board.inequalities {
+            let (smaller, larger) = (inequality.smaller as usize, inequality.larger as usize);
+            if lowest[larger] <= lowest[smaller] && lowest[larger] <= size {
+                lowest[larger] = lowest[smaller] + 1;
+                changed = true;
+            }
+            if highest[smaller] >= highest[larger] && highest[smaller] > 0 {
+                highest[smaller] = highest[larger].saturating_sub(1);
+                changed = true;
+            }
+        }
+    }
+    let mut search = FutoshikiSearch {
+        peers,
+        below,
+        above,
+        lowest,
+        highest,
+        grid: board.givens.clone(),
+        limit: limit as usize,
+        solutions: Vec::new(),
+        steps_left: steps,
+    };
+    search.search();
+    return (search.solutions, search.steps_left > 0);
+}
+
+/// This struct holds the state of a search for the ways to complete a Futoshiki puzzle.
+/// - peers: the cells linked to each cell.
+/// - below: the cells that must be smaller than each cell.
+/// - above: the cells that must be larger than each cell.
+/// - lowest: the smallest value each cell can take, from the chains of inequalities below it.
+/// - highest: the largest value each cell can take, from the chains of inequalities above it.
+/// - grid: the values placed so far, with 0 for empty cells.
+/// - limit: the number of solutions after which we stop looking.
+/// - solutions: the full grids found so far.
+/// - steps_left: the number of cells that can still be filled.
+struct FutoshikiSearch<'a> {
+    peers: &'a Vec<Vec<usize>>,
+    below: Vec<Vec<usize>>,
+    above: Vec<Vec<usize>>,
+    lowest: Vec<u32>,
+    highest: Vec<u32>,
+    grid: Vec<u32>,
+    limit: usize,
+    solutions: Vec<Vec<u32>>,
+    steps_left: u32,
+}
+
+impl FutoshikiSearch<'_> {
+    /// This function finds the values a cell can take without repeating along a line or breaking
+    /// an inequality.
+    /// - index: the cell.
+    fn options(&self, index: usize) -> Vec<u32> {
+        // bit `value` is set for each value a linked cell already has
+        let used = self.peers[index]
+            .iter()
+            .fold(0u64, |used, &other| used | (1 << self.grid[other]));
+        let mut low = self.lowest[index];
+        for &other in &self.below[index] {
+            low = low.max(self.grid[other] + 1);
+        }
+        let mut high = self.highest[index];
+        for &other in &self.above[index] {
+            if self.grid[other] != 0 {
+                high = high.min(self.grid[other] - 1);
+            }
+        }
+        return (low..=high)
+            .filter(|&value| used & (1 << value) == 0)
+            .collect();
+    }
+
+    /// This function recursively fills the empty cell with the fewest options.
+    fn search(&mut self) {
+        if self.solutions.len() >= self.limit || self.steps_left == 0 {
+            return;
+        }
+        self.steps_left -= 1;
+        let mut best: Option<(usize, Vec<u32>)> = None;
+        for index in 0..self.grid.len() {
+            if self.grid[index] != 0 {
+                continue;
+            }
+            let options = self.options(index);
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_options)| options.len() < best_options.len())
+            {
+                let dead_end = options.is_empty();
+                best = Some((index, options));
+                if dead_end {
+                    break;
+                }
+            }
+        }
+        let Some((index, options)) = best else {
+            self.solutions.push(self.grid.clone());
+            return;
+        };
+        for value in options {
+            self.grid[index] = value;
+            self.search();
+        }
+        self.grid[index] = 0;
+    }
+}
+
+/// This function checks that every inequality is between two cells of the grid next to each other.
+/// - inequalities: the inequality clues.
+/// - size: the size of the grid.
+pub fn validate_inequalities(inequalities: &Vec<Inequality>, size: u32) -> Result<(), String> {
+    let shape = BoardShape::square(size);
+    for inequality in inequalities {
+        shape.check_edge(inequality.smaller, inequality.larger)?;
+    }
+    return Ok(());
+}
+
+/// This function finds the inequalities a grid breaks, so they can be shown to the player.
+/// Empty cells don't break any inequality.
+/// - numbers_grid: the grid of numbers, with 0 for empty cells.
+/// - inequalities: the inequality clues.
+pub fn violated_inequalities(
+    numbers_grid: &[u32],
+    inequalities: &Vec<Inequality>,
+) -> Vec<Inequality> {
+    return inequalities
+        .iter()
+        .filter(|inequality| {
+            let smaller = numbers_grid[inequality.smaller as usize];
+            let larger = numbers_grid[inequality.larger as usize];
+            return smaller != 0 && larger != 0 && smaller >= larger;
+        })
+        .copied()
+        .collect();
+}
+
+/// This function checks if a full grid is a solution to a Futoshiki puzzle.
+/// - numbers_grid: the grid of numbers.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value.
+pub fn check_futoshiki_solution(
+    numbers_grid: &[u32],
+    board: &FutoshikiBoard,
+    size: u32,
+    rules: &NumbersRules,
+) -> bool {
+    if validate_inequalities(&board.inequalities, size).is_err()
+        || board.givens.len() != numbers_grid.len()
+        || !check_solution_ruled_numbers(numbers_grid, size, rules)
+    {
+        return false;
+    }
+    let givens_kept = board
+        .givens
+        .iter()
+        .zip(numbers_grid)
+        .all(|(&given, &value)| given == 0 || given == value);
+    return givens_kept && violated_inequalities(numbers_grid, &board.inequalities).is_empty();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_generate_futoshiki() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let rules = NumbersRules::Latin;
+        let board = generate_futoshiki(5, &rules, &mut rng).unwrap();
+        assert_eq!(validate_inequalities(&board.inequalities, 5), Ok(()));
+        let peers = rules.peers(5);
+        let (solutions, _) = futoshiki_solutions(&board, 5, &peers, 2, u32::MAX);
+        assert_eq!(solutions.len(), 1);
+        assert!(check_futoshiki_solution(&solutions[0], &board, 5, &rules));
+        // every clue left is needed, so taking any one out leaves more than one solution
+        for index in 0..board.inequalities.len() {
+            let mut fewer = board.clone();
+            fewer.inequalities.remove(index);
+            assert!(!is_unique(&fewer, 5, &peers));
+        }
+        for cell in (0..25).filter(|&cell| board.givens[cell] != 0) {
+            let mut fewer = board.clone();
+            fewer.givens[cell] = 0;
+            assert!(!is_unique(&fewer, 5, &peers));
+        }
+    }
+
+    #[test]
+    fn test_violated_inequalities() {
+        let inequalities = vec![
+            Inequality {
+                smaller: 0,
+                larger: 1,
+            },
+            Inequality {
+                smaller: 2,
+                larger: 3,
+            },
+        ];
+        let grid = vec![2, 1, 0, 1];
+        assert_eq!(
+            violated_inequalities(&grid, &inequalities),
+            vec![inequalities[0]]
+        );
+        let far = vec![Inequality {
+            smaller: 0,
+            larger: 3,
+        }];
+        assert!(validate_inequalities(&far, 2).is_err());
+    }
+}
@@ -0,0 +1,323 @@
+use crate::numbers::{generate_ruled_numbers_grid, NumbersRules};
+use crate::queens::{split_board_regions, BoardShape};
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::Rng;
+
+/// The value of a shaded cell in a hitori shading. Unshaded cells are 0.
+pub const SHADED: u32 = 1;
+/// The chance of each cell being shaded, if the rules allow it, when making the solution.
+const SHADE_CHANCE: f64 = 0.5;
+/// The number of solutions to try before giving up.
+const MAX_ATTEMPTS: u32 = 200;
+/// The most guesses the uniqueness check may make. Past this the puzzle is treated as not unique.
+const MAX_CHECK_STEPS: u32 = 20_000;
+
+/// This function checks that the unshaded cells are all connected to one another.
+/// - shaded: whether each cell is shaded.
+/// - size: the size of the grid.
+fn unshaded_connected(shaded: &Vec<bool>, size: u32) -> bool {
+    let colour_grid: Vec<u32> = shaded.iter().map(|&shaded| !shaded as u32).collect();
+    return split_board_regions(&colour_grid, size, size).is_empty();
+}
+
+/// This function finds the other cells in a cell's row and column with the same number. Only
+/// cells with a twin may be shaded, both by the solver and by the solution check.
+/// - numbers: the numbers in the grid.
+/// - size: the size of the grid.
+/// - cell: the cell.
+fn twins(numbers: &Vec<u32>, size: u32, cell: usize) -> Vec<usize> {
+    let size = size as usize;
+    let (row, col) = (cell / size, cell % size);
+    return (0..size)
+        .map(|step| row * size + step)
+        .chain((0..size).map(|step| step * size + col))
+        .filter(|&other| other != cell && numbers[other] == numbers[cell])
+        .collect();
+}
+
+/// This function shades random cells of a grid, keeping shaded cells apart and the unshaded
+/// cells connected.
+/// - size: the size of the grid.
+/// - rng: the random number generator to use.
+fn random_shading<R: Rng>(size: u32, rng: &mut R) -> Vec<bool> {
+    let shape = BoardShape::square(size);
+    let mut shaded = vec![false; (size * size) as usize];
+    let mut cells: Vec<usize> = (0..shaded.len()).collect();
+    cells.shuffle(rng);
+    for cell in cells {
+        let apart = shape.neighbours(cell).iter().all(|&next| !shaded[next]);
+        if apart && rng.random_bool(SHADE_CHANCE) {
+            shaded[cell] = true;
+            if !unshaded_connected(&shaded, size) {
+                shaded[cell] = false;
+            }
+        }
+    }
+    return shaded;
+}
+
+/// This function generates a hitori puzzle with a unique solution. A Latin numbers grid is shaded
+/// at random, then each shaded cell copies a number from an unshaded cell in its row or column, so
+/// it has to be shaded.
+/// - size: the size of the grid.
+/// - rng: the random number generator to use.
+pub fn generate_hitori<R: Rng>(size: u32, rng: &mut R) -> Result<Vec<u32>, String> {
+    if size < 2 {
+        return Err(format!("a {}x{} grid is too small for hitori", size, size));
+    }
+    let size_usize = size as usize;
+    for _ in 0..MAX_ATTEMPTS {
+        let mut numbers = generate_ruled_numbers_grid(size, &NumbersRules::Latin, rng);
+        let shaded = random_shading(size, rng);
+        for cell in 0..numbers.len() {
+            if !shaded[cell] {
+                continue;
+            }
+            let (row, col) = (cell / size_usize, cell % size_usize);
+            let copies: Vec<u32> = (0..size_usize)
+                .map(|step| row * size_usize + step)
+                .chain((0..size_usize).map(|step| step * size_usize + col))
+                .filter(|&other| !shaded[other])
+                .map(|other| numbers[other])
+                .collect();
+            numbers[cell] = *copies.choose(rng).unwrap();
+        }
+        let (solutions, finished) = hitori_solutions(&numbers, size, 2, MAX_CHECK_STEPS);
+        if finished && solutions.len() == 1 {
+            return Ok(numbers);
+        }
+    }
+    return Err(format!("couldn't generate a {}x{} hitori", size, size));
+}
+
+/// This function counts the ways to shade the puzzle, stopping once the limit is reached.
+/// - numbers: the numbers in the grid.
+/// - size: the size of the grid.
+/// - limit: the number of solutions after which we stop looking.
+pub fn count_hitori_solutions(numbers: &Vec<u32>, size: u32, limit: u32) -> u32 {
+    return hitori_solutions(numbers, size, limit, u32::MAX).0.len() as u32;
+}
+
+/// This function finds a way to shade the puzzle.
+/// - numbers: the numbers in the grid.
+/// - size: the size of the grid.
+pub fn find_hitori_solution(numbers: &Vec<u32>, size: u32) -> Option<Vec<u32>> {
+    return hitori_solutions(numbers, size, 1, u32::MAX).0.pop();
+}
+
+/// This function finds up to `limit` ways to shade the puzzle, and whether the search finished
+/// within the steps it was given. Only cells that share their number with another cell in their
+/// row or column may be shaded, the same as `check_hitori_solution`.
+/// - numbers: the numbers in the grid.
+/// - size: the size of the grid.
+/// - limit: the number of solutions after which we stop looking.
+/// - steps: the most guesses the search may make.
+fn hitori_solutions(
+    numbers: &Vec<u32>,
+    size: u32,
+    limit: u32,
+    steps: u32,
+) -> (Vec<Vec<u32>>, bool) {
+    if validate_hitori(numbers, size).is_err() {
+        return (Vec::new(), true);
+    }
+    let mut search = HitoriSearch {
+        numbers,
+        size,
+        shape: BoardShape::square(size),
+        limit: limit as usize,
+        solutions: Vec::new(),
+        steps_left: steps,
+    };
+    let shades = (0..numbers.len())
+        .map(|cell| match twins(numbers, size, cell).is_empty() {
+            true => Shade::Unshaded,
+            false => Shade::Unknown,
+        })
+        .collect();
+    search.search(shades);
+    return (search.solutions, search.steps_left > 0);
+}
+
+/// What we know about a cell of a hitori part way through being solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shade {
+    Unknown,
+    Shaded,
+    Unshaded,
+}
+
+/// This struct holds the state of a search for the ways to shade a hitori.
+/// - numbers: the numbers in the grid.
+/// - size: the size of the grid.
+/// - shape: the shape of the grid, to find the cells next to each other.
+/// - limit: the number of solutions after which we stop looking.
+/// - solutions: the shadings found so far.
+/// - steps_left: the number of guesses that can still be made.
+struct HitoriSearch<'a> {
+    numbers: &'a Vec<u32>,
+    size: u32,
+    shape: BoardShape,
+    limit: usize,
+    solutions: Vec<Vec<u32>>,
+    steps_left: u32,
+}
+
+impl HitoriSearch<'_> {
+    /// This function fills in what follows from the cells decided so far: the cells next to a
+    /// shaded cell are unshaded, and the twins of an unshaded cell are shaded. Gives false if the
+    /// rules are broken or the unshaded cells can no longer all be connected.
+    /// - shades: what we know about each cell.
+    fn propagate(&self, shades: &mut Vec<Shade>) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for cell in 0..shades.len() {
+                let (others, other_shade) = match shades[cell] {
+                    Shade::Unknown => continue,
+                    Shade::Shaded => (self.shape.neighbours(cell), Shade::Unshaded),
+                    Shade::Unshaded => (twins(self.numbers, self.size, cell), Shade::Shaded),
+                };
+                for other in others {
+                    if shades[other] == shades[cell] {
+                        return false;
+                    }
+                    if shades[other] == Shade::Unknown {
+                        shades[other] = other_shade;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        let shaded: Vec<bool> = shades.iter().map(|&shade| shade == Shade::Shaded).collect();
+        return unshaded_connected(&shaded, self.size);
+    }
+
+    /// This function recursively guesses the first unknown cell, after filling in what follows
+    /// from the guesses so far.
+    /// - shades: what we know about each cell.
+    fn search(&mut self, mut shades: Vec<Shade>) {
+        if self.solutions.len() >= self.limit || self.steps_left == 0 {
+            return;
+        }
+        self.steps_left -= 1;
+        if !self.propagate(&mut shades) {
+            return;
+        }
+        let Some(cell) = shades.iter().position(|&shade| shade == Shade::Unknown) else {
+            let shading = shades.iter().map(|&shade| (shade == Shade::Shaded) as u32);
+            self.solutions.push(shading.collect());
+            return;
+        };
+        for guess in [Shade::Shaded, Shade::Unshaded] {
+            let mut next = shades.clone();
+            next[cell] = guess;
+            self.search(next);
+        }
+    }
+}
+
+/// This function checks that a hitori puzzle fits its grid, with numbers from 1 to its size.
+/// - numbers: the numbers in the grid.
+/// - size: the size of the grid.
+pub fn validate_hitori(numbers: &Vec<u32>, size: u32) -> Result<(), String> {
+    if numbers.len() != (size * size) as usize {
+        return Err(format!(
+            "expected {} cells for a {}x{} grid but found {}",
+            size * size,
+            size,
+            size,
+            numbers.len()
+        ));
+    }
+    if let Some(value) = numbers.iter().find(|&&value| value == 0 || value > size) {
+        return Err(format!("{} isn't a number from 1 to {}", value, size));
+    }
+    return Ok(());
+}
+
+/// This function checks if a shading solves a hitori: only cells whose number repeats in their
+/// row or column are shaded, no number repeats among the unshaded cells of a row or column, no
+/// shaded cells are next to each other, and the unshaded cells are all connected.
+/// - shading: the shading, with `SHADED` for shaded cells and 0 for unshaded ones.
+/// - numbers: the numbers in the grid.
+/// - size: the size of the grid.
+pub fn check_hitori_solution(shading: &Vec<u32>, numbers: &Vec<u32>, size: u32) -> bool {
+    if validate_hitori(numbers, size).is_err() || shading.len() != numbers.len() {
+        return false;
+    }
+    if shading.iter().any(|&value| value > SHADED) {
+        return false;
+    }
+    let shaded: Vec<bool> = shading.iter().map(|&value| value == SHADED).collect();
+    if (0..shaded.len()).any(|cell| shaded[cell] && twins(numbers, size, cell).is_empty()) {
+        return false;
+    }
+    let shape = BoardShape::square(size);
+    let apart = (0..shaded.len())
+        .all(|cell| !shaded[cell] || shape.neighbours(cell).iter().all(|&next| !shaded[next]));
+    let no_repeats = BoardShape::square(size).lines().iter().all(|line| {
+        let mut seen = vec![false; size as usize + 1];
+        for &cell in line.iter().filter(|&&cell| !shaded[cell]) {
+            if seen[numbers[cell] as usize] {
+                return false;
+            }
+            seen[numbers[cell] as usize] = true;
+        }
+        return true;
+    });
+    return apart && no_repeats && unshaded_connected(&shaded, size);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_hitori_rules() {
+        let numbers = vec![1, 2, 3, 3, 1, 1, 2, 3, 1];
+        assert!(check_hitori_solution(
+            &vec![0, 0, 0, 0, 0, SHADED, 0, 0, 0],
+            &numbers,
+            3
+        ));
+        // the 1s in the last column still repeat
+        assert!(!check_hitori_solution(
+            &vec![0, 0, 0, 0, SHADED, 0, 0, 0, 0],
+            &numbers,
+            3
+        ));
+        // shaded cells next to each other
+        assert!(!check_hitori_solution(
+            &vec![0, 0, 0, 0, 0, SHADED, 0, 0, SHADED],
+            &numbers,
+            3
+        ));
+        // the top left 1 never repeats, so it can't be shaded
+        assert!(!check_hitori_solution(
+            &vec![SHADED, 0, 0, 0, 0, SHADED, 0, 0, 0],
+            &numbers,
+            3
+        ));
+        // the top left cell is cut off
+        assert!(!check_hitori_solution(
+            &vec![0, SHADED, 0, SHADED, 0, SHADED, 0, 0, 0],
+            &numbers,
+            3
+        ));
+        // shading the middle and bottom right cells works too
+        assert_eq!(count_hitori_solutions(&numbers, 3, 5), 2);
+    }
+
+    #[test]
+    fn test_generate_hitori() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let numbers = generate_hitori(6, &mut rng).unwrap();
+        assert_eq!(validate_hitori(&numbers, 6), Ok(()));
+        assert_eq!(count_hitori_solutions(&numbers, 6, 2), 1);
+        let shading = find_hitori_solution(&numbers, 6).unwrap();
+        assert!(check_hitori_solution(&shading, &numbers, 6));
+    }
+}
@@ -0,0 +1,539 @@
+use crate::numbers::fill_grid;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The largest digit, which is also the longest a run can be.
+const LARGEST_DIGIT: u32 = 9;
+/// The longest run a carved layout keeps. Runs can be up to 9 cells, but long ones make
+/// puzzles slow to check.
+const LONGEST_CARVED_RUN: usize = 6;
+/// The chance of each cell away from the clue row and column starting out as a block.
+const BLOCK_CHANCE: f64 = 0.15;
+/// The number of layouts to try before giving up.
+const MAX_LAYOUT_ATTEMPTS: u32 = 200;
+/// The most cells the digits of a layout may be filled in before trying another layout.
+const MAX_FILL_STEPS: u32 = 100_000;
+/// The most cells the uniqueness check may fill. Past this the puzzle is treated as not unique.
+const MAX_CHECK_STEPS: u32 = 50_000;
+/// The number of times a layout's digits may be changed to make it unique before cells are blocked.
+const MAX_REFILLS: u32 = 50;
+
+/// This struct is a run of white cells across or down, and the sum of its digits.
+/// - cells: the indices of the cells in the run, in order.
+/// - sum: the sum of the digits in the run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KakuroRun {
+    pub cells: Vec<u32>,
+    pub sum: u32,
+}
+
+/// This struct is a kakuro puzzle. The first row and column are always blocks, to hold the sums.
+/// - blocks: whether each cell is a block rather than a white cell.
+/// - across: the runs along the rows.
+/// - down: the runs down the columns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KakuroBoard {
+    pub blocks: Vec<bool>,
+    pub across: Vec<KakuroRun>,
+    pub down: Vec<KakuroRun>,
+}
+
+/// This function finds the white cells in a row or column between blocks, across then down.
+/// - blocks: whether each cell is a block.
+/// - size: the size of the grid.
+fn white_runs(blocks: &Vec<bool>, size: u32) -> (Vec<Vec<u32>>, Vec<Vec<u32>>) {
+    let lines = |across: bool| -> Vec<Vec<u32>> {
+        let mut runs: Vec<Vec<u32>> = Vec::new();
+        for line in 0..size {
+            let mut run: Vec<u32> = Vec::new();
+            for step in 0..=size {
+                let cell = if across {
+                    line * size + step
+                } else {
+                    step * size + line
+                };
+                if step < size && !blocks[cell as usize] {
+                    run.push(cell);
+                } else if !run.is_empty() {
+                    runs.push(run);
+                    run = Vec::new();
+                }
+            }
+        }
+        return runs;
+    };
+    return (lines(true), lines(false));
+}
+
+/// This function blocks cells until every white cell is in runs of 2 to `LONGEST_CARVED_RUN`
+/// cells both ways.
+/// - blocks: whether each cell is a block.
+/// - size: the size of the grid.
+fn tidy_blocks(blocks: &mut Vec<bool>, size: u32) {
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let (across, down) = white_runs(blocks, size);
+        for run in across.iter().chain(&down) {
+            if run.len() == 1 {
+                blocks[run[0] as usize] = true;
+                changed = true;
+            } else if run.len() > LONGEST_CARVED_RUN {
+                blocks[run[run.len() / 2] as usize] = true;
+                changed = true;
+            }
+        }
+    }
+}
+
+/// This function carves a random layout of blocks, the same when turned half way round.
+/// - size: the size of the grid, including the first row and column of blocks.
+/// - rng: the random number generator to use.
+pub fn carve_blocks<R: Rng>(size: u32, rng: &mut R) -> Vec<bool> {
+    let mut blocks = vec![false; (size * size) as usize];
+    for index in 0..size * size {
+        let (row, col) = (index / size, index % size);
+        if row == 0 || col == 0 {
+            blocks[index as usize] = true;
+        } else if rng.random_bool(BLOCK_CHANCE) {
+            // mirrored through the middle of the cells past the clue row and column
+            blocks[index as usize] = true;
+            blocks[((size - row) * size + size - col) as usize] = true;
+        }
+    }
+    tidy_blocks(&mut blocks, size);
+    return blocks;
+}
+
+/// This function builds the puzzle for a layout and the digits that fill it.
+/// - blocks: whether each cell is a block.
+/// - solution: the digit in each white cell, with 0 for blocks.
+/// - size: the size of the grid.
+fn kakuro_board(blocks: &Vec<bool>, solution: &Vec<u32>, size: u32) -> KakuroBoard {
+    let (across, down) = white_runs(blocks, size);
+    let with_sums = |runs: Vec<Vec<u32>>| -> Vec<KakuroRun> {
+        return runs
+            .into_iter()
+            .map(|cells| {
+                let sum = cells.iter().map(|&cell| solution[cell as usize]).sum();
+                return KakuroRun { cells, sum };
+            })
+            .collect();
+    };
+    return KakuroBoard {
+        blocks: blocks.clone(),
+        across: with_sums(across),
+        down: with_sums(down),
+    };
+}
+
+/// This function fills the empty white cells with digits that don't repeat along a run, using the
+/// same backtracking as the numbers grid. Gives nothing if no digits were found in time.
+/// - blocks: whether each cell is a block.
+/// - kept: the digits to keep, with 0 for the white cells to fill.
+/// - size: the size of the grid.
+/// - rng: the random number generator to use.
+fn fill_digits<R: Rng>(
+    blocks: &Vec<bool>,
+    kept: &Vec<u32>,
+    size: u32,
+    rng: &mut R,
+) -> Option<Vec<u32>> {
+    // the kept cells go first, so the filling can start after them
+    let (mut whites, empty): (Vec<usize>, Vec<usize>) = (0..blocks.len())
+        .filter(|&cell| !blocks[cell])
+        .partition(|&cell| kept[cell] != 0);
+    let first_empty = whites.len();
+    whites.extend(empty);
+    let mut position = vec![0; blocks.len()];
+    for (at, &cell) in whites.iter().enumerate() {
+        position[cell] = at;
+    }
+    let mut peers: Vec<Vec<usize>> = vec![Vec::new(); whites.len()];
+    let (across, down) = white_runs(blocks, size);
+    for run in across.iter().chain(&down) {
+        for &cell in run {
+            let others = run.iter().filter(|&&other| other != cell);
+            peers[position[cell as usize]].extend(others.map(|&other| position[other as usize]));
+        }
+    }
+    let mut digits: Vec<u32> = whites.iter().map(|&cell| kept[cell]).collect();
+    let mut steps_left = MAX_FILL_STEPS;
+    if !fill_grid(
+        &mut digits,
+        LARGEST_DIGIT as usize,
+        first_empty,
+        &peers,
+        &mut steps_left,
+        rng,
+    ) {
+        return None;
+    }
+    let mut solution = vec![0; blocks.len()];
+    for (at, &cell) in whites.iter().enumerate() {
+        solution[cell] = digits[at];
+    }
+    return Some(solution);
+}
+
+/// This function generates a kakuro with a unique solution. Where another solution is found, the
+/// cells it differs in are given new digits, and once that's been tried too often the first of
+/// them is blocked instead, splitting its runs.
+/// - size: the size of the grid, including the first row and column of blocks.
+/// - rng: the random number generator to use.
+pub fn generate_kakuro<R: Rng>(size: u32, rng: &mut R) -> Result<KakuroBoard, String> {
+    if size < 3 {
+        return Err(format!("a {}x{} grid is too small for kakuro", size, size));
+    }
+    // a layout that loses more than half its cells to make it unique is thrown away
+    let fewest_whites = ((size - 1) * (size - 1) / 2) as usize;
+    for _ in 0..MAX_LAYOUT_ATTEMPTS {
+        let mut blocks = carve_blocks(size, rng);
+        let Some(mut solution) = fill_digits(&blocks, &vec![0; blocks.len()], size, rng) else {
+            continue;
+        };
+        let mut refills = 0;
+        loop {
+            let whites: Vec<usize> = (0..blocks.len()).filter(|&cell| !blocks[cell]).collect();
+            if whites.len() < fewest_whites {
+                break;
+            }
+            let board = kakuro_board(&blocks, &solution, size);
+            let (solutions, finished) = kakuro_solutions(&board, size, 2, MAX_CHECK_STEPS);
+            let differing: Vec<usize> = match solutions.iter().find(|&other| *other != solution) {
+                Some(other) => (0..solution.len())
+                    .filter(|&cell| other[cell] != solution[cell])
+                    .collect(),
+                None if finished => return Ok(board),
+                None => whites.choose(rng).into_iter().copied().collect(),
+            };
+            if refills < MAX_REFILLS {
+                refills += 1;
+                let mut kept = solution.clone();
+                for &cell in &differing {
+                    kept[cell] = 0;
+                }
+                if let Some(refilled) = fill_digits(&blocks, &kept, size, rng) {
+                    solution = refilled;
+                    continue;
+                }
+            }
+            blocks[differing[0]] = true;
+            tidy_blocks(&mut blocks, size);
+            for cell in 0..blocks.len() {
+                if blocks[cell] {
+                    solution[cell] = 0;
+                }
+            }
+        }
+    }
+    return Err(format!("couldn't generate a {}x{} kakuro", size, size));
+}
+
+/// This function finds the smallest and largest sums of `count` different digits.
+/// - count: the number of digits.
+fn sum_range(count: u32) -> (u32, u32) {
+    let smallest = count * (count + 1) / 2;
+    let largest = count * LARGEST_DIGIT - count * count.saturating_sub(1) / 2;
+    return (smallest, largest);
+}
+
+/// This function counts the ways the kakuro can be filled, stopping once the limit is reached.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - limit: the number of solutions after which we stop looking.
+pub fn count_kakuro_solutions(board: &KakuroBoard, size: u32, limit: u32) -> u32 {
+    return kakuro_solutions(board, size, limit, u32::MAX).0.len() as u32;
+}
+
+/// This function finds a way to fill the kakuro.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+pub fn find_kakuro_solution(board: &KakuroBoard, size: u32) -> Option<Vec<u32>> {
+    return kakuro_solutions(board, size, 1, u32::MAX).0.pop();
+}
+
+/// This function finds up to `limit` ways to fill the kakuro, and whether the search finished
+/// within the steps it was given.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - limit: the number of solutions after which we stop looking.
+/// - steps: the most cells the search may fill.
+fn kakuro_solutions(
+    board: &KakuroBoard,
+    size: u32,
+    limit: u32,
+    steps: u32,
+) -> (Vec<Vec<u32>>, bool) {
+    if validate_kakuro(board, size).is_err() {
+        return (Vec::new(), true);
+    }
+    let runs: Vec<&KakuroRun> = board.across.iter().chain(&board.down).collect();
+    let mut runs_of: Vec<Vec<usize>> = vec![Vec::new(); board.blocks.len()];
+    for (run, cells) in runs.iter().enumerate() {
+        for &cell in &cells.cells {
+            runs_of[cell as usize].push(run);
+        }
+    }
+    let mut search = KakuroSearch {
+        blocks: &board.blocks,
+        reachable: reachable_sums(),
+        sums: runs.iter().map(|run| run.sum).collect(),
+        used: vec![0; runs.len()],
+        totals: vec![0; runs.len()],
+        empty: runs.iter().map(|run| run.cells.len() as u32).collect(),
+        runs_of,
+        grid: vec![0; board.blocks.len()],
+        limit: limit as usize,
+        solutions: Vec::new(),
+        steps_left: steps,
+    };
+    search.search();
+    return (search.solutions, search.steps_left > 0);
+}
+
+/// This function finds the sums that `count` different digits from each set of digits can make.
+/// The sums for a set and count are at `free * (LARGEST_DIGIT + 1) + count`, with bit `sum` set
+/// for each sum, where the set has bit `digit` set for each of its digits.
+fn reachable_sums() -> Vec<u64> {
+    let counts = (LARGEST_DIGIT + 1) as usize;
+    let mut sums = vec![0u64; (2 << LARGEST_DIGIT) * counts];
+    for free in 0..(2 << LARGEST_DIGIT) as usize {
+        if free & !1 == 0 {
+            sums[free * counts] = 1;
+            continue;
+        }
+        // either the highest digit is one of them or it isn't
+        let digit = usize::BITS - 1 - free.leading_zeros();
+        let rest = free & !(1 << digit);
+        for count in 0..counts {
+            sums[free * counts + count] = sums[rest * counts + count];
+            if count > 0 {
+                sums[free * counts + count] |= sums[rest * counts + count - 1] << digit;
+            }
+        }
+    }
+    return sums;
+}
+
+/// This struct holds the state of a search for the ways to fill a kakuro.
+/// - blocks: whether each cell is a block.
+/// - reachable: the sums different digits can make, from `reachable_sums`.
+/// - sums: the sum of each run, across then down.
+/// - used: the digits placed in each run, with bit `digit` set for each.
+/// - totals: the sum of the digits placed in each run.
+/// - empty: the number of empty cells in each run.
+/// - runs_of: the runs each cell is in.
+/// - grid: the digits placed so far, with 0 for empty cells and blocks.
+/// - limit: the number of solutions after which we stop looking.
+/// - solutions: the full grids found so far.
+/// - steps_left: the number of cells that can still be filled.
+struct KakuroSearch<'a> {
+    blocks: &'a Vec<bool>,
+    reachable: Vec<u64>,
+    sums: Vec<u32>,
+    used: Vec<u32>,
+    totals: Vec<u32>,
+    empty: Vec<u32>,
+    runs_of: Vec<Vec<usize>>,
+    grid: Vec<u32>,
+    limit: usize,
+    solutions: Vec<Vec<u32>>,
+    steps_left: u32,
+}
+
+impl KakuroSearch<'_> {
+    /// This function finds the digits a cell can take without repeating along its runs or
+    /// leaving a run's sum out of reach of the digits still free in it.
+    /// - index: the cell.
+    fn options(&self, index: usize) -> Vec<u32> {
+        let all_digits = ((1 << (LARGEST_DIGIT + 1)) - 1) & !1;
+        return (1..=LARGEST_DIGIT)
+            .filter(|&digit| {
+                return self.runs_of[index].iter().all(|&run| {
+                    if self.used[run] & (1 << digit) != 0 {
+                        return false;
+                    }
+                    let total = self.totals[run] + digit;
+                    if total > self.sums[run] {
+                        return false;
+                    }
+                    let free = all_digits & !self.used[run] & !(1 << digit);
+                    let count = self.empty[run] - 1;
+                    let sums = self.reachable[(free * (LARGEST_DIGIT + 1) + count) as usize];
+                    return sums & (1 << (self.sums[run] - total)) != 0;
+                });
+            })
+            .collect();
+    }
+
+    /// This function places or takes away a digit, keeping the runs' state up to date.
+    /// - index: the cell.
+    /// - digit: the digit to place, or 0 to take the cell's digit away.
+    fn set(&mut self, index: usize, digit: u32) {
+        let (old, runs) = (self.grid[index], &self.runs_of[index]);
+        for &run in runs {
+            if old != 0 {
+                self.used[run] &= !(1 << old);
+                self.totals[run] -= old;
+                self.empty[run] += 1;
+            }
+            if digit != 0 {
+                self.used[run] |= 1 << digit;
+                self.totals[run] += digit;
+                self.empty[run] -= 1;
+            }
+        }
+        self.grid[index] = digit;
+    }
+
+    /// This function recursively fills the empty cell with the fewest options.
+    fn search(&mut self) {
+        if self.solutions.len() >= self.limit || self.steps_left == 0 {
+            return;
+        }
+        self.steps_left -= 1;
+        let mut best: Option<(usize, Vec<u32>)> = None;
+        for index in 0..self.grid.len() {
+            if self.blocks[index] || self.grid[index] != 0 {
+                continue;
+            }
+            let options = self.options(index);
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_options)| options.len() < best_options.len())
+            {
+                let forced = options.len() <= 1;
+                best = Some((index, options));
+                if forced {
+                    break;
+                }
+            }
+        }
+        let Some((index, options)) = best else {
+            self.solutions.push(self.grid.clone());
+            return;
+        };
+        for digit in options {
+            self.set(index, digit);
+            self.search();
+        }
+        self.set(index, 0);
+    }
+}
+
+/// This function checks that a kakuro fits its grid: every run is of 2 to 9 white cells with a
+/// sum that different digits can make.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+pub fn validate_kakuro(board: &KakuroBoard, size: u32) -> Result<(), String> {
+    if board.blocks.len() != (size * size) as usize {
+        return Err(format!(
+            "expected {} cells for a {}x{} grid but found {}",
+            size * size,
+            size,
+            size,
+            board.blocks.len()
+        ));
+    }
+    for run in board.across.iter().chain(&board.down) {
+        let length = run.cells.len() as u32;
+        if !(2..=LARGEST_DIGIT).contains(&length) {
+            return Err(format!("a run has {} cells, not 2 to 9", length));
+        }
+        let white = |&cell: &u32| cell < size * size && !board.blocks[cell as usize];
+        if !run.cells.iter().all(white) {
+            return Err("a run goes through a block or off the grid".to_string());
+        }
+        let (smallest, largest) = sum_range(length);
+        if run.sum < smallest || run.sum > largest {
+            return Err(format!(
+                "{} different digits can't add up to {}",
+                length, run.sum
+            ));
+        }
+    }
+    return Ok(());
+}
+
+/// This function checks if a grid solves a kakuro: every white cell has a digit, and every run
+/// has different digits adding up to its sum.
+/// - kakuro_grid: the digit in each cell, with 0 for blocks.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+pub fn check_kakuro_solution(kakuro_grid: &Vec<u32>, board: &KakuroBoard, size: u32) -> bool {
+    if validate_kakuro(board, size).is_err() || kakuro_grid.len() != board.blocks.len() {
+        return false;
+    }
+    let cells_fit = (0..kakuro_grid.len()).all(|cell| {
+        let digit = kakuro_grid[cell];
+        if board.blocks[cell] {
+            return digit == 0;
+        }
+        return (1..=LARGEST_DIGIT).contains(&digit);
+    });
+    // the runs index by digit, so stop before any digit out of range reaches them
+    if !cells_fit {
+        return false;
+    }
+    let runs_fit = board.across.iter().chain(&board.down).all(|run| {
+        let mut used = [false; LARGEST_DIGIT as usize + 1];
+        let mut total = 0;
+        for &cell in &run.cells {
+            let digit = kakuro_grid[cell as usize] as usize;
+            if used[digit] {
+                return false;
+            }
+            used[digit] = true;
+            total += digit as u32;
+        }
+        return total == run.sum;
+    });
+    return runs_fit;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_kakuro_runs() {
+        // a 2x2 square of white cells below and right of the clue row and column
+        let mut blocks = vec![true; 9];
+        for cell in [4, 5, 7, 8] {
+            blocks[cell] = false;
+        }
+        let solution = vec![0, 0, 0, 0, 1, 2, 0, 2, 1];
+        let board = kakuro_board(&blocks, &solution, 3);
+        assert_eq!(board.across[0].cells, vec![4, 5]);
+        assert_eq!(board.down[1].sum, 3);
+        assert!(check_kakuro_solution(&solution, &board, 3));
+        assert!(!check_kakuro_solution(
+            &vec![0, 0, 0, 0, 1, 2, 0, 1, 2],
+            &board,
+            3
+        ));
+        // a digit past 9 is turned away rather than read off the end of the run
+        assert!(!check_kakuro_solution(
+            &vec![0, 0, 0, 0, 10, 2, 0, 2, 1],
+            &board,
+            3
+        ));
+        // the 1s and 2s swapped still meet the sums, so this layout isn't unique
+        assert_eq!(count_kakuro_solutions(&board, 3, 5), 2);
+        assert_eq!(sum_range(3), (6, 24));
+    }
+
+    #[test]
+    fn test_generate_kakuro() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let board = generate_kakuro(7, &mut rng).unwrap();
+        assert_eq!(validate_kakuro(&board, 7), Ok(()));
+        assert_eq!(count_kakuro_solutions(&board, 7, 2), 1);
+        let solution = find_kakuro_solution(&board, 7).unwrap();
+        assert!(check_kakuro_solution(&solution, &board, 7));
+    }
+}
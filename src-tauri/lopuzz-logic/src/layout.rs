@@ -0,0 +1,171 @@
+use crate::queens::{count_queens_solutions, split_board_regions, BoardShape, QueensVariant};
+
+/// This struct is a queens board read from a text layout.
+/// - colour_grid: the grid of colours, numbered from 1 in the order the letters first appear,
+///   with 0 for void cells.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+/// - labels: the letter used for each colour, so `labels[0]` is the letter of colour 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueensLayout {
+    pub colour_grid: Vec<u32>,
+    pub rows: u32,
+    pub cols: u32,
+    pub labels: Vec<char>,
+}
+
+/// This function reads a queens board sketched as a grid of letters, one letter per cell
+/// and one letter per region, like `AABBC / ADDBC / ...`. Rows are separated by `/` or new lines,
+/// and spaces within a row are ignored. `#` or `.` marks a void cell, which lets boards be
+/// rectangular or have holes. The board is checked to have exactly one region per row in play,
+/// every region connected and exactly one solution.
+/// - text: the layout.
+pub fn parse_queens_layout(text: &str) -> Result<QueensLayout, String> {
+    let rows: Vec<Vec<char>> = text
+        .split(['/', '\n'])
+        .map(|row| {
+            row.chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<Vec<char>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect();
+    if rows.is_empty() {
+        return Err("the layout is empty".to_string());
+    }
+    let width = rows[0].len();
+    for (i, row) in rows.iter().enumerate() {
+        if row.len() != width {
+            return Err(format!(
+                "row {} has {} cells but row 1 has {}",
+                i + 1,
+                row.len(),
+                width
+            ));
+        }
+    }
+
+    let mut labels: Vec<char> = Vec::new();
+    let mut colour_grid: Vec<u32> = Vec::new();
+    for &label in rows.iter().flatten() {
+        if label == '#' || label == '.' {
+            colour_grid.push(0);
+            continue;
+        }
+        let colour = match labels.iter().position(|&l| l == label) {
+            Some(position) => position + 1,
+            None => {
+                labels.push(label);
+                labels.len()
+            }
+        };
+        colour_grid.push(colour as u32);
+    }
+    let layout = QueensLayout {
+        colour_grid,
+        rows: rows.len() as u32,
+        cols: width as u32,
+        labels,
+    };
+
+    let variant = QueensVariant {
+        shape: BoardShape::of_grid(&layout.colour_grid, layout.rows, layout.cols),
+        ..QueensVariant::queens(layout.rows)
+    };
+    let n_regions = variant.check()?;
+    if layout.labels.len() != n_regions as usize {
+        return Err(format!(
+            "a {}x{} board needs {} regions but found {} ({})",
+            layout.rows,
+            layout.cols,
+            n_regions,
+            layout.labels.len(),
+            layout.labels.iter().collect::<String>()
+        ));
+    }
+    let split = split_board_regions(&layout.colour_grid, layout.rows, layout.cols);
+    if !split.is_empty() {
+        let names: Vec<String> = split
+            .iter()
+            .map(|&colour| layout.labels[colour as usize - 1].to_string())
+            .collect();
+        return Err(format!(
+            "every region must be connected, but {} is split into pieces",
+            names.join(", ")
+        ));
+    }
+    match count_queens_solutions(&layout.colour_grid, &variant, 2) {
+        0 => return Err("the board has no solution".to_string()),
+        1 => return Ok(layout),
+        _ => return Err("the board has more than one solution".to_string()),
+    }
+}
+
+/// This function writes a queens board as a letter layout, one row per line, with colour 1 as `A`
+/// and `#` for void cells. It is the reverse of `parse_queens_layout`.
+/// - colour_grid: the grid of colours.
+/// - cols: the number of columns.
+pub fn format_queens_layout(colour_grid: &Vec<u32>, cols: u32) -> String {
+    let mut text = String::new();
+    for row in colour_grid.chunks(cols as usize) {
+        for &colour in row {
+            // colours past Z carry on into the lower case letters
+            let label = match colour {
+                0 => '#',
+                1..=26 => (b'A' + (colour - 1) as u8) as char,
+                27..=52 => (b'a' + (colour - 27) as u8) as char,
+                _ => '?',
+            };
+            text.push(label);
+        }
+        text.push('\n');
+    }
+    return text;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_queens_layout() {
+        let layout = parse_queens_layout("EEEBB / EECBB / DECBB / DECBB / DDDAB").unwrap();
+        assert_eq!((layout.rows, layout.cols), (5, 5));
+        assert_eq!(layout.labels, vec!['E', 'B', 'C', 'D', 'A']);
+        assert_eq!(layout.colour_grid[..5], [1, 1, 1, 2, 2]);
+        let text = format_queens_layout(&layout.colour_grid, 5);
+        assert_eq!(text, "AAABB\nAACBB\nDACBB\nDACBB\nDDDEB\n");
+        // writing it out and reading it back in gives the same board
+        assert_eq!(
+            parse_queens_layout(&text).unwrap().colour_grid,
+            layout.colour_grid
+        );
+    }
+
+    #[test]
+    fn test_layout_errors() {
+        assert!(parse_queens_layout("AB / ABC")
+            .unwrap_err()
+            .contains("row 2"));
+        assert!(parse_queens_layout("AB / CD")
+            .unwrap_err()
+            .contains("found 4"));
+        // B is in two pieces, either side of A
+        let error = parse_queens_layout("BAB / AAC / CCC").unwrap_err();
+        assert!(error.contains("B is split"));
+    }
+
+    #[test]
+    fn test_void_layout() {
+        // the last column is void, so this 4x5 board plays like a 4x4 one
+        let layout = parse_queens_layout("ABBB. / AACB. / DACC. / DDCC.").unwrap();
+        assert_eq!((layout.rows, layout.cols), (4, 5));
+        assert_eq!(layout.colour_grid[4], 0);
+        let text = format_queens_layout(&layout.colour_grid, 5);
+        assert_eq!(text, "ABBB#\nAACB#\nDACC#\nDDCC#\n");
+        // a void row leaves four columns but only three rows to put queens in
+        assert!(parse_queens_layout("ABBB / AACB / #### / DDCC")
+            .unwrap_err()
+            .contains("shared evenly between 4 columns"));
+    }
+}
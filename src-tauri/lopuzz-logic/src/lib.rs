@@ -0,0 +1,29 @@
+//! The puzzle generators, solvers and file formats shared by the app and the command line tools.
+
+pub mod booklet;
+pub mod cages;
+pub mod clash;
+pub mod drawing;
+pub mod futoshiki;
+pub mod hitori;
+pub mod kakuro;
+pub mod layout;
+pub mod nonogram;
+pub mod numbers;
+pub mod packs;
+pub mod palette;
+pub mod pdf;
+pub mod png;
+pub mod puzzle_file;
+pub mod queens;
+pub mod raster;
+pub mod rating;
+pub mod regions;
+pub mod render;
+pub mod share;
+pub mod sudoku;
+pub mod svg;
+pub mod tango;
+pub mod timer;
+pub mod utilities;
+pub mod zip;
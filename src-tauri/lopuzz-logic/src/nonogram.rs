@@ -0,0 +1,414 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The chance of each cell of a random solution being filled. Fuller grids have longer runs, so
+/// are more often solvable line by line.
+const FILL_CHANCE: f64 = 0.6;
+/// The number of random solutions to try before giving up on finding one solvable line by line.
+const MAX_RANDOM_ATTEMPTS: u32 = 500;
+
+/// What we know about a cell of a nonogram part way through being solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Unknown,
+    Filled,
+    Empty,
+}
+
+/// This struct is a nonogram puzzle.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+/// - row_clues: the lengths of the runs of filled cells in each row, from the left.
+/// - col_clues: the lengths of the runs of filled cells in each column, from the top.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NonogramBoard {
+    pub rows: u32,
+    pub cols: u32,
+    pub row_clues: Vec<Vec<u32>>,
+    pub col_clues: Vec<Vec<u32>>,
+}
+
+/// This struct is an image to turn into a nonogram.
+/// - width, height: the size of the image in pixels.
+/// - rgb: the red, green and blue bytes of each pixel, row by row from the top.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NonogramImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgb: Vec<u8>,
+}
+
+/// This function finds the lengths of the runs of filled cells along a line.
+/// - cells: the line, with 1 for filled cells and 0 for empty ones.
+pub fn line_clue(cells: &[u32]) -> Vec<u32> {
+    let mut clue: Vec<u32> = Vec::new();
+    let mut run = 0;
+    for &cell in cells {
+        if cell == 1 {
+            run += 1;
+        } else if run > 0 {
+            clue.push(run);
+            run = 0;
+        }
+    }
+    if run > 0 {
+        clue.push(run);
+    }
+    return clue;
+}
+
+/// This function lists the cells of every row, then every column.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+fn nonogram_lines(rows: u32, cols: u32) -> Vec<Vec<usize>> {
+    let (rows, cols) = (rows as usize, cols as usize);
+    let row_lines = (0..rows).map(|row| (0..cols).map(|col| row * cols + col).collect());
+    let col_lines = (0..cols).map(|col| (0..rows).map(|row| row * cols + col).collect());
+    return row_lines.chain(col_lines).collect();
+}
+
+/// This function works out the clues of a solution.
+/// - solution: the grid, with 1 for filled cells and 0 for empty ones.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+pub fn nonogram_clues(solution: &Vec<u32>, rows: u32, cols: u32) -> NonogramBoard {
+    let mut clues: Vec<Vec<u32>> = nonogram_lines(rows, cols)
+        .iter()
+        .map(|line| {
+            let cells: Vec<u32> = line.iter().map(|&cell| solution[cell]).collect();
+            return line_clue(&cells);
+        })
+        .collect();
+    let col_clues = clues.split_off(rows as usize);
+    return NonogramBoard {
+        rows,
+        cols,
+        row_clues: clues,
+        col_clues,
+    };
+}
+
+/// This function makes a random solution.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+/// - rng: the random number generator to use.
+pub fn random_nonogram_solution<R: Rng>(rows: u32, cols: u32, rng: &mut R) -> Vec<u32> {
+    return (0..rows * cols)
+        .map(|_| rng.random_bool(FILL_CHANCE) as u32)
+        .collect();
+}
+
+/// This function makes a solution from an image, filling each cell whose part of the image is
+/// darker than the image as a whole.
+/// - image: the image.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+pub fn image_nonogram_solution(
+    image: &NonogramImage,
+    rows: u32,
+    cols: u32,
+) -> Result<Vec<u32>, String> {
+    let (width, height) = (image.width, image.height);
+    if image.rgb.len() != (width * height * 3) as usize {
+        return Err(format!(
+            "expected {} bytes for a {}x{} image but found {}",
+            width * height * 3,
+            width,
+            height,
+            image.rgb.len()
+        ));
+    }
+    if width < cols || height < rows {
+        return Err(format!(
+            "a {}x{} image is too small for {} rows and {} columns",
+            width, height, rows, cols
+        ));
+    }
+    let brightness = |x: u32, y: u32| {
+        let at = ((y * width + x) * 3) as usize;
+        return image.rgb[at..at + 3]
+            .iter()
+            .map(|&byte| byte as u32)
+            .sum::<u32>();
+    };
+    // the average brightness of the block of pixels under each cell
+    let cells: Vec<f64> = (0..rows * cols)
+        .map(|index| {
+            let (row, col) = (index / cols, index % cols);
+            let (top, bottom) = (row * height / rows, (row + 1) * height / rows);
+            let (left, right) = (col * width / cols, (col + 1) * width / cols);
+            let mut total = 0;
+            for y in top..bottom {
+                for x in left..right {
+                    total += brightness(x, y);
+                }
+            }
+            return total as f64 / ((bottom - top) * (right - left)) as f64;
+        })
+        .collect();
+    let mean = cells.iter().sum::<f64>() / cells.len() as f64;
+    return Ok(cells.iter().map(|&cell| (cell < mean) as u32).collect());
+}
+
+/// This function generates a random nonogram that can be solved line by line, so has a unique
+/// solution.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+/// - rng: the random number generator to use.
+pub fn generate_nonogram<R: Rng>(
+    rows: u32,
+    cols: u32,
+    rng: &mut R,
+) -> Result<NonogramBoard, String> {
+    for _ in 0..MAX_RANDOM_ATTEMPTS {
+        let solution = random_nonogram_solution(rows, cols, rng);
+        let board = nonogram_clues(&solution, rows, cols);
+        if line_solve(&board).is_some() {
+            return Ok(board);
+        }
+    }
+    return Err(format!(
+        "couldn't find a {}x{} nonogram that can be solved line by line",
+        rows, cols
+    ));
+}
+
+/// This function makes a nonogram from an image, as long as it can be solved line by line.
+/// - image: the image.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+pub fn nonogram_from_image(
+    image: &NonogramImage,
+    rows: u32,
+    cols: u32,
+) -> Result<NonogramBoard, String> {
+    let solution = image_nonogram_solution(image, rows, cols)?;
+    let board = nonogram_clues(&solution, rows, cols);
+    if line_solve(&board).is_none() {
+        return Err(
+            "this image gives a nonogram that can't be solved line by line, try another size"
+                .to_string(),
+        );
+    }
+    return Ok(board);
+}
+
+/// This function solves a nonogram one line at a time, filling in what each line's clue forces
+/// until nothing changes. Gives the solution if that was enough, which means it is unique.
+/// - board: the puzzle.
+pub fn line_solve(board: &NonogramBoard) -> Option<Vec<u32>> {
+    if validate_nonogram(board).is_err() {
+        return None;
+    }
+    let lines = nonogram_lines(board.rows, board.cols);
+    let clues: Vec<&Vec<u32>> = board.row_clues.iter().chain(&board.col_clues).collect();
+    let mut marks = vec![Mark::Unknown; (board.rows * board.cols) as usize];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (line, clue) in lines.iter().zip(&clues) {
+            let current: Vec<Mark> = line.iter().map(|&cell| marks[cell]).collect();
+            let solved = solve_line(clue, &current)?;
+            for (&cell, &mark) in line.iter().zip(&solved) {
+                if marks[cell] != mark {
+                    marks[cell] = mark;
+                    changed = true;
+                }
+            }
+        }
+    }
+    if marks.contains(&Mark::Unknown) {
+        return None;
+    }
+    return Some(
+        marks
+            .iter()
+            .map(|&mark| (mark == Mark::Filled) as u32)
+            .collect(),
+    );
+}
+
+/// This function finds what a clue forces along a line: the cells filled in every way of
+/// placing the runs that agrees with what's known, and those empty in every way.
+/// Gives nothing if there's no way to place the runs.
+/// - clue: the lengths of the runs.
+/// - line: what's known about each cell of the line.
+fn solve_line(clue: &[u32], line: &[Mark]) -> Option<Vec<Mark>> {
+    let mut placer = LinePlacer {
+        clue,
+        line,
+        seen: vec![vec![None; clue.len() + 1]; line.len() + 1],
+        can_fill: vec![false; line.len()],
+        can_empty: vec![false; line.len()],
+    };
+    if !placer.fits(0, 0) {
+        return None;
+    }
+    let solved = (0..line.len())
+        .map(
+            |cell| match (placer.can_fill[cell], placer.can_empty[cell]) {
+                (true, false) => Mark::Filled,
+                (false, true) => Mark::Empty,
+                _ => Mark::Unknown,
+            },
+        )
+        .collect();
+    return Some(solved);
+}
+
+/// This struct holds the state of working out the ways to place the runs of a clue along a line.
+/// - clue: the lengths of the runs.
+/// - line: what's known about each cell of the line.
+/// - seen: whether the runs from each one on fit in the cells from each one on, once worked out.
+/// - can_fill: whether each cell is filled in some way of placing the runs.
+/// - can_empty: whether each cell is empty in some way of placing the runs.
+struct LinePlacer<'a> {
+    clue: &'a [u32],
+    line: &'a [Mark],
+    seen: Vec<Vec<Option<bool>>>,
+    can_fill: Vec<bool>,
+    can_empty: Vec<bool>,
+}
+
+impl LinePlacer<'_> {
+    /// This function checks if the runs from `run` on fit in the cells from `cell` on, noting
+    /// what each cell can be along the way. Only called once the cells before have been placed.
+    /// - cell: the first cell left.
+    /// - run: the first run left.
+    fn fits(&mut self, cell: usize, run: usize) -> bool {
+        if cell >= self.line.len() {
+            return run == self.clue.len();
+        }
+        if let Some(fits) = self.seen[cell][run] {
+            return fits;
+        }
+        let mut fits = false;
+        // leave this cell empty
+        if self.line[cell] != Mark::Filled && self.fits(cell + 1, run) {
+            self.can_empty[cell] = true;
+            fits = true;
+        }
+        // start the next run here, with an empty cell after it unless it reaches the end
+        if run < self.clue.len() {
+            let end = cell + self.clue[run] as usize;
+            let room = end <= self.line.len()
+                && self.line[cell..end].iter().all(|&mark| mark != Mark::Empty)
+                && (end == self.line.len() || self.line[end] != Mark::Filled);
+            if room && self.fits((end + 1).min(self.line.len()), run + 1) {
+                for filled in cell..end {
+                    self.can_fill[filled] = true;
+                }
+                if end < self.line.len() {
+                    self.can_empty[end] = true;
+                }
+                fits = true;
+            }
+        }
+        self.seen[cell][run] = Some(fits);
+        return fits;
+    }
+}
+
+/// This function checks that the clues of a nonogram fit its grid.
+/// - board: the puzzle.
+pub fn validate_nonogram(board: &NonogramBoard) -> Result<(), String> {
+    if board.rows == 0 || board.cols == 0 {
+        return Err("a nonogram needs at least one row and one column".to_string());
+    }
+    if board.row_clues.len() != board.rows as usize || board.col_clues.len() != board.cols as usize
+    {
+        return Err(format!(
+            "expected clues for {} rows and {} columns but found {} and {}",
+            board.rows,
+            board.cols,
+            board.row_clues.len(),
+            board.col_clues.len()
+        ));
+    }
+    let row_lines = board.row_clues.iter().map(|clue| (clue, board.cols));
+    let col_lines = board.col_clues.iter().map(|clue| (clue, board.rows));
+    for (clue, length) in row_lines.chain(col_lines) {
+        // each run needs a gap after it but the last
+        let needed = clue.iter().sum::<u32>() + clue.len().saturating_sub(1) as u32;
+        if clue.contains(&0) || needed > length {
+            return Err(format!(
+                "the clue {:?} doesn't fit in a line of {} cells",
+                clue, length
+            ));
+        }
+    }
+    return Ok(());
+}
+
+/// This function checks if the player's grid meets every clue of a nonogram.
+/// - nonogram_grid: the grid, with 1 for filled cells and 0 for empty ones.
+/// - board: the puzzle.
+pub fn check_nonogram_solution(nonogram_grid: &Vec<u32>, board: &NonogramBoard) -> bool {
+    if nonogram_grid.len() != (board.rows * board.cols) as usize
+        || nonogram_grid.iter().any(|&cell| cell > 1)
+    {
+        return false;
+    }
+    return nonogram_clues(nonogram_grid, board.rows, board.cols) == *board;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_edge_cases() {
+        use Mark::{Empty as E, Filled as F, Unknown as U};
+        // an empty line has no runs, and its clue empties every cell
+        assert_eq!(line_clue(&[0, 0, 0, 0]), Vec::<u32>::new());
+        assert_eq!(solve_line(&[], &[U; 4]), Some(vec![E; 4]));
+        assert_eq!(solve_line(&[], &[U, F, U, U]), None);
+        // a full line is one run as long as the line
+        assert_eq!(line_clue(&[1, 1, 1, 1]), vec![4]);
+        assert_eq!(solve_line(&[4], &[U; 4]), Some(vec![F; 4]));
+        // runs and the gaps between them that need more cells than the line has
+        assert_eq!(solve_line(&[2, 2], &[U; 4]), None);
+        let board = NonogramBoard {
+            rows: 1,
+            cols: 4,
+            row_clues: vec![vec![2, 2]],
+            col_clues: vec![vec![1]; 4],
+        };
+        assert!(validate_nonogram(&board).is_err());
+        assert_eq!(line_solve(&board), None);
+        let zero_run = NonogramBoard {
+            row_clues: vec![vec![0]],
+            ..board
+        };
+        assert!(validate_nonogram(&zero_run).is_err());
+    }
+
+    #[test]
+    fn test_image_nonograms() {
+        // two dark pixels on a diagonal give a 1 in every line, which either diagonal meets
+        let image = NonogramImage {
+            width: 2,
+            height: 2,
+            rgb: vec![0, 0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 0],
+        };
+        let solution = image_nonogram_solution(&image, 2, 2).unwrap();
+        assert_eq!(solution, vec![1, 0, 0, 1]);
+        let board = nonogram_clues(&solution, 2, 2);
+        assert!(check_nonogram_solution(&vec![0, 1, 1, 0], &board));
+        assert_eq!(line_solve(&board), None);
+        assert!(nonogram_from_image(&image, 2, 2).is_err());
+        // an image of one colour has nothing darker than itself, so every line is empty
+        let plain = NonogramImage {
+            width: 3,
+            height: 3,
+            rgb: vec![90; 27],
+        };
+        let board = nonogram_from_image(&plain, 3, 3).unwrap();
+        assert!(board
+            .row_clues
+            .iter()
+            .chain(&board.col_clues)
+            .all(|clue| clue.is_empty()));
+        assert_eq!(line_solve(&board), Some(vec![0; 9]));
+    }
+}
@@ -0,0 +1,595 @@
+use crate::sudoku::validate_boxes;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use std::vec;
+
+/// The most cells a full grid may be filled in before we give up on it, as some rules or jigsaw
+/// boxes leave the search stuck for a very long time.
+const MAX_FILL_STEPS: u32 = 1_000_000;
+
+/// The most cells the uniqueness check may fill when deciding if a clue can be taken out. Past
+/// this the clue is kept, so hard jigsaw grids don't take minutes to make.
+const MAX_REMOVAL_STEPS: u32 = 20_000;
+
+/// The most cells the check that clues can be completed may fill. Clues from share codes and
+/// files can be anything, so past this they're rejected rather than freezing the app.
+const MAX_CHECK_STEPS: u32 = 50_000;
+
+/// The largest numbers grid. The searches keep the values a cell can still take as bits of a
+/// `u64`, with a bit for every value up to the size.
+pub const MAX_NUMBERS_SIZE: u32 = 62;
+
+/// This enum is the set of lines a numbers grid must not repeat a value along, besides its
+/// rows and columns.
+/// - Latin: no other lines, so the grid is a plain Latin square.
+/// - XLatin: the two main diagonals, corner to corner.
+/// - AllDiagonals: every diagonal in both directions. Only some sizes, like 5 and 7, can be filled.
+/// - Boxes: the boxes of a sudoku, given as the box of each cell numbered from 1. The boxes can
+///   be the usual rectangles or any jigsaw shapes, as long as each has `size` cells.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumbersRules {
+    Latin,
+    XLatin,
+    #[default]
+    AllDiagonals,
+    Boxes(Vec<u32>),
+}
+
+impl NumbersRules {
+    /// This function checks the rules can be used for a grid of the size, so that the searches
+    /// don't read past the boxes or overflow their value masks.
+    /// - size: the size of the grid.
+    pub fn validate(&self, size: u32) -> Result<(), String> {
+        if size > MAX_NUMBERS_SIZE {
+            return Err(format!(
+                "a {}x{} grid is too large, the largest is {}x{}",
+                size, size, MAX_NUMBERS_SIZE, MAX_NUMBERS_SIZE
+            ));
+        }
+        if let NumbersRules::Boxes(boxes) = self {
+            validate_boxes(boxes, size)?;
+        }
+        return Ok(());
+    }
+
+    /// This function checks if two different cells are on a line together, so can't share a value.
+    /// - cell1: the first cell (row, col).
+    /// - cell2: the second cell (row, col).
+    /// - size: the size of the grid.
+    pub fn linked(&self, cell1: (usize, usize), cell2: (usize, usize), size: usize) -> bool {
+        let ((row1, col1), (row2, col2)) = (cell1, cell2);
+        if row1 == row2 || col1 == col2 {
+            return true;
+        }
+        match self {
+            NumbersRules::Latin => return false,
+            NumbersRules::XLatin => {
+                let leading = row1 == col1 && row2 == col2;
+                let trailing = row1 + col1 == size - 1 && row2 + col2 == size - 1;
+                return leading || trailing;
+            }
+            NumbersRules::AllDiagonals => {
+                return row1 + col2 == row2 + col1 || row1 + col1 == row2 + col2
+            }
+            NumbersRules::Boxes(boxes) => {
+                return boxes[row1 * size + col1] == boxes[row2 * size + col2];
+            }
+        }
+    }
+
+    /// This function lists the cells linked to each cell, so searches don't have to work them out
+    /// again for every placement.
+    /// - size: the size of the grid.
+    pub fn peers(&self, size: usize) -> Vec<Vec<usize>> {
+        return (0..size * size)
+            .map(|index| {
+                let cell = (index / size, index % size);
+                return (0..size * size)
+                    .filter(|&other| {
+                        other != index && self.linked(cell, (other / size, other % size), size)
+                    })
+                    .collect();
+            })
+            .collect();
+    }
+}
+
+/// Thisnfunction generates a numbers grid.
+/// - size: the size of the grid generated.
+/// - rng: the random number generator to use.
+pub fn generate_numbers_grid<R: Rng>(size: u32, rng: &mut R) -> Vec<u32> {
+    return generate_ruled_numbers_grid(size, &NumbersRules::default(), rng);
+}
+
+/// This function generates a full numbers grid where no value repeats along a row, column or
+/// any line of the rules. Gives a grid of 0s if the rules can't be met at this size, or no grid
+/// is found quickly enough.
+/// - size: the size of the grid generated.
+/// - rules: the lines that can't repeat a value.
+/// - rng: the random number generator to use.
+pub fn generate_ruled_numbers_grid<R: Rng>(
+    size: u32,
+    rules: &NumbersRules,
+    rng: &mut R,
+) -> Vec<u32> {
+    let mut grid = vec![0; (size * size) as usize];
+    if rules.validate(size).is_err() {
+        return grid;
+    }
+    let peers = rules.peers(size as usize);
+    let mut steps_left = MAX_FILL_STEPS;
+    if fill_grid(&mut grid, size as usize, 0, &peers, &mut steps_left, rng) {
+        return grid;
+    }
+    return vec![0; (size * size) as usize];
+}
+
+/// This function fills the grid one at a time recursively. Kakuro uses it too, with its own
+/// cells, peers and largest value.
+/// - grid: the current grid state.
+/// - largest: the largest value, which is the size of the grid for numbers.
+/// - index: the position of the cell to fill next.
+/// - peers: the cells linked to each cell.
+/// - steps_left: the number of cells that can still be filled before giving up.
+/// - rng: the random number generator to use.
+pub fn fill_grid<R: Rng>(
+    grid: &mut Vec<u32>,
+    largest: usize,
+    index: usize,
+    peers: &Vec<Vec<usize>>,
+    steps_left: &mut u32,
+    rng: &mut R,
+) -> bool {
+    if index == grid.len() {
+        return true;
+    }
+    if *steps_left == 0 {
+        return false;
+    }
+    *steps_left -= 1;
+    let mut possible_values: Vec<u32> = valid_placements(grid, index, largest, peers);
+    possible_values.shuffle(rng);
+    for value in possible_values {
+        grid[index] = value;
+        if fill_grid(grid, largest, index + 1, peers, steps_left, rng) {
+            return true;
+        }
+        grid[index] = 0;
+    }
+    return false;
+}
+
+/// This function finds all the valid placements for the current index for the current grid,
+/// in order: the values that no linked cell has yet.
+/// - grid: the current grid state, with 0 for empty cells.
+/// - index: the current square.
+/// - largest: the largest value, which is the size of the grid for numbers.
+/// - peers: the cells linked to each cell.
+fn valid_placements(
+    grid: &Vec<u32>,
+    index: usize,
+    largest: usize,
+    peers: &Vec<Vec<usize>>,
+) -> Vec<u32> {
+    let used = used_values(grid, index, peers);
+    return (1..=largest as u32)
+        .filter(|&value| used & (1 << value) == 0)
+        .collect();
+}
+
+/// This function finds the values the cells linked to an index already have, with bit `value`
+/// set for each one. Bit 0 is set for empty cells and can be ignored.
+/// - grid: the current grid state, with 0 for empty cells.
+/// - index: the current square.
+/// - peers: the cells linked to each cell.
+fn used_values(grid: &Vec<u32>, index: usize, peers: &Vec<Vec<usize>>) -> u64 {
+    return peers[index]
+        .iter()
+        .fold(0, |used, &other| used | (1 << grid[other]));
+}
+
+/// This function takes values out of a full grid while the puzzle still has exactly one solution
+/// under the rules, then puts some back according to the difficulty.
+/// - grid: the full grid to work with.
+/// - difficulty: the difficulty setting.
+/// - size: the size of the grid.
+/// - rules: the lines that can't repeat a value.
+/// - rng: the random number generator to use.
+pub fn remove_ruled_values<R: Rng>(
+    grid: &Vec<u32>,
+    difficulty: u32,
+    size: u32,
+    rules: &NumbersRules,
+    rng: &mut R,
+) -> Vec<u32> {
+    let mut cells: Vec<usize> = (0..(size * size) as usize).collect();
+    cells.shuffle(rng);
+    let peers = rules.peers(size as usize);
+    let mut return_grid = grid.clone();
+    let mut removed: Vec<usize> = Vec::new();
+    for index in cells {
+        return_grid[index] = 0;
+        let (mut solution_count, mut steps_left) = (0, MAX_REMOVAL_STEPS);
+        count_completions(
+            &mut return_grid,
+            size as usize,
+            &peers,
+            2,
+            &mut solution_count,
+            &mut None,
+            &mut steps_left,
+        );
+        if solution_count == 1 {
+            removed.push(index);
+        } else {
+            return_grid[index] = grid[index];
+        }
+    }
+
+    // easy puzzles get size to 2 * size clues back, medium size / 2 to size and hard fewer
+    // than size / 2. Small grids can leave a range empty, so they get its lower end.
+    let (fewest, most) = match difficulty {
+        0 => (size, 2 * size),
+        1 => (size / 2, size),
+        _ => (0, size / 2),
+    };
+    let mut n_additions = fewest;
+    if fewest < most {
+        n_additions = rng.random_range(fewest..most);
+    }
+    removed.shuffle(rng);
+    for &index in removed.iter().take(n_additions as usize) {
+        return_grid[index] = grid[index];
+    }
+    return return_grid;
+}
+
+/// This function checks if a value placed at a cell clashes with any other filled cell
+/// on the same row, column or line of the rules.
+/// - grid: the current grid state, with 0 for empty cells.
+/// - row: the row of the cell.
+/// - col: the column of the cell.
+/// - value: the value to place.
+/// - size: the size of the grid.
+/// - rules: the lines that can't repeat a value.
+fn clashes(
+    grid: &Vec<u32>,
+    row: usize,
+    col: usize,
+    value: u32,
+    size: usize,
+    rules: &NumbersRules,
+) -> bool {
+    for r in 0..size {
+        for c in 0..size {
+            if (r, c) == (row, col) || grid[r * size + c] != value {
+                continue;
+            }
+            if rules.linked((r, c), (row, col), size) {
+                return true;
+            }
+        }
+    }
+    return false;
+}
+
+/// This function finds the values that can go in a cell without clashing with the filled cells.
+/// - grid: the current grid state, with 0 for empty cells.
+/// - row: the row of the cell.
+/// - col: the column of the cell.
+/// - size: the size of the grid.
+pub fn candidates(grid: &Vec<u32>, row: usize, col: usize, size: usize) -> Vec<u32> {
+    return ruled_candidates(grid, row, col, size, &NumbersRules::default());
+}
+
+/// This function finds the values that can go in a cell without clashing with the filled cells
+/// under the rules.
+/// - grid: the current grid state, with 0 for empty cells.
+/// - row: the row of the cell.
+/// - col: the column of the cell.
+/// - size: the size of the grid.
+/// - rules: the lines that can't repeat a value.
+pub fn ruled_candidates(
+    grid: &Vec<u32>,
+    row: usize,
+    col: usize,
+    size: usize,
+    rules: &NumbersRules,
+) -> Vec<u32> {
+    return (1..=size as u32)
+        .filter(|&value| !clashes(grid, row, col, value, size, rules))
+        .collect();
+}
+
+/// This function checks that the given numbers are in range and don't clash with one another.
+/// - grid: the grid of givens, with 0 for empty cells.
+/// - size: the size of the grid.
+pub fn validate_numbers_clues(grid: &Vec<u32>, size: u32) -> Result<(), String> {
+    return validate_ruled_numbers_clues(grid, size, &NumbersRules::default());
+}
+
+/// This function checks that the given numbers are in range, don't clash with one another under
+/// the rules and can be completed.
+/// - grid: the grid of givens, with 0 for empty cells.
+/// - size: the size of the grid.
+/// - rules: the lines that can't repeat a value.
+pub fn validate_ruled_numbers_clues(
+    grid: &Vec<u32>,
+    size: u32,
+    rules: &NumbersRules,
+) -> Result<(), String> {
+    rules.validate(size)?;
+    if grid.len() != (size * size) as usize {
+        return Err(format!(
+            "expected {} cells for a {}x{} grid but found {}",
+            size * size,
+            size,
+            size,
+            grid.len()
+        ));
+    }
+    for (index, &value) in grid.iter().enumerate() {
+        if value > size {
+            return Err(format!("the value {} is too large for the grid", value));
+        }
+        let (row, col) = (index / size as usize, index % size as usize);
+        if value != 0 && clashes(grid, row, col, value, size as usize, rules) {
+            return Err(format!(
+                "the {} at row {} column {} clashes with another clue",
+                value,
+                row + 1,
+                col + 1
+            ));
+        }
+    }
+    let mut working_grid = grid.clone();
+    let (mut solution_count, mut steps_left) = (0, MAX_CHECK_STEPS);
+    count_completions(
+        &mut working_grid,
+        size as usize,
+        &rules.peers(size as usize),
+        1,
+        &mut solution_count,
+        &mut None,
+        &mut steps_left,
+    );
+    if steps_left == 0 {
+        return Err("the clues take too long to check".to_string());
+    }
+    if solution_count == 0 {
+        return Err("the clues have no solution".to_string());
+    }
+    return Ok(());
+}
+
+/// This function counts the ways the grid of givens can be completed, stopping once the limit is reached.
+/// - grid: the grid of givens, with 0 for empty cells.
+/// - size: the size of the grid.
+/// - limit: the number of solutions after which we stop looking.
+pub fn count_numbers_solutions(grid: &Vec<u32>, size: u32, limit: u32) -> u32 {
+    return count_ruled_numbers_solutions(grid, size, &NumbersRules::default(), limit);
+}
+
+/// This function counts the ways the grid of givens can be completed under the rules,
+/// stopping once the limit is reached.
+/// - grid: the grid of givens, with 0 for empty cells.
+/// - size: the size of the grid.
+/// - rules: the lines that can't repeat a value.
+/// - limit: the number of solutions after which we stop looking.
+pub fn count_ruled_numbers_solutions(
+    grid: &Vec<u32>,
+    size: u32,
+    rules: &NumbersRules,
+    limit: u32,
+) -> u32 {
+    if rules.validate(size).is_err() {
+        return 0;
+    }
+    let mut working_grid = grid.clone();
+    let (mut solution_count, mut steps_left) = (0, u32::MAX);
+    count_completions(
+        &mut working_grid,
+        size as usize,
+        &rules.peers(size as usize),
+        limit,
+        &mut solution_count,
+        &mut None,
+        &mut steps_left,
+    );
+    return solution_count;
+}
+
+/// This function finds a completion of the grid of givens.
+/// - grid: the grid of givens, with 0 for empty cells.
+/// - size: the size of the grid.
+pub fn find_numbers_solution(grid: &Vec<u32>, size: u32) -> Option<Vec<u32>> {
+    return find_ruled_numbers_solution(grid, size, &NumbersRules::default());
+}
+
+/// This function finds a completion of the grid of givens under the rules.
+/// - grid: the grid of givens, with 0 for empty cells.
+/// - size: the size of the grid.
+/// - rules: the lines that can't repeat a value.
+pub fn find_ruled_numbers_solution(
+    grid: &Vec<u32>,
+    size: u32,
+    rules: &NumbersRules,
+) -> Option<Vec<u32>> {
+    if rules.validate(size).is_err() {
+        return None;
+    }
+    let mut working_grid = grid.clone();
+    let (mut first_solution, mut steps_left) = (None, u32::MAX);
+    count_completions(
+        &mut working_grid,
+        size as usize,
+        &rules.peers(size as usize),
+        1,
+        &mut 0,
+        &mut first_solution,
+        &mut steps_left,
+    );
+    return first_solution;
+}
+
+/// This function recursively fills the empty cell with the fewest options, counting each complete grid.
+/// - grid: the current grid state.
+/// - size: the size of the grid.
+/// - peers: the cells linked to each cell.
+/// - limit: the number of solutions after which we stop looking.
+/// - solution_count: the current number of solutions found.
+/// - first_solution: the first complete grid found.
+/// - steps_left: the number of cells that can still be filled. If it runs out the count is set
+///   to the limit, as the grid can't be shown to have fewer solutions.
+fn count_completions(
+    grid: &mut Vec<u32>,
+    size: usize,
+    peers: &Vec<Vec<usize>>,
+    limit: u32,
+    solution_count: &mut u32,
+    first_solution: &mut Option<Vec<u32>>,
+    steps_left: &mut u32,
+) {
+    if *solution_count >= limit {
+        return;
+    }
+    if *steps_left == 0 {
+        *solution_count = limit;
+        return;
+    }
+    *steps_left -= 1;
+    // find the most constrained empty cell
+    let all_values: u64 = ((1 << (size + 1)) - 1) & !1;
+    let mut best: Option<(usize, u64)> = None;
+    for index in 0..size * size {
+        if grid[index] != 0 {
+            continue;
+        }
+        let options = all_values & !used_values(grid, index, peers);
+        if best.is_none_or(|(_, best_options)| options.count_ones() < best_options.count_ones()) {
+            best = Some((index, options));
+            if options == 0 {
+                break;
+            }
+        }
+    }
+    let Some((index, options)) = best else {
+        if first_solution.is_none() {
+            *first_solution = Some(grid.clone());
+        }
+        *solution_count += 1;
+        return;
+    };
+    for value in (1..=size as u32).filter(|&value| options & (1 << value) != 0) {
+        grid[index] = value;
+        count_completions(
+            grid,
+            size,
+            peers,
+            limit,
+            solution_count,
+            first_solution,
+            steps_left,
+        );
+        grid[index] = 0;
+    }
+}
+
+/// This function checks if the solution is valid for a numbers game played under the rules:
+/// every value is in range and none repeats along a row, column or line of the rules.
+/// - number_grid: the grid of numbers.
+/// - size: the size of the grid.
+/// - rules: the lines that can't repeat a value.
+pub fn check_solution_ruled_numbers(numbers_grid: &[u32], size: u32, rules: &NumbersRules) -> bool {
+    if rules.validate(size).is_err() {
+        return false;
+    }
+    let size = size as usize;
+    if numbers_grid.len() != size * size {
+        return false;
+    }
+    for index in 0..size * size {
+        let value = numbers_grid[index];
+        if value == 0 || value > size as u32 {
+            return false;
+        }
+        let cell = (index / size, index % size);
+        for (other, &other_value) in numbers_grid.iter().enumerate().skip(index + 1) {
+            if other_value == value && rules.linked(cell, (other / size, other % size), size) {
+                return false;
+            }
+        }
+    }
+    return true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_numbers_rules() {
+        // no size 4 grid has every diagonal distinct, but the other rules can be met
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(
+            generate_ruled_numbers_grid(4, &NumbersRules::AllDiagonals, &mut rng)
+                .iter()
+                .all(|&value| value == 0)
+        );
+        for rules in [NumbersRules::Latin, NumbersRules::XLatin] {
+            let grid = generate_ruled_numbers_grid(6, &rules, &mut rng);
+            assert!(check_solution_ruled_numbers(&grid, 6, &rules));
+            let givens = remove_ruled_values(&grid, 2, 6, &rules, &mut rng);
+            assert_eq!(count_ruled_numbers_solutions(&givens, 6, &rules, 2), 1);
+            assert_eq!(find_ruled_numbers_solution(&givens, 6, &rules), Some(grid));
+        }
+        // boxes that don't cover the grid are turned away instead of read past their end
+        let short_boxes = NumbersRules::Boxes(vec![1, 1, 2, 2]);
+        assert!(short_boxes.validate(4).is_err());
+        assert!(generate_ruled_numbers_grid(4, &short_boxes, &mut rng)
+            .iter()
+            .all(|&value| value == 0));
+        // the value masks only have room for grids up to 62
+        assert!(validate_ruled_numbers_clues(&vec![0; 64 * 64], 64, &NumbersRules::Latin).is_err());
+        assert_eq!(
+            count_ruled_numbers_solutions(&vec![0; 64 * 64], 64, &NumbersRules::Latin, 1),
+            0
+        );
+        // the tiniest grids leave some difficulties no range of clues to put back
+        for difficulty in 0..3 {
+            let givens =
+                remove_ruled_values(&vec![1], difficulty, 1, &NumbersRules::Latin, &mut rng);
+            assert_eq!(
+                count_ruled_numbers_solutions(&givens, 1, &NumbersRules::Latin, 2),
+                1
+            );
+            assert!(
+                remove_ruled_values(&vec![], difficulty, 0, &NumbersRules::Latin, &mut rng)
+                    .is_empty()
+            );
+        }
+        // short grids are never solutions
+        assert!(!check_solution_ruled_numbers(
+            &[1, 2],
+            2,
+            &NumbersRules::Latin
+        ));
+        // a cyclic Latin square repeats along its diagonals
+        let cyclic: Vec<u32> = (0..16).map(|i| (i / 4 + i % 4) % 4 + 1).collect();
+        assert!(check_solution_ruled_numbers(
+            &cyclic,
+            4,
+            &NumbersRules::Latin
+        ));
+        assert!(!check_solution_ruled_numbers(
+            &cyclic,
+            4,
+            &NumbersRules::XLatin
+        ));
+    }
+}
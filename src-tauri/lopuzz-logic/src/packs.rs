@@ -0,0 +1,295 @@
+use crate::puzzle_file::{load_puzzle, PuzzleFile};
+use crate::utilities::GameKind;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// The name of the manifest file at the top of each pack directory.
+pub const MANIFEST_FILE: &str = "pack.json";
+
+/// The current version of the pack manifest schema.
+pub const PACK_SCHEMA_VERSION: u32 = 1;
+
+/// This struct is the manifest describing a pack. It sits in the pack's directory next to the puzzles.
+/// - schema_version: the version of the manifest schema.
+/// - id: the unique id of the pack, used to track progress.
+/// - title: the name shown to the player.
+/// - description: a short description of the pack.
+/// - game: the game every puzzle in the pack is for.
+/// - puzzles: the puzzle files, relative to the pack directory, in the order they are played.
+///   They must be plain relative paths that stay inside the pack directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackManifest {
+    pub schema_version: u32,
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub game: GameKind,
+    pub puzzles: Vec<String>,
+}
+
+/// This struct is a pack that has been found on disk.
+/// - manifest: the pack's manifest.
+/// - directory: the directory the pack was loaded from.
+#[derive(Debug, Clone)]
+pub struct Pack {
+    pub manifest: PackManifest,
+    pub directory: PathBuf,
+}
+
+/// This struct is what the frontend is shown for each pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackSummary {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub game: GameKind,
+    pub n_puzzles: usize,
+    pub n_solved: usize,
+}
+
+/// This struct is what the frontend is shown when listing packs.
+/// - packs: a summary of each pack found.
+/// - skipped: why each pack that couldn't be read was left out.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackListing {
+    pub packs: Vec<PackSummary>,
+    pub skipped: Vec<String>,
+}
+
+/// This struct is a puzzle from a pack, along with where it sits in the pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackPuzzle {
+    pub pack_id: String,
+    pub index: usize,
+    pub puzzle: PuzzleFile,
+}
+
+/// This struct keeps track of which puzzles of each pack have been solved.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PackProgress {
+    solved: HashMap<String, BTreeSet<usize>>,
+}
+
+impl Pack {
+    /// This function loads a pack from its directory.
+    /// - directory: the directory containing the manifest.
+    pub fn load(directory: &Path) -> Result<Pack, String> {
+        let manifest_path = directory.join(MANIFEST_FILE);
+        let json = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("{}: {}", manifest_path.display(), e))?;
+        let manifest: PackManifest = serde_json::from_str(&json)
+            .map_err(|e| format!("{}: {}", manifest_path.display(), e))?;
+        if manifest.schema_version != PACK_SCHEMA_VERSION {
+            return Err(format!(
+                "{}: unsupported pack schema version {}",
+                manifest_path.display(),
+                manifest.schema_version
+            ));
+        }
+        for file in &manifest.puzzles {
+            check_puzzle_path(file).map_err(|e| format!("{}: {}", manifest_path.display(), e))?;
+        }
+        return Ok(Pack {
+            manifest,
+            directory: directory.to_path_buf(),
+        });
+    }
+
+    /// This function loads one of the pack's puzzles, checking it is for the pack's game.
+    /// - index: the position of the puzzle in the pack.
+    pub fn puzzle(&self, index: usize) -> Result<PuzzleFile, String> {
+        let Some(file) = self.manifest.puzzles.get(index) else {
+            return Err(format!("pack {} has no puzzle {}", self.manifest.id, index));
+        };
+        let puzzle = load_puzzle(&self.puzzle_path(file)?)?;
+        if puzzle.game != self.manifest.game {
+            return Err(format!(
+                "{} is a {} puzzle but pack {} is for {}",
+                file,
+                puzzle.game.name(),
+                self.manifest.id,
+                self.manifest.game.name()
+            ));
+        }
+        return Ok(puzzle);
+    }
+
+    /// This function finds a puzzle file of the pack on disk, checking that it is inside the
+    /// pack directory even once links are followed.
+    /// - file: the puzzle file as written in the manifest.
+    fn puzzle_path(&self, file: &str) -> Result<PathBuf, String> {
+        check_puzzle_path(file)?;
+        let path = self.directory.join(file);
+        let resolve = |path: &Path| {
+            return path
+                .canonicalize()
+                .map_err(|e| format!("{}: {}", path.display(), e));
+        };
+        let resolved = resolve(&path)?;
+        if !resolved.starts_with(resolve(&self.directory)?) {
+            return Err(format!("{} is outside pack {}", file, self.manifest.id));
+        }
+        return Ok(resolved);
+    }
+}
+
+/// This function checks that a puzzle file named in a manifest is a plain relative path, so a
+/// pack can't read files outside its own directory.
+/// - file: the puzzle file as written in the manifest.
+fn check_puzzle_path(file: &str) -> Result<(), String> {
+    let path = Path::new(file);
+    let plain = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if file.is_empty() || !plain {
+        return Err(format!("{} isn't a path inside the pack directory", file));
+    }
+    return Ok(());
+}
+
+impl PackProgress {
+    /// This function loads the progress file, starting afresh if there isn't one yet.
+    /// - path: the path of the progress file.
+    pub fn load(path: &Path) -> Result<PackProgress, String> {
+        if !path.exists() {
+            return Ok(PackProgress::default());
+        }
+        let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        return serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    /// This function saves the progress file, creating its directory if needed.
+    /// - path: the path of the progress file.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        return fs::write(path, json).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    /// This function records a puzzle as solved.
+    /// - pack_id: the id of the pack.
+    /// - index: the position of the puzzle in the pack.
+    pub fn mark_solved(&mut self, pack_id: &str, index: usize) {
+        self.solved
+            .entry(pack_id.to_string())
+            .or_default()
+            .insert(index);
+    }
+
+    /// This function finds the number of solved puzzles in a pack.
+    /// - pack: the pack.
+    pub fn n_solved(&self, pack: &Pack) -> usize {
+        let Some(solved) = self.solved.get(&pack.manifest.id) else {
+            return 0;
+        };
+        return solved
+            .iter()
+            .filter(|&&index| index < pack.manifest.puzzles.len())
+            .count();
+    }
+
+    /// This function finds the first puzzle in a pack that hasn't been solved yet.
+    /// - pack: the pack.
+    pub fn next_unsolved(&self, pack: &Pack) -> Option<usize> {
+        let solved = self.solved.get(&pack.manifest.id);
+        return (0..pack.manifest.puzzles.len())
+            .find(|index| solved.is_none_or(|solved| !solved.contains(index)));
+    }
+}
+
+/// This function finds every pack in the given directories. Each pack is a subdirectory with a manifest.
+/// When two packs share an id, the one found in the later directory wins, so user packs can replace bundled ones.
+/// Packs that can't be read are skipped, and why is given back alongside the packs.
+/// - directories: the directories to search, in order.
+pub fn find_packs(directories: &[PathBuf]) -> (Vec<Pack>, Vec<String>) {
+    let mut packs: Vec<Pack> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    for directory in directories {
+        let Ok(entries) = fs::read_dir(directory) else {
+            continue;
+        };
+        let mut pack_dirs: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.join(MANIFEST_FILE).is_file())
+            .collect();
+        pack_dirs.sort();
+        for pack_dir in pack_dirs {
+            match Pack::load(&pack_dir) {
+                Ok(pack) => {
+                    packs.retain(|p| p.manifest.id != pack.manifest.id);
+                    packs.push(pack);
+                }
+                Err(e) => skipped.push(e),
+            }
+        }
+    }
+    return (packs, skipped);
+}
+
+/// This function summarises the packs for the frontend.
+/// - packs: the packs.
+/// - progress: the player's progress.
+pub fn summarise_packs(packs: &[Pack], progress: &PackProgress) -> Vec<PackSummary> {
+    return packs
+        .iter()
+        .map(|pack| PackSummary {
+            id: pack.manifest.id.clone(),
+            title: pack.manifest.title.clone(),
+            description: pack.manifest.description.clone(),
+            game: pack.manifest.game,
+            n_puzzles: pack.manifest.puzzles.len(),
+            n_solved: progress.n_solved(pack),
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_unsolved() {
+        let pack = Pack {
+            manifest: PackManifest {
+                schema_version: PACK_SCHEMA_VERSION,
+                id: "test".to_string(),
+                title: "Test".to_string(),
+                description: String::new(),
+                game: GameKind::Numbers,
+                puzzles: vec!["a.json".to_string(), "b.json".to_string()],
+            },
+            directory: PathBuf::new(),
+        };
+        let mut progress = PackProgress::default();
+        assert_eq!(progress.next_unsolved(&pack), Some(0));
+        progress.mark_solved("test", 0);
+        assert_eq!(progress.next_unsolved(&pack), Some(1));
+        progress.mark_solved("test", 1);
+        // solved indices that no longer exist in the pack shouldn't count
+        progress.mark_solved("test", 5);
+        assert_eq!(progress.next_unsolved(&pack), None);
+        assert_eq!(progress.n_solved(&pack), 2);
+    }
+
+    #[test]
+    fn test_find_packs_skips_broken() {
+        let directory = std::env::temp_dir().join(format!("lopuzz-packs-{}", std::process::id()));
+        fs::create_dir_all(directory.join("broken")).unwrap();
+        fs::write(directory.join("broken").join(MANIFEST_FILE), "not json").unwrap();
+        // a manifest may not point outside its pack
+        fs::create_dir_all(directory.join("escaping")).unwrap();
+        let manifest = r#"{"schema_version": 1, "id": "escaping", "title": "Escaping", "game": "numbers", "puzzles": ["../secret.json"]}"#;
+        fs::write(directory.join("escaping").join(MANIFEST_FILE), manifest).unwrap();
+        let (packs, skipped) = find_packs(&[directory.clone()]);
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(packs.is_empty());
+        assert_eq!(skipped.len(), 2);
+        assert!(check_puzzle_path("/etc/passwd").is_err());
+        assert!(check_puzzle_path("puzzles/01.json").is_ok());
+    }
+}
@@ -0,0 +1,157 @@
+use serde::Serialize;
+
+/// This struct is a queens board along with the palette slot to draw each region in.
+/// - colour_grid: the grid of colours, numbered from 1.
+/// - rows: the number of rows, so boards that aren't square can be drawn.
+/// - cols: the number of columns.
+/// - palette_slots: the palette slot of each colour, numbered from 1, so `palette_slots[0]` is
+///   the slot of colour 1. Neighbouring regions never share a slot, and as few slots are used as possible.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueensBoard {
+    pub colour_grid: Vec<u32>,
+    pub rows: u32,
+    pub cols: u32,
+    pub palette_slots: Vec<u32>,
+}
+
+impl QueensBoard {
+    /// This function pairs a queens board with the palette slots for its regions.
+    /// - colour_grid: the grid of colours.
+    /// - cols: the number of columns in the grid.
+    pub fn new(colour_grid: Vec<u32>, cols: u32) -> Result<Self, String> {
+        let palette_slots = palette_colouring(&colour_grid, cols)?;
+        return Ok(QueensBoard {
+            rows: colour_grid.len() as u32 / cols,
+            cols,
+            colour_grid,
+            palette_slots,
+        });
+    }
+}
+
+/// This function finds which regions touch each other along an edge.
+/// Gives the neighbours of each colour, in order, so `adjacency[0]` holds the neighbours of colour 1.
+/// - colour_grid: the grid of colours, numbered from 1, with 0 for void cells.
+/// - cols: the number of columns in the grid.
+pub fn region_adjacency(colour_grid: &Vec<u32>, cols: u32) -> Result<Vec<Vec<u32>>, String> {
+    if cols == 0 || !colour_grid.len().is_multiple_of(cols as usize) {
+        return Err(format!(
+            "a grid of {} cells can't be split into rows of {}",
+            colour_grid.len(),
+            cols
+        ));
+    }
+    let n_colours = colour_grid.iter().copied().max().unwrap_or(0) as usize;
+    let mut adjacency: Vec<Vec<u32>> = vec![Vec::new(); n_colours];
+    let size = cols as usize;
+    for (index, &own) in colour_grid.iter().enumerate() {
+        // only looking right and down finds every shared edge once
+        let mut neighbours: Vec<usize> = Vec::new();
+        if index % size + 1 < size {
+            neighbours.push(index + 1);
+        }
+        if index + size < colour_grid.len() {
+            neighbours.push(index + size);
+        }
+        for other in neighbours {
+            let other = colour_grid[other];
+            if own == 0 || other == 0 || own == other {
+                continue;
+            }
+            if !adjacency[own as usize - 1].contains(&other) {
+                adjacency[own as usize - 1].push(other);
+                adjacency[other as usize - 1].push(own);
+            }
+        }
+    }
+    for neighbours in adjacency.iter_mut() {
+        neighbours.sort();
+    }
+    return Ok(adjacency);
+}
+
+/// This function gives each region a palette slot so that neighbouring regions never share one,
+/// using the fewest slots it can. Region maps never need more than four, so this stays quick.
+/// Gives the slot of each colour, numbered from 1.
+/// - colour_grid: the grid of colours, numbered from 1, with 0 for void cells.
+/// - cols: the number of columns in the grid.
+pub fn palette_colouring(colour_grid: &Vec<u32>, cols: u32) -> Result<Vec<u32>, String> {
+    let adjacency = region_adjacency(colour_grid, cols)?;
+    let n_colours = adjacency.len();
+    // the most connected regions are the hardest to fit, so they go first
+    let mut order: Vec<usize> = (0..n_colours).collect();
+    order.sort_by_key(|&colour| std::cmp::Reverse(adjacency[colour].len()));
+
+    let mut slots = vec![0; n_colours];
+    for n_slots in 1..=n_colours as u32 {
+        if assign_slots(&adjacency, &order, 0, n_slots, &mut slots) {
+            return Ok(slots);
+        }
+    }
+    return Ok(slots);
+}
+
+/// This function tries slots for the regions in order, backtracking when a region has no slot left.
+/// Gives whether every region was given a slot.
+/// - adjacency: the neighbours of each colour.
+/// - order: the order to give the regions slots in, as indices into `adjacency`.
+/// - position: how far through the order it is.
+/// - n_slots: how many slots can be used.
+/// - slots: the slot of each colour so far, 0 for none yet.
+fn assign_slots(
+    adjacency: &Vec<Vec<u32>>,
+    order: &[usize],
+    position: usize,
+    n_slots: u32,
+    slots: &mut Vec<u32>,
+) -> bool {
+    if position == order.len() {
+        return true;
+    }
+    let region = order[position];
+    for slot in 1..=n_slots {
+        let taken = adjacency[region]
+            .iter()
+            .any(|&neighbour| slots[neighbour as usize - 1] == slot);
+        if taken {
+            continue;
+        }
+        slots[region] = slot;
+        if assign_slots(adjacency, order, position + 1, n_slots, slots) {
+            return true;
+        }
+    }
+    slots[region] = 0;
+    return false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_colouring() {
+        let colour_grid: Vec<u32> = vec![
+            5, 5, 5, 2, 2, //
+            5, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 4, 4, 1, 2,
+        ];
+        let adjacency = region_adjacency(&colour_grid, 5).unwrap();
+        assert_eq!(adjacency[0], vec![2, 4]);
+        assert_eq!(adjacency[4], vec![2, 3, 4]);
+
+        let slots = palette_colouring(&colour_grid, 5).unwrap();
+        for (colour, neighbours) in adjacency.iter().enumerate() {
+            for &neighbour in neighbours {
+                assert_ne!(slots[colour], slots[neighbour as usize - 1]);
+            }
+        }
+        // 3, 4 and 5 all touch each other, so three slots is the fewest possible
+        assert_eq!(slots.iter().max(), Some(&3));
+
+        assert!(region_adjacency(&colour_grid, 0).is_err());
+        assert!(region_adjacency(&colour_grid[..7].to_vec(), 5).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt::Write;
+
+/// The width of an A4 page in points.
+pub const A4_WIDTH: f32 = 595.0;
+/// The height of an A4 page in points.
+pub const A4_HEIGHT: f32 = 842.0;
+
+/// The fonts every PDF reader has built in, so nothing needs embedding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Font {
+    Helvetica,
+    HelveticaBold,
+}
+
+impl Font {
+    /// This function gives the resource name the font is referred to by in page content.
+    fn resource(&self) -> &'static str {
+        match self {
+            Font::Helvetica => "F1",
+            Font::HelveticaBold => "F2",
+        }
+    }
+}
+
+/// This struct is a single page being drawn. Coordinates are in points from the bottom left corner.
+#[derive(Debug, Clone, Default)]
+pub struct Page {
+    content: String,
+}
+
+impl Page {
+    /// This function sets the colour used to fill shapes.
+    /// - rgb: the red, green and blue parts, from 0 to 1.
+    pub fn fill_colour(&mut self, rgb: (f32, f32, f32)) {
+        let _ = writeln!(self.content, "{:.3} {:.3} {:.3} rg", rgb.0, rgb.1, rgb.2);
+    }
+
+    /// This function sets the colour and width used to draw lines.
+    /// - rgb: the red, green and blue parts, from 0 to 1.
+    /// - width: the width of the line in points.
+    pub fn stroke_style(&mut self, rgb: (f32, f32, f32), width: f32) {
+        let _ = writeln!(
+            self.content,
+            "{:.3} {:.3} {:.3} RG {:.2} w",
+            rgb.0, rgb.1, rgb.2, width
+        );
+    }
+
+    /// This function fills a rectangle.
+    /// - x, y: the bottom left corner.
+    /// - width, height: the size of the rectangle.
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let _ = writeln!(
+            self.content,
+            "{:.2} {:.2} {:.2} {:.2} re f",
+            x, y, width, height
+        );
+    }
+
+    /// This function draws the outline of a rectangle.
+    /// - x, y: the bottom left corner.
+    /// - width, height: the size of the rectangle.
+    pub fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let _ = writeln!(
+            self.content,
+            "{:.2} {:.2} {:.2} {:.2} re S",
+            x, y, width, height
+        );
+    }
+
+    /// This function draws a straight line.
+    /// - x1, y1: the start of the line.
+    /// - x2, y2: the end of the line.
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        let _ = writeln!(
+            self.content,
+            "{:.2} {:.2} m {:.2} {:.2} l S",
+            x1, y1, x2, y2
+        );
+    }
+
+    /// This function fills a closed shape through the given points.
+    /// - points: the corners of the shape.
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)]) {
+        for (i, (x, y)) in points.iter().enumerate() {
+            let operator = if i == 0 { "m" } else { "l" };
+            let _ = writeln!(self.content, "{:.2} {:.2} {}", x, y, operator);
+        }
+        self.content.push_str("h f\n");
+    }
+
+    /// This function writes a line of text. Characters outside of ASCII are replaced with '?'.
+    /// - x, y: the start of the text's baseline.
+    /// - font: the font to write in.
+    /// - font_size: the size of the font in points.
+    /// - text: the text to write.
+    pub fn text(&mut self, x: f32, y: f32, font: Font, font_size: f32, text: &str) {
+        let mut escaped = String::new();
+        for character in text.chars() {
+            match character {
+                '(' | ')' | '\\' => {
+                    escaped.push('\\');
+                    escaped.push(character);
+                }
+                ' '..='~' => escaped.push(character),
+                _ => escaped.push('?'),
+            }
+        }
+        let _ = writeln!(
+            self.content,
+            "BT /{} {:.1} Tf {:.2} {:.2} Td ({}) Tj ET",
+            font.resource(),
+            font_size,
+            x,
+            y,
+            escaped
+        );
+    }
+}
+
+/// This function estimates the width of text, which is needed to centre it.
+/// Digits are exact for Helvetica, other characters use an average width.
+/// - text: the text.
+/// - font_size: the size of the font in points.
+pub fn text_width(text: &str, font_size: f32) -> f32 {
+    let ems: f32 = text
+        .chars()
+        .map(|c| match c {
+            '0'..='9' => 0.556,
+            ' ' => 0.278,
+            _ => 0.6,
+        })
+        .sum();
+    return ems * font_size;
+}
+
+/// This function puts pages together into a PDF file.
+/// - pages: the pages, in order.
+/// - width, height: the size of every page in points.
+pub fn write_pdf(pages: &[Page], width: f32, height: f32) -> Vec<u8> {
+    // objects 1 and 2 are the catalog and page tree, 3 and 4 the fonts,
+    // then each page is a page object followed by its content stream
+    let mut objects: Vec<String> = Vec::new();
+    objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", 5 + 2 * i))
+        .collect();
+    objects.push(format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        pages.len()
+    ));
+    objects.push(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    );
+    objects.push(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    );
+    for (i, page) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            width,
+            height,
+            6 + 2 * i
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            page.content.len(),
+            page.content
+        ));
+    }
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n".to_vec();
+    let mut offsets: Vec<usize> = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+    let xref_offset = pdf.len();
+    let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(xref, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        xref,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    );
+    pdf.extend(xref.as_bytes());
+    return pdf;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xref_offsets() {
+        let mut page = Page::default();
+        page.text(10.0, 10.0, Font::Helvetica, 12.0, "(hi)");
+        let pdf = write_pdf(&[page], A4_WIDTH, A4_HEIGHT);
+        let text = String::from_utf8(pdf).unwrap();
+        assert!(text.contains("\\(hi\\)"));
+        // every object must start exactly where the cross reference table says it does
+        let xref_start: usize = text.lines().rev().nth(1).unwrap().parse().unwrap();
+        let entries: Vec<usize> = text[xref_start..]
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+        assert_eq!(entries.len(), 6);
+        for (i, offset) in entries.iter().enumerate() {
+            assert!(text[*offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+}
@@ -0,0 +1,230 @@
+/// The first bytes of every PNG file.
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// The furthest back a deflate match can reach.
+const WINDOW: usize = 32768;
+/// The shortest and longest matches deflate can encode.
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// The first length of each deflate length code, from code 257, and its number of extra bits.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// The first distance of each deflate distance code, and its number of extra bits.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// This function encodes an image as a PNG file.
+/// - width, height: the size of the image in pixels.
+/// - rgb: the red, green and blue bytes of each pixel, row by row from the top.
+pub fn encode_png(width: u32, height: u32, rgb: &[u8]) -> Result<Vec<u8>, String> {
+    let stride = width as usize * 3;
+    if width == 0 || height == 0 || rgb.len() != stride * height as usize {
+        return Err(format!(
+            "expected {} bytes for a {}x{} image but found {}",
+            stride * height as usize,
+            width,
+            height,
+            rgb.len()
+        ));
+    }
+    // each row starts with the filter it uses, which is always none here
+    let mut raw: Vec<u8> = Vec::with_capacity((stride + 1) * height as usize);
+    for row in rgb.chunks(stride) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut header: Vec<u8> = Vec::new();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bits per channel, truecolour, then the standard compression, filtering and no interlacing
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib(&raw, stride + 1));
+    write_chunk(&mut png, b"IEND", &[]);
+    return Ok(png);
+}
+
+/// This function adds a chunk, with its length and checksum, to a PNG file.
+/// - png: the file being written.
+/// - kind: the four letter type of the chunk.
+/// - data: the contents of the chunk.
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// This function finds the CRC-32 checksum that PNG chunks end with.
+/// - data: the bytes to check.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xffff_ffff;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    return !crc;
+}
+
+/// This function finds the Adler-32 checksum that zlib streams end with.
+/// - data: the bytes to check.
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b): (u32, u32) = (1, 0);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    return (b << 16) | a;
+}
+
+/// This struct writes bits in the order deflate expects, starting from the lowest bit of each byte.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit: u32,
+}
+
+impl BitWriter {
+    /// This function writes a number, lowest bit first.
+    /// - value: the number.
+    /// - n_bits: how many of its bits to write.
+    fn write(&mut self, value: u32, n_bits: u32) {
+        for i in 0..n_bits {
+            if self.bit == 0 {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 1 << self.bit;
+            }
+            self.bit = (self.bit + 1) % 8;
+        }
+    }
+
+    /// This function writes a Huffman code, which deflate stores highest bit first.
+    /// - code: the code.
+    /// - n_bits: the length of the code.
+    fn write_code(&mut self, code: u32, n_bits: u32) {
+        for i in (0..n_bits).rev() {
+            self.write((code >> i) & 1, 1);
+        }
+    }
+
+    /// This function writes a literal byte or length code using deflate's fixed Huffman codes.
+    /// - symbol: the literal, 256 for the end of the block, or a length code.
+    fn write_symbol(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xc0 + symbol - 280, 8),
+        }
+    }
+}
+
+/// This function compresses data into a zlib stream with a single fixed Huffman block.
+/// Board images are mostly flat colour, so it only looks for repeats of the previous pixel
+/// and of the row above, which finds nearly everything worth finding.
+/// - data: the bytes to compress.
+/// - stride: the length of a row of the image, in bytes.
+fn zlib(data: &[u8], stride: usize) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    // the last block, compressed with the fixed codes
+    bits.write(1, 1);
+    bits.write(1, 2);
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0);
+        for distance in [3, stride] {
+            if distance > i || distance > WINDOW {
+                continue;
+            }
+            let mut length = 0;
+            while length < MAX_MATCH
+                && i + length < data.len()
+                && data[i + length] == data[i + length - distance]
+            {
+                length += 1;
+            }
+            if length > best.0 {
+                best = (length, distance);
+            }
+        }
+        let (length, distance) = best;
+        if length < MIN_MATCH {
+            bits.write_symbol(data[i] as u32);
+            i += 1;
+            continue;
+        }
+        let code = LENGTH_BASE
+            .iter()
+            .rposition(|&base| base as usize <= length)
+            .unwrap();
+        bits.write_symbol(257 + code as u32);
+        bits.write(
+            (length - LENGTH_BASE[code] as usize) as u32,
+            LENGTH_EXTRA[code] as u32,
+        );
+        let code = DISTANCE_BASE
+            .iter()
+            .rposition(|&base| base as usize <= distance)
+            .unwrap();
+        bits.write_code(code as u32, 5);
+        bits.write(
+            (distance - DISTANCE_BASE[code] as usize) as u32,
+            DISTANCE_EXTRA[code] as u32,
+        );
+        i += length;
+    }
+    bits.write_symbol(256);
+
+    // the zlib header says deflate with a 32K window, with check bits making it a multiple of 31
+    let mut stream = vec![0x78, 0x01];
+    stream.extend(bits.bytes);
+    stream.extend(adler32(data).to_be_bytes());
+    return stream;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        // the known checksums of the standard test string
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_encode_png() {
+        let rgb = vec![255; 4 * 4 * 3];
+        let png = encode_png(4, 4, &rgb).unwrap();
+        assert_eq!(png[..8], SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert!(png.ends_with(&[b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+        assert!(encode_png(4, 4, &rgb[1..]).is_err());
+    }
+}
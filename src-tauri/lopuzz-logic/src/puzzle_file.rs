@@ -0,0 +1,631 @@
+use crate::cages::{
+    check_cage_solution, count_cage_solutions, find_cage_solution, validate_cages, Cage,
+};
+use crate::clash::ClashRules;
+use crate::futoshiki::{
+    check_futoshiki_solution, count_futoshiki_solutions, find_futoshiki_solution,
+    validate_inequalities, FutoshikiBoard, Inequality,
+};
+use crate::numbers::{
+    check_solution_ruled_numbers, count_ruled_numbers_solutions, find_ruled_numbers_solution,
+    validate_ruled_numbers_clues, NumbersRules,
+};
+use crate::queens::{
+    check_queens_solution, count_queens_solutions, find_queens_solution, validate_queens_board,
+    BoardShape, QueensVariant,
+};
+use crate::rating::{solve_numbers_logically, solve_queens_logically, SolveReport};
+use crate::utilities::GameKind;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// The current version of the puzzle file schema.
+/// Bump this and add a step to `MIGRATIONS` whenever the schema changes.
+pub const PUZZLE_SCHEMA_VERSION: u32 = 3;
+
+/// A step that moves a puzzle, as untyped JSON, from one schema version to the next.
+type Migration = fn(Value) -> Result<Value, String>;
+
+/// The steps that move a file from one schema version to the next.
+/// `MIGRATIONS[i]` turns a version `i + 1` file into a version `i + 2` file.
+const MIGRATIONS: &[Migration] = &[add_default_rules, add_board_shape];
+
+/// This struct is a puzzle as it is stored on disk.
+/// - schema_version: the version of the schema the puzzle was written with.
+/// - game: the game the puzzle is for.
+/// - size: the size of the grid. The number of columns for a queens board with a shape.
+/// - stars: the number of stars in each row and region, for queens.
+/// - shape: the shape of a queens board that is a rectangle or has void cells, which are 0 in
+///   the colour grid. `None` for a full square board.
+/// - grid: the layout of the board. The colour grid for queens, empty for numbers.
+/// - givens: the values given to the player. Given queens (1s) for queens, the clues for numbers.
+/// - solution: the solved grid. Queens are 1s for queens, the filled grid for numbers.
+/// - seed: the seed the puzzle was generated from, if any.
+/// - difficulty: the difficulty rating of the puzzle, if known.
+/// - rules: the variant rules the puzzle is played with.
+/// - metadata: anything else about the puzzle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PuzzleFile {
+    pub schema_version: u32,
+    pub game: GameKind,
+    pub size: u32,
+    #[serde(default = "one_star")]
+    pub stars: u32,
+    #[serde(default)]
+    pub shape: Option<BoardShape>,
+    #[serde(default)]
+    pub grid: Vec<u32>,
+    #[serde(default)]
+    pub givens: Vec<u32>,
+    #[serde(default)]
+    pub solution: Option<Vec<u32>>,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub difficulty: Option<u32>,
+    #[serde(default)]
+    pub rules: PuzzleRules,
+    #[serde(default)]
+    pub metadata: PuzzleMetadata,
+}
+
+/// This struct holds the rules of a puzzle's variant. The defaults are the standard rules of each game.
+/// - clash: which queens clash with one another, for queens.
+/// - numbers: the lines that can't repeat a value, for numbers.
+/// - cages: the cages of a KenKen-style numbers game, if any.
+/// - inequalities: the inequality clues of a Futoshiki numbers game, if any.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PuzzleRules {
+    pub clash: ClashRules,
+    pub numbers: NumbersRules,
+    pub cages: Vec<Cage>,
+    pub inequalities: Vec<Inequality>,
+}
+
+impl PuzzleRules {
+    /// This function checks that the rules make sense for the game and the size of the grid.
+    /// - game: the game the puzzle is for.
+    /// - size: the size of the grid.
+    pub fn validate(&self, game: GameKind, size: u32) -> Result<(), String> {
+        match game {
+            GameKind::Queens => {
+                if self.numbers != NumbersRules::default()
+                    || !self.cages.is_empty()
+                    || !self.inequalities.is_empty()
+                {
+                    return Err(
+                        "numbers rules, cages and inequalities don't apply to queens".to_string(),
+                    );
+                }
+            }
+            GameKind::Numbers => {
+                if self.clash != ClashRules::default() {
+                    return Err("clash rules don't apply to numbers".to_string());
+                }
+                if !self.cages.is_empty() && !self.inequalities.is_empty() {
+                    return Err("a puzzle can't have both cages and inequalities".to_string());
+                }
+                self.numbers.validate(size)?;
+                if !self.cages.is_empty() {
+                    validate_cages(&self.cages, size)?;
+                }
+                validate_inequalities(&self.inequalities, size)?;
+            }
+        }
+        return Ok(());
+    }
+
+    /// This function checks that a board with these rules can be drawn from its grid alone.
+    /// Cages, inequalities and boxes need marks of their own that the drawings don't have yet.
+    pub fn check_drawable(&self) -> Result<(), String> {
+        if !self.cages.is_empty() || !self.inequalities.is_empty() {
+            return Err("puzzles with cages or inequalities can't be drawn yet".to_string());
+        }
+        if let NumbersRules::Boxes(_) = self.numbers {
+            return Err("puzzles with boxes can't be drawn yet".to_string());
+        }
+        return Ok(());
+    }
+}
+
+/// This struct holds the descriptive parts of a puzzle file. Every field is optional.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PuzzleMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub created: Option<String>,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+}
+
+impl PuzzleFile {
+    /// This function creates a puzzle file for a queens board.
+    /// - colour_grid: the grid of colours.
+    /// - size: the size of the grid.
+    pub fn queens(colour_grid: Vec<u32>, size: u32) -> PuzzleFile {
+        return PuzzleFile {
+            schema_version: PUZZLE_SCHEMA_VERSION,
+            game: GameKind::Queens,
+            size,
+            stars: 1,
+            shape: None,
+            grid: colour_grid,
+            givens: Vec::new(),
+            solution: None,
+            seed: None,
+            difficulty: None,
+            rules: PuzzleRules::default(),
+            metadata: PuzzleMetadata::default(),
+        };
+    }
+
+    /// This function creates a puzzle file for a numbers board.
+    /// - givens: the grid of clues, with 0 for empty cells.
+    /// - size: the size of the grid.
+    pub fn numbers(givens: Vec<u32>, size: u32) -> PuzzleFile {
+        return PuzzleFile {
+            schema_version: PUZZLE_SCHEMA_VERSION,
+            game: GameKind::Numbers,
+            size,
+            stars: 1,
+            shape: None,
+            grid: Vec::new(),
+            givens,
+            solution: None,
+            seed: None,
+            difficulty: None,
+            rules: PuzzleRules::default(),
+            metadata: PuzzleMetadata::default(),
+        };
+    }
+
+    /// This function creates a puzzle file for a board of any queens variant. The clash rules and
+    /// region limits of the variant aren't part of the board, so set the rules separately.
+    /// - colour_grid: the grid of colours, with 0 for void cells.
+    /// - variant: the variant the board is played with.
+    pub fn queens_variant_board(colour_grid: Vec<u32>, variant: &QueensVariant) -> PuzzleFile {
+        let shape = &variant.shape;
+        let square = shape.rows == shape.cols && !shape.void.contains(&true);
+        return PuzzleFile {
+            stars: variant.stars,
+            shape: if square { None } else { Some(shape.clone()) },
+            ..PuzzleFile::queens(colour_grid, shape.cols)
+        };
+    }
+
+    /// This function creates a puzzle file for a board of either game with the standard rules.
+    /// - game: the game.
+    /// - board: the colour grid for queens, or the grid of givens for numbers.
+    /// - size: the size of the grid.
+    pub fn from_board(game: GameKind, board: Vec<u32>, size: u32) -> PuzzleFile {
+        match game {
+            GameKind::Queens => return PuzzleFile::queens(board, size),
+            GameKind::Numbers => return PuzzleFile::numbers(board, size),
+        }
+    }
+
+    /// This function gives the grid the player sees: the colour grid for queens, or the givens
+    /// for numbers.
+    pub fn board(&self) -> &Vec<u32> {
+        match self.game {
+            GameKind::Queens => return &self.grid,
+            GameKind::Numbers => return &self.givens,
+        }
+    }
+
+    /// This function checks that the puzzle is playable and that its parts agree with one another.
+    pub fn validate(&self) -> Result<(), String> {
+        let (rows, cols) = self.dimensions();
+        let n_cells = (rows * cols) as usize;
+        self.check_form()?;
+        match self.game {
+            GameKind::Queens => {
+                validate_queens_board(&self.grid, &self.queens_variant())?;
+                if !self.givens.is_empty() && self.givens.len() != n_cells {
+                    return Err(format!(
+                        "expected {} given cells but found {}",
+                        n_cells,
+                        self.givens.len()
+                    ));
+                }
+            }
+            GameKind::Numbers => {
+                if !self.rules.cages.is_empty() && self.givens.iter().any(|&value| value != 0) {
+                    return Err("cage puzzles don't have given numbers".to_string());
+                }
+                validate_ruled_numbers_clues(&self.givens, self.size, &self.rules.numbers)?;
+            }
+        }
+        if let Some(solution) = &self.solution {
+            if !self.check_solution(solution) {
+                return Err("the solution doesn't solve the board".to_string());
+            }
+        }
+        return Ok(());
+    }
+
+    /// This function checks the parts of the puzzle that don't need solving: the rules, star count
+    /// and shape suit the game, and the board and solution have a cell for every square of the
+    /// grid. A puzzle that passes can be solved, but may have no solution or many.
+    pub fn check_form(&self) -> Result<(), String> {
+        self.rules.validate(self.game, self.size)?;
+        match self.game {
+            GameKind::Queens => {
+                if let Some(shape) = &self.shape {
+                    if shape.cols != self.size {
+                        return Err(format!(
+                            "a board with a shape has its number of columns, {}, as its size, not {}",
+                            shape.cols, self.size
+                        ));
+                    }
+                    if shape.void.len() != (shape.rows * shape.cols) as usize {
+                        return Err(format!(
+                            "expected {} cells in the shape of a {}x{} board but found {}",
+                            shape.rows * shape.cols,
+                            shape.rows,
+                            shape.cols,
+                            shape.void.len()
+                        ));
+                    }
+                }
+                self.queens_variant().check()?;
+            }
+            GameKind::Numbers => {
+                if self.stars != 1 || self.shape.is_some() {
+                    return Err("star counts and board shapes don't apply to numbers".to_string());
+                }
+            }
+        }
+        self.check_cells()?;
+        if self.game == GameKind::Queens && !self.queens_variant().fits(&self.grid) {
+            return Err("the void cells of the board don't match its shape".to_string());
+        }
+        return Ok(());
+    }
+
+    /// This function checks that the board, and the solution if there is one, have a cell for
+    /// every square of the grid, so they can be drawn and solved without checking the rest.
+    pub fn check_cells(&self) -> Result<(), String> {
+        let (rows, cols) = self.dimensions();
+        let n_cells = (rows * cols) as usize;
+        if self.board().len() != n_cells {
+            return Err(format!(
+                "expected {} cells for a {}x{} grid but found {}",
+                n_cells,
+                rows,
+                cols,
+                self.board().len()
+            ));
+        }
+        if let Some(solution) = &self.solution {
+            if solution.len() != n_cells {
+                return Err(format!(
+                    "expected {} cells in the solution but found {}",
+                    n_cells,
+                    solution.len()
+                ));
+            }
+        }
+        return Ok(());
+    }
+
+    /// This function checks if a grid solves the puzzle.
+    /// - solution: the solved grid, in the same form as the `solution` field.
+    pub fn check_solution(&self, solution: &Vec<u32>) -> bool {
+        let (rows, cols) = self.dimensions();
+        let n_cells = (rows * cols) as usize;
+        if solution.len() != n_cells {
+            return false;
+        }
+        match self.game {
+            GameKind::Queens => {
+                let queens: Vec<u32> = (0..n_cells as u32)
+                    .filter(|&i| solution[i as usize] == 1)
+                    .collect();
+                return check_queens_solution(&self.grid, &queens, &self.queens_variant());
+            }
+            GameKind::Numbers => {
+                let rules = &self.rules;
+                if !rules.cages.is_empty() {
+                    return check_cage_solution(solution, &rules.cages, self.size, &rules.numbers);
+                }
+                if !rules.inequalities.is_empty() {
+                    return check_futoshiki_solution(
+                        solution,
+                        &self.futoshiki_board(),
+                        self.size,
+                        &rules.numbers,
+                    );
+                }
+                let agrees = self
+                    .givens
+                    .iter()
+                    .zip(solution)
+                    .all(|(&given, &value)| given == 0 || given == value);
+                return agrees && check_solution_ruled_numbers(solution, self.size, &rules.numbers);
+            }
+        }
+    }
+
+    /// This function counts the solutions of the puzzle under its rules, stopping once the limit
+    /// is reached.
+    /// - limit: the number of solutions after which we stop looking.
+    pub fn count_solutions(&self, limit: u32) -> u32 {
+        let rules = &self.rules;
+        match self.game {
+            GameKind::Queens => {
+                return count_queens_solutions(&self.grid, &self.queens_variant(), limit);
+            }
+            GameKind::Numbers => {
+                if !rules.cages.is_empty() {
+                    return count_cage_solutions(&rules.cages, self.size, &rules.numbers, limit);
+                }
+                if !rules.inequalities.is_empty() {
+                    return count_futoshiki_solutions(
+                        &self.futoshiki_board(),
+                        self.size,
+                        &rules.numbers,
+                        limit,
+                    );
+                }
+                return count_ruled_numbers_solutions(
+                    &self.givens,
+                    self.size,
+                    &rules.numbers,
+                    limit,
+                );
+            }
+        }
+    }
+
+    /// This function finds a solution of the puzzle under its rules, in the same form as the
+    /// `solution` field.
+    pub fn find_solution(&self) -> Option<Vec<u32>> {
+        let rules = &self.rules;
+        match self.game {
+            GameKind::Queens => {
+                return find_queens_solution(&self.grid, &self.queens_variant());
+            }
+            GameKind::Numbers => {
+                if !rules.cages.is_empty() {
+                    return find_cage_solution(&rules.cages, self.size, &rules.numbers);
+                }
+                if !rules.inequalities.is_empty() {
+                    return find_futoshiki_solution(
+                        &self.futoshiki_board(),
+                        self.size,
+                        &rules.numbers,
+                    );
+                }
+                return find_ruled_numbers_solution(&self.givens, self.size, &rules.numbers);
+            }
+        }
+    }
+
+    /// This function solves the puzzle the way a person would to rate it.
+    /// Gives `None` for variants, as the logical solvers only know the standard rules on a
+    /// square board with one queen.
+    pub fn rate(&self) -> Option<SolveReport> {
+        if self.rules != PuzzleRules::default() || self.stars != 1 || self.shape.is_some() {
+            return None;
+        }
+        match self.game {
+            GameKind::Queens => return Some(solve_queens_logically(&self.grid, self.size)),
+            GameKind::Numbers => return Some(solve_numbers_logically(&self.givens, self.size)),
+        }
+    }
+
+    /// This function checks that the puzzle can be drawn from its grid alone.
+    /// Only full square boards can be drawn so far.
+    pub fn check_drawable(&self) -> Result<(), String> {
+        self.rules.check_drawable()?;
+        if self.shape.is_some() {
+            return Err("boards with a shape can't be drawn yet".to_string());
+        }
+        return Ok(());
+    }
+
+    /// This function gives the number of rows and columns of the grid.
+    pub fn dimensions(&self) -> (u32, u32) {
+        match &self.shape {
+            Some(shape) => return (shape.rows, shape.cols),
+            None => return (self.size, self.size),
+        }
+    }
+
+    /// This function gives the queens variant the puzzle is played with.
+    pub fn queens_variant(&self) -> QueensVariant {
+        return QueensVariant {
+            shape: self.shape.clone().unwrap_or(BoardShape::square(self.size)),
+            stars: self.stars,
+            clash: self.rules.clash,
+            ..QueensVariant::queens(self.size)
+        };
+    }
+
+    /// This function gives the puzzle as a Futoshiki board, with its givens and inequalities.
+    fn futoshiki_board(&self) -> FutoshikiBoard {
+        return FutoshikiBoard {
+            givens: self.givens.clone(),
+            inequalities: self.rules.inequalities.clone(),
+        };
+    }
+}
+
+/// This function reads a puzzle from JSON, migrating older schema versions to the current one.
+/// - json: the JSON text.
+pub fn puzzle_from_json(json: &str) -> Result<PuzzleFile, String> {
+    let puzzle = parse_puzzle_json(json)?;
+    puzzle.validate()?;
+    return Ok(puzzle);
+}
+
+/// This function reads a puzzle from JSON, checking its form but not that it has a single
+/// solution. Useful for inspecting puzzles that may be broken.
+/// - json: the JSON text.
+pub fn parse_puzzle_json(json: &str) -> Result<PuzzleFile, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let value = migrate(value)?;
+    let puzzle: PuzzleFile = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    puzzle.check_form()?;
+    return Ok(puzzle);
+}
+
+/// This function writes a puzzle as JSON.
+/// - puzzle: the puzzle to write.
+pub fn puzzle_to_json(puzzle: &PuzzleFile) -> Result<String, String> {
+    puzzle.validate()?;
+    return serde_json::to_string_pretty(puzzle).map_err(|e| e.to_string());
+}
+
+/// This function loads a puzzle file from disk.
+/// - path: the path of the file.
+pub fn load_puzzle(path: &Path) -> Result<PuzzleFile, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    return puzzle_from_json(&json).map_err(|e| format!("{}: {}", path.display(), e));
+}
+
+/// This function saves a puzzle file to disk.
+/// - path: the path of the file.
+/// - puzzle: the puzzle to save.
+pub fn save_puzzle(path: &Path, puzzle: &PuzzleFile) -> Result<(), String> {
+    let json = puzzle_to_json(puzzle)?;
+    return fs::write(path, json).map_err(|e| format!("{}: {}", path.display(), e));
+}
+
+/// This function brings a puzzle up to the current schema version, one version at a time.
+/// - value: the puzzle as untyped JSON.
+fn migrate(mut value: Value) -> Result<Value, String> {
+    let Some(mut version) = value.get("schema_version").and_then(Value::as_u64) else {
+        return Err("the puzzle has no schema_version".to_string());
+    };
+    if version == 0 || version > PUZZLE_SCHEMA_VERSION as u64 {
+        return Err(format!(
+            "unsupported puzzle schema version {} (this version of the app reads up to {})",
+            version, PUZZLE_SCHEMA_VERSION
+        ));
+    }
+    while version < PUZZLE_SCHEMA_VERSION as u64 {
+        let Some(step) = MIGRATIONS.get((version - 1) as usize) else {
+            return Err(format!(
+                "there is no way to upgrade schema version {}",
+                version
+            ));
+        };
+        value = step(value)?;
+        version += 1;
+        value["schema_version"] = Value::from(version);
+    }
+    return Ok(value);
+}
+
+/// This function moves a puzzle from schema version 1 to 2, which added variant rules.
+/// Version 1 files were all played with the standard rules.
+/// - value: the puzzle as untyped JSON.
+fn add_default_rules(mut value: Value) -> Result<Value, String> {
+    let rules = serde_json::to_value(PuzzleRules::default()).map_err(|e| e.to_string())?;
+    let Some(object) = value.as_object_mut() else {
+        return Err("the puzzle isn't a JSON object".to_string());
+    };
+    object.entry("rules").or_insert(rules);
+    return Ok(value);
+}
+
+/// This function moves a puzzle from schema version 2 to 3, which added star counts and board
+/// shapes. Version 2 files were all full square boards with one queen.
+/// - value: the puzzle as untyped JSON.
+fn add_board_shape(mut value: Value) -> Result<Value, String> {
+    let Some(object) = value.as_object_mut() else {
+        return Err("the puzzle isn't a JSON object".to_string());
+    };
+    object.entry("stars").or_insert(Value::from(1));
+    object.entry("shape").or_insert(Value::Null);
+    return Ok(value);
+}
+
+/// This function gives the star count of puzzle files that don't say, which is one.
+fn one_star() -> u32 {
+    return 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut puzzle = PuzzleFile::numbers(
+            vec![
+                1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+            5,
+        );
+        puzzle.seed = Some(7);
+        puzzle.rules.numbers = NumbersRules::Latin;
+        puzzle.metadata.title = Some("test".to_string());
+        let json = puzzle_to_json(&puzzle).unwrap();
+        assert_eq!(puzzle_from_json(&json).unwrap(), puzzle);
+
+        // a Latin square breaks the diagonal rule, so it only solves the puzzle under its own rules
+        let solution = puzzle.find_solution().unwrap();
+        assert!(puzzle.check_solution(&solution));
+        puzzle.rules.numbers = NumbersRules::default();
+        assert!(!puzzle.check_solution(&solution));
+
+        puzzle.rules.clash.wrap = true;
+        assert!(puzzle.validate().is_err());
+    }
+
+    #[test]
+    fn test_minimal_and_unsupported_files() {
+        let minimal = r#"{"schema_version": 1, "game": "numbers", "size": 5, "givens": [0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0], "future_field": true}"#;
+        let puzzle = puzzle_from_json(minimal).unwrap();
+        assert_eq!(puzzle.schema_version, PUZZLE_SCHEMA_VERSION);
+        assert_eq!(puzzle.rules, PuzzleRules::default());
+        assert_eq!(puzzle.metadata, PuzzleMetadata::default());
+
+        let newer = minimal.replace("\"schema_version\": 1", "\"schema_version\": 99");
+        assert!(puzzle_from_json(&newer).is_err());
+    }
+
+    #[test]
+    fn test_shaped_board() {
+        // a 6x3 board with a void corner and one star in each row, two to a column
+        let colour_grid = vec![1, 1, 2, 1, 3, 2, 4, 3, 2, 4, 3, 5, 4, 6, 5, 0, 6, 5];
+        let variant = QueensVariant {
+            shape: BoardShape::of_grid(&colour_grid, 6, 3),
+            ..QueensVariant::queens(6)
+        };
+        let puzzle = PuzzleFile::queens_variant_board(colour_grid, &variant);
+        assert_eq!((puzzle.size, puzzle.dimensions()), (3, (6, 3)));
+        assert_eq!(puzzle.queens_variant(), variant);
+        assert!(puzzle.check_drawable().is_err());
+        assert_eq!(puzzle.rate(), None);
+
+        // the shape and star count survive a trip through JSON, but a grid that doesn't match the
+        // shape is turned away when read
+        let mut json: Value = serde_json::to_value(&puzzle).unwrap();
+        assert_eq!(parse_puzzle_json(&json.to_string()).unwrap(), puzzle);
+        json["grid"][15] = Value::from(6);
+        assert!(parse_puzzle_json(&json.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_version_2_files() {
+        let version_2 =
+            r#"{"schema_version": 2, "game": "queens", "size": 1, "grid": [1], "rules": {}}"#;
+        let puzzle = puzzle_from_json(version_2).unwrap();
+        assert_eq!(puzzle.schema_version, 3);
+        assert_eq!((puzzle.stars, puzzle.shape.clone()), (1, None));
+        assert_eq!(puzzle, PuzzleFile::queens(vec![1], 1));
+
+        // stars don't apply to numbers, so parsing turns the file away without solving it
+        let numbers =
+            r#"{"schema_version": 3, "game": "numbers", "size": 1, "givens": [0], "stars": 2}"#;
+        assert!(parse_puzzle_json(numbers).is_err());
+    }
+}
@@ -0,0 +1,1436 @@
+use std::vec;
+
+use crate::clash::ClashRules;
+use crate::regions::{rebalance_regions, RegionConstraints};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The number of boards to grow before giving up on meeting the region constraints.
+const MAX_GENERATION_ATTEMPTS: u32 = 100;
+
+/// The number of dead ends growing the regions of a board may reach before starting again.
+const MAX_IMPOSSIBLE_ROUTES: u32 = 20;
+
+/// The number of times to start growing the regions of a board again before giving up.
+const MAX_GROWING_ATTEMPTS: u32 = 20_000;
+
+/// The number of stars to try placing anywhere on the board before giving up on covering it.
+const MAX_SCATTER_STEPS: u32 = 100_000;
+
+/// This struct is the shape of a board that isn't a plain square: its size and which cells are void.
+/// Void cells can't hold a star and aren't part of any region, and are 0 in a colour grid.
+/// Rows and columns that are entirely void don't need any stars.
+/// A board with `stars` stars in every row and region shares its stars evenly between the columns,
+/// so a board with more columns than rows has fewer stars in each column. An 8x4 board with one
+/// star in each row has two in each column.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+/// - void: whether each cell is void, row by row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardShape {
+    pub rows: u32,
+    pub cols: u32,
+    pub void: Vec<bool>,
+}
+
+impl BoardShape {
+    /// This function gives the shape of a square board with no void cells.
+    /// - size: the size of the grid.
+    pub fn square(size: u32) -> BoardShape {
+        return BoardShape {
+            rows: size,
+            cols: size,
+            void: vec![false; (size * size) as usize],
+        };
+    }
+
+    /// This function reads the shape of a colour grid, where colour 0 marks a void cell.
+    /// - colour_grid: the grid of colours.
+    /// - rows: the number of rows.
+    /// - cols: the number of columns.
+    pub fn of_grid(colour_grid: &[u32], rows: u32, cols: u32) -> BoardShape {
+        return BoardShape {
+            rows,
+            cols,
+            void: colour_grid.iter().map(|&colour| colour == 0).collect(),
+        };
+    }
+
+    /// This function lists the cells in play of every row, then every column.
+    pub fn lines(&self) -> Vec<Vec<usize>> {
+        let (rows, cols) = (self.rows as usize, self.cols as usize);
+        let in_play = |&index: &usize| !self.void[index];
+        let row_lines = (0..rows).map(|row| {
+            (0..cols)
+                .map(|col| row * cols + col)
+                .filter(in_play)
+                .collect()
+        });
+        let col_lines = (0..cols).map(|col| {
+            (0..rows)
+                .map(|row| row * cols + col)
+                .filter(in_play)
+                .collect()
+        });
+        return row_lines.chain(col_lines).collect();
+    }
+
+    /// This function gives whether each row, then each column, has any cells to play in.
+    pub fn lines_in_play(&self) -> (Vec<bool>, Vec<bool>) {
+        let mut rows = vec![false; self.rows as usize];
+        let mut cols = vec![false; self.cols as usize];
+        for (index, &void) in self.void.iter().enumerate() {
+            if !void {
+                rows[index / self.cols as usize] = true;
+                cols[index % self.cols as usize] = true;
+            }
+        }
+        return (rows, cols);
+    }
+
+    /// This function checks that the counts work out for a board with `stars` stars in every row
+    /// and region, and gives the number of regions it needs, one for each row in play. The columns
+    /// in play hold the same stars between them, so they must be able to share them evenly.
+    /// - stars: the number of stars in each row and region.
+    pub fn check(&self, stars: u32) -> Result<u32, String> {
+        if self.void.len() != (self.rows * self.cols) as usize {
+            return Err(format!(
+                "expected {} cells for a {}x{} grid but found {}",
+                self.rows * self.cols,
+                self.rows,
+                self.cols,
+                self.void.len()
+            ));
+        }
+        if stars == 0 {
+            return Err("there must be at least one star in each row".to_string());
+        }
+        let (rows, cols) = self.lines_in_play();
+        let rows = rows.iter().filter(|&&in_play| in_play).count() as u32;
+        let cols = cols.iter().filter(|&&in_play| in_play).count() as u32;
+        if rows == 0 {
+            return Err("every cell is void".to_string());
+        }
+        if !(rows * stars).is_multiple_of(cols) {
+            return Err(format!(
+                "{} stars in {} rows can't be shared evenly between {} columns",
+                rows * stars,
+                rows,
+                cols
+            ));
+        }
+        return Ok(rows);
+    }
+
+    /// This function gives the number of stars each row, then each column, needs on a board with
+    /// `stars` stars in every row and region. Lines that are all void need none.
+    /// - stars: the number of stars in each row and region.
+    pub fn star_counts(&self, stars: u32) -> (Vec<u32>, Vec<u32>) {
+        let (rows_in_play, cols_in_play) = self.lines_in_play();
+        let n_rows = rows_in_play.iter().filter(|&&in_play| in_play).count() as u32;
+        let n_cols = cols_in_play.iter().filter(|&&in_play| in_play).count() as u32;
+        let col_stars = (n_rows * stars).checked_div(n_cols).unwrap_or(0);
+        let needs = |in_play: &bool, count: u32| if *in_play { count } else { 0 };
+        return (
+            rows_in_play
+                .iter()
+                .map(|in_play| needs(in_play, stars))
+                .collect(),
+            cols_in_play
+                .iter()
+                .map(|in_play| needs(in_play, col_stars))
+                .collect(),
+        );
+    }
+
+    /// This function finds the cells next to a cell, sharing an edge, that aren't void.
+    /// - index: the cell.
+    pub fn neighbours(&self, index: usize) -> Vec<usize> {
+        let (rows, cols) = (self.rows as i32, self.cols as i32);
+        let (row, col) = (index as i32 / cols, index as i32 % cols);
+        let mut neighbours: Vec<usize> = Vec::new();
+        for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (r, c) = (row + dr, col + dc);
+            if r < 0 || r >= rows || c < 0 || c >= cols {
+                continue;
+            }
+            let next = (r * cols + c) as usize;
+            if !self.void[next] {
+                neighbours.push(next);
+            }
+        }
+        return neighbours;
+    }
+
+    /// This function lists every pair of cells next to each other that aren't void, once each,
+    /// going along the rows with the edge to the right of a cell before the edge below it.
+    pub fn edges(&self) -> Vec<(u32, u32)> {
+        let mut edges: Vec<(u32, u32)> = Vec::new();
+        for index in 0..self.rows * self.cols {
+            if self.void[index as usize] {
+                continue;
+            }
+            for (next, on_board) in [
+                (index + 1, index % self.cols + 1 < self.cols),
+                (index + self.cols, index / self.cols + 1 < self.rows),
+            ] {
+                if on_board && !self.void[next as usize] {
+                    edges.push((index, next));
+                }
+            }
+        }
+        return edges;
+    }
+
+    /// This function checks that two cells are on the board and next to each other, for clues
+    /// and walls that sit on the edge between them.
+    /// - cell1: the first cell.
+    /// - cell2: the second cell.
+    pub fn check_edge(&self, cell1: u32, cell2: u32) -> Result<(), String> {
+        let n_cells = self.void.len();
+        let inside = (cell1 as usize) < n_cells && (cell2 as usize) < n_cells;
+        if !inside || !self.neighbours(cell1 as usize).contains(&(cell2 as usize)) {
+            return Err(format!(
+                "cells {} and {} aren't next to each other",
+                cell1, cell2
+            ));
+        }
+        return Ok(());
+    }
+}
+
+/// This struct is the variant of queens a board is played with. The usual queens puzzle is a
+/// square board with one star, called a queen, in every row, column and region.
+/// - shape: the shape of the board.
+/// - stars: the number of stars in each row and region.
+/// - clash: which stars clash with one another. Anything but the usual rules needs a square
+///   board and one star.
+/// - constraints: the limits on region sizes when generating a board.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueensVariant {
+    pub shape: BoardShape,
+    pub stars: u32,
+    #[serde(default)]
+    pub clash: ClashRules,
+    #[serde(default)]
+    pub constraints: RegionConstraints,
+}
+
+impl QueensVariant {
+    /// This function gives the usual queens puzzle on a square board, with no single cell regions
+    /// where the board is big enough to avoid them.
+    /// - size: the size of the grid.
+    pub fn queens(size: u32) -> QueensVariant {
+        return QueensVariant {
+            shape: BoardShape::square(size),
+            stars: 1,
+            clash: ClashRules::default(),
+            constraints: RegionConstraints {
+                allow_single_cells: size == 1,
+                ..RegionConstraints::default()
+            },
+        };
+    }
+
+    /// This function checks that boards can be made and solved for the variant, and gives the
+    /// number of regions a board needs.
+    pub fn check(&self) -> Result<u32, String> {
+        let n_regions = self.shape.check(self.stars)?;
+        let square = self.shape.rows == self.shape.cols;
+        if self.clash != ClashRules::default() && (!square || self.stars != 1) {
+            return Err(
+                "clash rules other than the usual ones need a square board and one star"
+                    .to_string(),
+            );
+        }
+        return Ok(n_regions);
+    }
+
+    /// This function checks whether a colour grid has the shape of the variant's board, with 0
+    /// in exactly the void cells.
+    /// - colour_grid: the grid of colours.
+    pub fn fits(&self, colour_grid: &[u32]) -> bool {
+        return colour_grid.len() == self.shape.void.len()
+            && colour_grid
+                .iter()
+                .zip(&self.shape.void)
+                .all(|(&colour, &void)| (colour == 0) == void);
+    }
+}
+
+/// This function generates a board for a queens variant, with every region connected, every
+/// region size within the variant's limits and a unique single solution.
+/// Void cells are 0 in the colour grid it gives.
+/// - variant: the variant to generate a board for.
+/// - rng: the random number generator to use.
+pub fn generate_queens_game<R: Rng>(
+    variant: &QueensVariant,
+    rng: &mut R,
+) -> Result<Vec<u32>, String> {
+    let n_regions = variant.check()?;
+    variant.constraints.check(&variant.shape, n_regions)?;
+    let (rows, cols) = (variant.shape.rows, variant.shape.cols);
+    for _ in 0..MAX_GENERATION_ATTEMPTS {
+        let mut colour_grid = grow_regions(variant, rng)?;
+        // growing can occasionally leave a region split or a second solution, so check before balancing
+        if !split_board_regions(&colour_grid, rows, cols).is_empty()
+            || count_queens_solutions(&colour_grid, variant, 2) != 1
+        {
+            continue;
+        }
+        if rebalance_regions(&mut colour_grid, variant) {
+            return Ok(colour_grid);
+        }
+    }
+    return Err(format!(
+        "couldn't generate a {}x{} board with {} stars meeting the region constraints",
+        rows, cols, variant.stars
+    ));
+}
+
+/// This function grows a region from each star of a random solution until the grid is coloured,
+/// starting again with new stars whenever growing gets stuck.
+/// - variant: the variant to grow a board for.
+/// - rng: the random number generator to use.
+fn grow_regions<R: Rng>(variant: &QueensVariant, rng: &mut R) -> Result<Vec<u32>, String> {
+    let (shape, stars, rules) = (&variant.shape, variant.stars, &variant.clash);
+    for _ in 0..MAX_GROWING_ATTEMPTS {
+        let star_grid = generate_grid(variant, rng);
+        if star_grid.iter().sum::<u32>() == 0 {
+            return Err(format!(
+                "there is no way to place {} stars in each row and region of this {}x{} board",
+                stars, shape.rows, shape.cols
+            ));
+        }
+        if let Some(colour_grid) = grow_from_stars(&star_grid, shape, stars, rules, rng) {
+            return Ok(colour_grid);
+        }
+    }
+    return Err(format!(
+        "couldn't grow regions with a single solution on this {}x{} board",
+        shape.rows, shape.cols
+    ));
+}
+
+/// This function grows the regions out from the stars of a solution until the board is coloured.
+/// Each cell only takes a colour if no other solution could then use it, so the stars it grew
+/// from stay the only solution. Gives nothing if the regions can't be started or growing gets stuck.
+/// - star_grid: the grid with 1 where each star goes.
+/// - shape: the shape of the board.
+/// - stars: the number of stars in each row and region.
+/// - rules: which stars clash with one another.
+/// - rng: the random number generator to use.
+fn grow_from_stars<R: Rng>(
+    star_grid: &Vec<u32>,
+    shape: &BoardShape,
+    stars: u32,
+    rules: &ClashRules,
+    rng: &mut R,
+) -> Option<Vec<u32>> {
+    // queue is used to keep track of what cells to colour in next
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    // seen is used to keep track of what cells have already been seen so we don't have duplicates in the queue
+    let mut seen: HashSet<usize> = HashSet::new();
+    let mut growth = RegionGrowth {
+        colour_grid: vec![0; star_grid.len()],
+        shape,
+        stars,
+        rules,
+        colour_counter: HashMap::new(),
+        impossible_routes_found: 0,
+    };
+    // for randomising colours
+    let n_regions = star_grid.iter().sum::<u32>() / stars;
+    let mut colours: Vec<u32> = (1..n_regions + 1).collect();
+    colours.shuffle(rng);
+    if stars == 1 {
+        // give each queen a different colour
+        let star_cells = (0..star_grid.len()).filter(|&index| star_grid[index] == 1);
+        for (index, &colour) in star_cells.zip(&colours) {
+            growth.get_neighbours(&mut queue, &mut seen, index);
+            growth.colour_counter.insert(colour, 1);
+            growth.colour_grid[index] = colour;
+        }
+    } else {
+        // stars sharing a region have to be joined up first, and the paths joining them
+        // mustn't already allow another solution
+        growth.colour_grid = seed_regions(star_grid, shape, stars, &colours, rng)?;
+        let mut search = StarSearch::new(&growth.colour_grid, shape, stars, *rules, 2);
+        search.run();
+        if search.solutions.len() != 1 {
+            return None;
+        }
+        for index in 0..star_grid.len() {
+            let colour = growth.colour_grid[index];
+            if colour != 0 {
+                *growth.colour_counter.entry(colour).or_insert(0) += 1;
+            }
+        }
+        for index in 0..star_grid.len() {
+            if growth.colour_grid[index] != 0 {
+                growth.get_neighbours(&mut queue, &mut seen, index);
+            }
+        }
+    }
+
+    // we start colouring recursively, using the queue containing the neighbours of the stars
+    if growth.colour_grid_recursively(queue, seen, rng) {
+        return Some(growth.colour_grid);
+    }
+    return None;
+}
+
+/// This function gives the stars of a solution their starting colours. Nearby stars are grouped,
+/// and each group is joined by a path of its colour so the region can grow as one piece.
+/// Gives nothing if a group can't be joined.
+/// - star_grid: the grid with 1 where each star goes.
+/// - shape: the shape of the board.
+/// - stars: the number of stars in each region.
+/// - colours: the colour of each group, in order.
+/// - rng: the random number generator to use.
+fn seed_regions<R: Rng>(
+    star_grid: &Vec<u32>,
+    shape: &BoardShape,
+    stars: u32,
+    colours: &Vec<u32>,
+    rng: &mut R,
+) -> Option<Vec<u32>> {
+    let mut colour_grid = vec![0; star_grid.len()];
+    let mut unassigned: Vec<usize> = (0..star_grid.len())
+        .filter(|&index| star_grid[index] == 1)
+        .collect();
+    unassigned.shuffle(rng);
+    let cols = shape.cols as i32;
+    let distance = |a: usize, b: usize| {
+        let (a, b) = (a as i32, b as i32);
+        return (a / cols - b / cols).abs() + (a % cols - b % cols).abs();
+    };
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    while let Some(first) = unassigned.pop() {
+        let mut group = vec![first];
+        // keep adding the star closest to the group until it is full
+        while group.len() < stars as usize && !unassigned.is_empty() {
+            let closest = (0..unassigned.len())
+                .min_by_key(|&i| group.iter().map(|&g| distance(g, unassigned[i])).min())
+                .unwrap();
+            group.push(unassigned.swap_remove(closest));
+        }
+        for &index in &group {
+            colour_grid[index] = colours[groups.len()];
+        }
+        groups.push(group);
+    }
+
+    for group in &groups {
+        let colour = colour_grid[group[0]];
+        for &target in &group[1..] {
+            // breadth first search from the region so far to the next star, through uncoloured cells
+            let mut previous: HashMap<usize, usize> = HashMap::new();
+            let mut queue: VecDeque<usize> = (0..colour_grid.len())
+                .filter(|&index| colour_grid[index] == colour && index != target)
+                .collect();
+            let mut found = false;
+            while let Some(index) = queue.pop_front() {
+                for next in shape.neighbours(index) {
+                    if next == target {
+                        previous.insert(next, index);
+                        found = true;
+                        break;
+                    }
+                    if colour_grid[next] == 0 && !previous.contains_key(&next) {
+                        previous.insert(next, index);
+                        queue.push_back(next);
+                    }
+                }
+                if found {
+                    break;
+                }
+            }
+            if !found {
+                return None;
+            }
+            let mut step = previous[&target];
+            while colour_grid[step] != colour {
+                colour_grid[step] = colour;
+                step = previous[&step];
+            }
+        }
+    }
+    return Some(colour_grid);
+}
+
+/// This function generates a grid of star locations for a queens variant, with its stars in
+/// every row in play and the columns in play sharing them evenly, none in a void cell and no
+/// two attacking one another.
+/// When the rules free the rows and columns, the same number of stars go anywhere instead.
+/// Gives a grid of 0s if there is no way to place them.
+/// - variant: the variant to place stars for.
+/// - rng: the random number generator to use.
+pub fn generate_grid<R: Rng>(variant: &QueensVariant, rng: &mut R) -> Vec<u32> {
+    let (shape, stars, rules) = (&variant.shape, variant.stars, &variant.clash);
+    let mut grid = vec![0; (shape.rows * shape.cols) as usize];
+    let (row_stars, col_stars) = shape.star_counts(stars);
+    let placed = if rules.free_lines {
+        let n_stars = row_stars.iter().sum();
+        let mut steps_left = MAX_SCATTER_STEPS;
+        scatter_stars(
+            &mut grid,
+            &shape.void,
+            n_stars,
+            shape.cols,
+            rules,
+            &mut steps_left,
+            rng,
+        )
+    } else {
+        let col_stars = col_stars.into_iter().max().unwrap_or(0);
+        add_row(&mut grid, 0, shape, &row_stars, col_stars, rules, rng)
+    };
+    if placed {
+        return grid;
+    } else {
+        return vec![0; (shape.rows * shape.cols) as usize];
+    }
+}
+
+/// This function recursively adds the stars of the current row, asserting they do not break the rules.
+/// - grid: the current state of the grid.
+/// - row: the row of the next stars to add.
+/// - shape: the shape of the board.
+/// - row_stars: the number of stars each row needs, 0 for rows that are all void.
+/// - col_stars: the number of stars in each column in play.
+/// - rules: which stars attack one another.
+/// - rng: the random number generator to use.
+fn add_row<R: Rng>(
+    grid: &mut Vec<u32>,
+    row: u32,
+    shape: &BoardShape,
+    row_stars: &Vec<u32>,
+    col_stars: u32,
+    rules: &ClashRules,
+    rng: &mut R,
+) -> bool {
+    if row == shape.rows {
+        return true;
+    }
+
+    let mut choices = row_choices(shape.cols, row_stars[row as usize]);
+    choices.shuffle(rng);
+
+    let size = shape.cols;
+    for cols in choices {
+        let fits = cols.iter().all(|&col| {
+            !shape.void[(row * size + col) as usize]
+                && is_valid(grid, row, col, size, col_stars, rules)
+        });
+        if fits {
+            for &col in &cols {
+                grid[(row * size + col) as usize] = 1;
+            }
+            if add_row(grid, row + 1, shape, row_stars, col_stars, rules, rng) {
+                return true;
+            }
+            for &col in &cols {
+                grid[(row * size + col) as usize] = 0;
+            }
+        }
+    }
+    return false;
+}
+
+/// This function recursively adds stars anywhere on the board until every cell is a star or
+/// attacked by one, using exactly `n_stars` stars. A cell left unattacked could take its region's
+/// star in place of the real one, so boards with a single solution need every cell covered.
+/// Each step covers the first cell left, with a star either on it or on a cell attacking it.
+/// - grid: the current state of the grid.
+/// - covered: whether each cell is a star, attacked by one or void.
+/// - n_stars: the number of stars still to add.
+/// - size: the number of columns in the grid.
+/// - rules: which stars attack one another.
+/// - steps_left: the number of steps to take before giving up, as some boards can't be covered.
+/// - rng: the random number generator to use.
+fn scatter_stars<R: Rng>(
+    grid: &mut Vec<u32>,
+    covered: &Vec<bool>,
+    n_stars: u32,
+    size: u32,
+    rules: &ClashRules,
+    steps_left: &mut u32,
+    rng: &mut R,
+) -> bool {
+    let cell = |index: usize| (index as u32 / size, index as u32 % size);
+    let Some(first) = covered.iter().position(|&c| !c) else {
+        return n_stars == 0;
+    };
+    if n_stars == 0 || *steps_left == 0 {
+        return false;
+    }
+    *steps_left -= 1;
+    // stars can't go on a cell that is already attacked
+    let mut choices: Vec<usize> = (0..grid.len())
+        .filter(|&i| !covered[i] && (i == first || rules.attacks(cell(i), cell(first), size)))
+        .collect();
+    choices.shuffle(rng);
+    for index in choices {
+        let now_covered: Vec<bool> = (0..grid.len())
+            .map(|other| {
+                covered[other] || other == index || rules.attacks(cell(index), cell(other), size)
+            })
+            .collect();
+        grid[index] = 1;
+        if scatter_stars(
+            grid,
+            &now_covered,
+            n_stars - 1,
+            size,
+            rules,
+            steps_left,
+            rng,
+        ) {
+            return true;
+        }
+        grid[index] = 0;
+    }
+    return false;
+}
+
+/// This function lists every way of putting `stars` stars in a row without two of them touching.
+/// - size: the size of the grid.
+/// - stars: the number of stars in the row.
+fn row_choices(size: u32, stars: u32) -> Vec<Vec<u32>> {
+    if stars == 0 {
+        return vec![Vec::new()];
+    }
+    let mut choices: Vec<Vec<u32>> = Vec::new();
+    for first in 0..size {
+        // the rest of the row starts two columns along, so nothing touches the first star
+        for mut rest in row_choices(size.saturating_sub(first + 2), stars - 1) {
+            for col in rest.iter_mut() {
+                *col += first + 2;
+            }
+            rest.insert(0, first);
+            choices.push(rest);
+        }
+    }
+    return choices;
+}
+
+/// this function checks if a new star fits with the stars in the rows above
+/// - grid: the current state of the grid.
+/// - row: the row of the most recent star addition.
+/// - col: the column of the most recent star addition.
+/// - size: the number of columns in the grid.
+/// - stars: the number of stars in each column.
+/// - rules: which stars attack one another.
+fn is_valid(
+    grid: &Vec<u32>,
+    row: u32,
+    col: u32,
+    size: u32,
+    stars: u32,
+    rules: &ClashRules,
+) -> bool {
+    let mut in_column = 0;
+    for r in 0..row {
+        for c in 0..size {
+            let i = (r * size + c) as usize;
+            if grid[i] == 1 {
+                // too many in the column
+                if c == col {
+                    in_column += 1;
+                    if in_column >= stars {
+                        return false;
+                    }
+                }
+
+                // 3×3 proximity, or whatever the rules say instead
+                if rules.attacks((r, c), (row, col), size) {
+                    return false;
+                }
+            }
+        }
+    }
+    return true;
+}
+
+/// This function finds the 3 colours with the largest frequency
+/// - colour_counter: the hashmap of colours to their frequencies.
+fn smallest_colours(colour_counter: &HashMap<u32, u32>) -> Vec<u32> {
+    let mut items: Vec<(u32, u32)> = colour_counter
+        .iter()
+        .map(|(c, count)| (*c, *count))
+        .collect();
+
+    // break ties by colour so the order doesn't depend on the hash map's order
+    items.sort_by_key(|&(c, count)| (count, c));
+
+    items.iter().take(2).map(|(c, _)| *c).collect()
+}
+
+/// This struct holds the state of growing regions out from the stars of a solution, one cell at a time.
+/// - colour_grid: the grid of colours. If a cell is uncoloured it has a value 0. Otherwise its number relates to its colour.
+/// - shape: the shape of the board.
+/// - stars: the number of stars in each row and region.
+/// - rules: which stars clash with one another.
+/// - colour_counter: the number of cells of each colour.
+/// - impossible_routes_found: the number of dead ends reached, to give up after too many.
+struct RegionGrowth<'a> {
+    colour_grid: Vec<u32>,
+    shape: &'a BoardShape,
+    stars: u32,
+    rules: &'a ClashRules,
+    colour_counter: HashMap<u32, u32>,
+    impossible_routes_found: u32,
+}
+
+impl<'a> RegionGrowth<'a> {
+    /// this function locates the nearby uncoloured cells.
+    /// - queue: the queue of cells to check.
+    /// - seen: the hashset of all of the seen values. Used to avoid duplicates in the queue.
+    /// - index: the cell in question.
+    fn get_neighbours(&self, queue: &mut VecDeque<usize>, seen: &mut HashSet<usize>, index: usize) {
+        // find the largest colours
+        let s_colours = smallest_colours(&self.colour_counter);
+        let colour = self.colour_grid[index];
+        let mut back_or_front = true;
+        // if the colour of the current cell is in the largest frequencies, then we want to push the next cell to the back
+        if s_colours.contains(&colour) {
+            back_or_front = false;
+        }
+
+        // check for each directly adjacent cell if it is uncoloured, if so push to queue.
+        for next in self.shape.neighbours(index) {
+            if self.colour_grid[next] == 0 && seen.insert(next) {
+                if back_or_front {
+                    queue.push_back(next);
+                } else {
+                    queue.push_front(next);
+                }
+            }
+        }
+    }
+
+    /// This function gets the colours of the adjacent cells
+    /// - index: the cell in question.
+    fn find_colours(&self, index: usize) -> Vec<u32> {
+        return self
+            .shape
+            .neighbours(index)
+            .into_iter()
+            .map(|next| self.colour_grid[next])
+            .filter(|&colour| colour != 0)
+            .collect();
+    }
+
+    /// This function checks if all possible neighbours of a cell have been found
+    /// - index: the cell in question.
+    fn all_neighbours_found(&self, index: usize) -> bool {
+        return self
+            .shape
+            .neighbours(index)
+            .into_iter()
+            .all(|next| self.colour_grid[next] != 0);
+    }
+
+    /// This function checks if giving a cell a colour adds a new solution. The cell is forced to
+    /// hold a star, so any solution found can't be the one the regions grew from.
+    /// - index: the cell in question.
+    /// - colour: the colour to give it.
+    fn adds_solution(&mut self, index: usize, colour: u32) -> bool {
+        let before = self.colour_grid[index];
+        self.colour_grid[index] = colour;
+        let mut search = StarSearch::new(&self.colour_grid, self.shape, self.stars, *self.rules, 1);
+        search.required = Some(index);
+        search.run();
+        let found = !search.solutions.is_empty();
+        self.colour_grid[index] = before;
+        return found;
+    }
+
+    /// This function colours in the grid recursively, resulting in a single solution.
+    /// - queue: the queue of indicies to colour.
+    /// - seen: the hashset of all of the seen values. Used to avoid duplicates in the queue.
+    /// - rng: the random number generator to use.
+    fn colour_grid_recursively<R: Rng>(
+        &mut self,
+        mut queue: VecDeque<usize>,
+        mut seen: HashSet<usize>,
+        rng: &mut R,
+    ) -> bool {
+        if queue.is_empty() {
+            let uncoloured = (0..self.colour_grid.len())
+                .any(|index| self.colour_grid[index] == 0 && !self.shape.void[index]);
+            if uncoloured {
+                self.impossible_routes_found += 1;
+                return false;
+            }
+            return true;
+        }
+        while let Some(index) = queue.pop_front() {
+            let mut colours: Vec<u32> = self.find_colours(index);
+            colours.shuffle(rng);
+            for colour in colours {
+                // if we find a valid layout of colours with the current (cell, colour) pair, we want to return true
+                if self.adds_solution(index, colour) {
+                    continue;
+                }
+                *self.colour_counter.entry(colour).or_insert(0) += 1;
+                self.colour_grid[index] = colour;
+                self.get_neighbours(&mut queue, &mut seen, index);
+                if self.colour_grid_recursively(queue.clone(), seen.clone(), rng) {
+                    return true;
+                }
+                *self.colour_counter.entry(colour).or_insert(0) -= 1;
+                if self.impossible_routes_found >= MAX_IMPOSSIBLE_ROUTES {
+                    return false;
+                }
+                // if we don't find a valid layout with this colour, we try other colours
+            }
+            // if we don't find a valid layout with this cell and the cell's neighbours all exist then we have found a dead end, and must go back
+            if self.all_neighbours_found(index) {
+                self.impossible_routes_found += 1;
+                return false;
+            }
+        }
+        // if we get to this point, we have checked everything in the queue and assumedly have coloured in the grid appropriately
+        return false;
+    }
+}
+
+/// This function checks if a cell clashes with the stars placed so far: one of them attacks it,
+/// or its row, column or colour already has all of its stars. Rows and columns don't count
+/// when the rules free them.
+/// - placed: the stars placed so far (row, col, colour)
+/// - cell: the new cell (row, col, colour)
+/// - variant: the variant being played, for its star counts and clash rules.
+pub fn check_clash(
+    placed: &[(u32, u32, u32)],
+    cell: (u32, u32, u32),
+    variant: &QueensVariant,
+) -> bool {
+    let (stars, rules, size) = (variant.stars, &variant.clash, variant.shape.cols);
+    let (_, col_stars) = variant.shape.star_counts(stars);
+    let col_stars = col_stars.into_iter().max().unwrap_or(0);
+    let (row, col, colour) = cell;
+    let (mut in_row, mut in_col, mut in_colour) = (0, 0, 0);
+    for &(r, c, clr) in placed {
+        // too close, or attacking one another under the rules
+        if rules.attacks((r, c), (row, col), size) {
+            return true;
+        }
+        in_row += (r == row) as u32;
+        in_col += (c == col) as u32;
+        in_colour += (clr == colour) as u32;
+        let full_line = in_row >= stars || in_col >= col_stars;
+        if (full_line && !rules.free_lines) || in_colour >= stars {
+            return true;
+        }
+    }
+    return stars == 0;
+}
+
+/// This function finds the solutions of a board for a queens variant, up to the limit, each as a
+/// grid with 1 where each star goes. Rows and columns with no cells in play take no stars.
+/// Gives none if the variant isn't supported or the board doesn't have its shape.
+/// - colour_grid: the grid of colours, with 0 for void cells.
+/// - variant: the variant the board is played with.
+/// - limit: the number of solutions after which we stop looking.
+pub fn queens_solutions(
+    colour_grid: &Vec<u32>,
+    variant: &QueensVariant,
+    limit: u32,
+) -> Vec<Vec<u32>> {
+    if variant.check().is_err() || !variant.fits(colour_grid) {
+        return Vec::new();
+    }
+    let mut search = StarSearch::new(
+        colour_grid,
+        &variant.shape,
+        variant.stars,
+        variant.clash,
+        limit,
+    );
+    search.run();
+    return search.solutions;
+}
+
+/// This function counts the solutions of a board for a queens variant, stopping once the limit
+/// is reached.
+/// - colour_grid: the grid of colours, with 0 for void cells.
+/// - variant: the variant the board is played with.
+/// - limit: the number of solutions after which we stop looking.
+pub fn count_queens_solutions(colour_grid: &Vec<u32>, variant: &QueensVariant, limit: u32) -> u32 {
+    return queens_solutions(colour_grid, variant, limit).len() as u32;
+}
+
+/// This function finds a solution of a board for a queens variant, giving a grid with 1 where
+/// each star goes.
+/// - colour_grid: the grid of colours, with 0 for void cells.
+/// - variant: the variant the board is played with.
+pub fn find_queens_solution(colour_grid: &Vec<u32>, variant: &QueensVariant) -> Option<Vec<u32>> {
+    return queens_solutions(colour_grid, variant, 1).pop();
+}
+
+/// This struct holds the state of a search for the solutions of a colour grid,
+/// placing stars row by row from the top left, or region by region when the rules free the rows and columns.
+/// - colour_grid: the grid of colours, with 0 for void and uncoloured cells, which can't hold a star.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+/// - limit: the number of solutions after which we stop looking.
+/// - row_stars: the number of stars each row needs, 0 for rows that are all void.
+/// - col_stars: the number of stars each column needs, 0 for columns that are all void.
+/// - stars: the number of stars in each region.
+/// - rules: which stars attack one another. Anything but the default needs one star in each row and region.
+/// - required: a cell that must hold a star, if any.
+/// - star_grid: 1 where a star has been placed.
+/// - placed: the (row, col) of each star placed so far.
+/// - col_counts: the number of stars in each column.
+/// - colour_counts: the number of stars in each colour.
+/// - on_board: whether each colour is on the board at all.
+/// - colours_below: the number of cells of each colour below each row, to spot colours that can't be filled.
+/// - solutions: the star grids of the solutions found so far.
+struct StarSearch<'a> {
+    colour_grid: &'a Vec<u32>,
+    rows: u32,
+    cols: u32,
+    limit: u32,
+    row_stars: Vec<u32>,
+    col_stars: Vec<u32>,
+    stars: u32,
+    rules: ClashRules,
+    required: Option<usize>,
+    star_grid: Vec<u32>,
+    placed: Vec<(u32, u32)>,
+    col_counts: Vec<u32>,
+    colour_counts: Vec<u32>,
+    on_board: Vec<bool>,
+    colours_below: Vec<Vec<u32>>,
+    solutions: Vec<Vec<u32>>,
+}
+
+impl<'a> StarSearch<'a> {
+    /// This function sets up a search with no stars placed. Rows and columns of the shape that
+    /// are all void need no stars.
+    fn new(
+        colour_grid: &'a Vec<u32>,
+        shape: &BoardShape,
+        stars: u32,
+        rules: ClashRules,
+        limit: u32,
+    ) -> Self {
+        let n_colours = colour_grid.iter().copied().max().unwrap_or(0) as usize + 1;
+        let mut on_board = vec![false; n_colours];
+        for &colour in colour_grid {
+            on_board[colour as usize] = colour != 0;
+        }
+        let (rows, cols) = (shape.rows, shape.cols);
+        let (row_stars, col_stars) = shape.star_counts(stars);
+        let (width, height) = (cols as usize, rows as usize);
+        let mut colours_below = vec![vec![0; n_colours]; height];
+        for row in (0..height).rev().skip(1) {
+            colours_below[row] = colours_below[row + 1].clone();
+            for col in 0..width {
+                colours_below[row][colour_grid[(row + 1) * width + col] as usize] += 1;
+            }
+        }
+        return StarSearch {
+            colour_grid,
+            rows,
+            cols,
+            limit,
+            row_stars,
+            col_stars,
+            stars,
+            rules,
+            required: None,
+            star_grid: vec![0; (rows * cols) as usize],
+            placed: Vec::new(),
+            col_counts: vec![0; width],
+            colour_counts: vec![0; n_colours],
+            on_board,
+            colours_below,
+            solutions: Vec::new(),
+        };
+    }
+
+    /// This function runs the search from an empty board.
+    fn run(&mut self) {
+        if !self.rules.free_lines {
+            self.place(0, 0, 0);
+            return;
+        }
+        let mut regions: Vec<Vec<usize>> = vec![Vec::new(); self.on_board.len()];
+        for (index, &colour) in self.colour_grid.iter().enumerate() {
+            if colour != 0 {
+                regions[colour as usize].push(index);
+            }
+        }
+        if let Some(required) = self.required {
+            regions[self.colour_grid[required] as usize] = vec![required];
+        }
+        regions.retain(|cells| !cells.is_empty());
+        // the smallest regions have the fewest choices, so fill them first
+        regions.sort_by_key(|cells| cells.len());
+        self.place_by_region(&regions, 0);
+    }
+
+    /// This function recursively places the star of each region in turn, wherever no star placed
+    /// so far attacks it, counting each complete placement.
+    /// - regions: the cells of each region, in the order to fill them.
+    /// - next: the region to place a star in next.
+    fn place_by_region(&mut self, regions: &[Vec<usize>], next: usize) {
+        if self.solutions.len() as u32 >= self.limit {
+            return;
+        }
+        if next == regions.len() {
+            self.solutions.push(self.star_grid.clone());
+            return;
+        }
+        for &index in &regions[next] {
+            let (row, col) = (index as u32 / self.cols, index as u32 % self.cols);
+            if self.attacked(row, col) {
+                continue;
+            }
+            self.star_grid[index] = 1;
+            self.placed.push((row, col));
+            self.place_by_region(regions, next + 1);
+            self.placed.pop();
+            self.star_grid[index] = 0;
+        }
+    }
+
+    /// This function recursively places the stars of a row from left to right, counting each complete placement.
+    /// - row: the row being filled.
+    /// - min_col: the first column the next star may go in. Stars in a row are kept two apart so they don't touch.
+    /// - in_row: the number of stars placed in the row so far.
+    fn place(&mut self, row: u32, min_col: u32, in_row: u32) {
+        if self.solutions.len() as u32 >= self.limit {
+            return;
+        }
+        if row == self.rows {
+            self.solutions.push(self.star_grid.clone());
+            return;
+        }
+        if in_row == self.row_stars[row as usize] {
+            if self.still_possible(row) {
+                self.place(row + 1, 0, 0);
+            }
+            return;
+        }
+        let size = self.cols;
+        let still_needed = self.row_stars[row as usize] - in_row;
+        for col in min_col..size {
+            // leave room for the rest of the row's stars
+            if col + 2 * (still_needed - 1) >= size {
+                break;
+            }
+            let index = (row * size + col) as usize;
+            let colour = self.colour_grid[index] as usize;
+            if colour == 0
+                || self.col_counts[col as usize] >= self.col_stars[col as usize]
+                || self.colour_counts[colour] >= self.stars
+            {
+                continue;
+            }
+            if self.attacked(row, col) || self.blocks_required(row, col, in_row) {
+                continue;
+            }
+            self.star_grid[index] = 1;
+            self.placed.push((row, col));
+            self.col_counts[col as usize] += 1;
+            self.colour_counts[colour] += 1;
+            self.place(row, col + 2, in_row + 1);
+            self.colour_counts[colour] -= 1;
+            self.col_counts[col as usize] -= 1;
+            self.placed.pop();
+            self.star_grid[index] = 0;
+        }
+    }
+
+    /// This function checks if a star placed so far attacks a cell.
+    /// - row: the row of the cell.
+    /// - col: the column of the cell.
+    fn attacked(&self, row: u32, col: u32) -> bool {
+        if self.rules != ClashRules::default() {
+            return self
+                .placed
+                .iter()
+                .any(|&star| self.rules.attacks(star, (row, col), self.cols));
+        }
+        // stars in neighbouring rows can't touch, even diagonally, and only the row above is filled in
+        if row == 0 {
+            return false;
+        }
+        let size = self.cols;
+        let above = ((row - 1) * size) as usize;
+        return (col.saturating_sub(1)..=(col + 1).min(size - 1))
+            .any(|c| self.star_grid[above + c as usize] == 1);
+    }
+
+    /// This function checks if a star in a cell would leave no room for a star in the required cell,
+    /// by passing it, filling its row, column or colour, or attacking it.
+    /// - row: the row of the cell.
+    /// - col: the column of the cell.
+    /// - in_row: the number of stars placed in the row so far.
+    fn blocks_required(&self, row: u32, col: u32, in_row: u32) -> bool {
+        let Some(required) = self.required else {
+            return false;
+        };
+        if self.star_grid[required] == 1 {
+            return false;
+        }
+        let (required_row, required_col) =
+            (required as u32 / self.cols, required as u32 % self.cols);
+        if (row, col) == (required_row, required_col) {
+            return false;
+        }
+        // stars go left to right, so once past the required cell it can't take one
+        if (row, col) > (required_row, required_col) {
+            return true;
+        }
+        let colour = self.colour_grid[(row * self.cols + col) as usize];
+        let required_colour = self.colour_grid[required];
+        return (row == required_row && in_row + 1 >= self.row_stars[row as usize])
+            || (col == required_col
+                && self.col_counts[col as usize] + 1 >= self.col_stars[col as usize])
+            || (colour == required_colour
+                && self.colour_counts[colour as usize] + 1 >= self.stars)
+            || self
+                .rules
+                .attacks((row, col), (required_row, required_col), self.cols);
+    }
+
+    /// This function checks, once a row is full, that every column and colour can still get all of its stars
+    /// from the rows below.
+    /// - row: the row that has just been filled.
+    fn still_possible(&self, row: u32) -> bool {
+        let rows_left = self.rows - 1 - row;
+        // stars in a column can't be in neighbouring rows, so at most every other row can take one
+        let most_per_column = rows_left.div_ceil(2);
+        let columns_short = self
+            .col_counts
+            .iter()
+            .zip(&self.col_stars)
+            .any(|(&count, &needed)| count + most_per_column < needed);
+        if columns_short {
+            return false;
+        }
+        // colours that aren't on the board don't need stars
+        let colours_short = self
+            .colour_counts
+            .iter()
+            .zip(&self.colours_below[row as usize])
+            .zip(&self.on_board)
+            .any(|((&count, &below), &on_board)| on_board && count + below < self.stars);
+        return !colours_short;
+    }
+}
+
+/// This function checks that every colour in the grid forms a single 4-connected region.
+/// - colour_grid: the grid of colours.
+/// - size: the size of the grid.
+pub fn regions_connected(colour_grid: &Vec<u32>, size: u32) -> bool {
+    return split_regions(colour_grid, size).is_empty();
+}
+
+/// This function finds the colours whose cells are split into more than one 4-connected piece.
+/// - colour_grid: the grid of colours.
+/// - size: the size of the grid.
+pub fn split_regions(colour_grid: &Vec<u32>, size: u32) -> Vec<u32> {
+    return split_board_regions(colour_grid, size, size);
+}
+
+/// This function finds the colours whose cells are split into more than one 4-connected piece,
+/// on a board of any shape. Void cells, colour 0, aren't a region so are never split.
+/// - colour_grid: the grid of colours.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+pub fn split_board_regions(colour_grid: &Vec<u32>, rows: u32, cols: u32) -> Vec<u32> {
+    let mut visited = vec![false; (rows * cols) as usize];
+    let mut seen_colours: HashSet<u32> = HashSet::new();
+    let mut split: Vec<u32> = Vec::new();
+    for start in 0..(rows * cols) as usize {
+        if visited[start] || colour_grid[start] == 0 {
+            continue;
+        }
+        // the first time we reach a colour we flood fill it, so reaching it again means it's split
+        let colour = colour_grid[start];
+        if !seen_colours.insert(colour) && !split.contains(&colour) {
+            split.push(colour);
+        }
+        let mut queue: VecDeque<usize> = VecDeque::from([start]);
+        visited[start] = true;
+        while let Some(index) = queue.pop_front() {
+            let row = (index as u32 / cols) as i32;
+            let col = (index as u32 % cols) as i32;
+            for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let r = row + dr;
+                let c = col + dc;
+                if r < 0 || r >= rows as i32 || c < 0 || c >= cols as i32 {
+                    continue;
+                }
+                let next = (r * cols as i32 + c) as usize;
+                if !visited[next] && colour_grid[next] == colour {
+                    visited[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    return split;
+}
+
+/// This function checks that a colour grid is a valid board for a queens variant: it has the
+/// variant's shape, one region for each row in play, every region connected and exactly one
+/// solution.
+/// - colour_grid: the grid of colours, with 0 for void cells.
+/// - variant: the variant the board is played with.
+pub fn validate_queens_board(
+    colour_grid: &Vec<u32>,
+    variant: &QueensVariant,
+) -> Result<(), String> {
+    let (rows, cols) = (variant.shape.rows, variant.shape.cols);
+    if colour_grid.len() != (rows * cols) as usize {
+        return Err(format!(
+            "expected {} cells for a {}x{} grid but found {}",
+            rows * cols,
+            rows,
+            cols,
+            colour_grid.len()
+        ));
+    }
+    let n_regions = variant.check()?;
+    if !variant.fits(colour_grid) {
+        return Err("the void cells of the board don't match its shape".to_string());
+    }
+    let colours: HashSet<u32> = colour_grid.iter().copied().filter(|&c| c != 0).collect();
+    if colours.len() != n_regions as usize {
+        return Err(format!(
+            "expected {} regions but found {}",
+            n_regions,
+            colours.len()
+        ));
+    }
+    if !split_board_regions(colour_grid, rows, cols).is_empty() {
+        return Err("every region must be connected".to_string());
+    }
+    match count_queens_solutions(colour_grid, variant, 2) {
+        0 => return Err("the board has no solution".to_string()),
+        1 => return Ok(()),
+        _ => return Err("the board has more than one solution".to_string()),
+    }
+}
+
+/// This function checks if a solution is valid for a board of a queens variant: a star for every
+/// row in play, none in a void cell, and none clashing with another under the variant's rules.
+/// - colour_grid: the grid of the colours, with 0 for void cells.
+/// - solution: the indices of each star.
+/// - variant: the variant the board is played with.
+pub fn check_queens_solution(
+    colour_grid: &[u32],
+    solution: &[u32],
+    variant: &QueensVariant,
+) -> bool {
+    if variant.check().is_err() || !variant.fits(colour_grid) {
+        return false;
+    }
+    let (row_stars, _) = variant.shape.star_counts(variant.stars);
+    if solution.len() != row_stars.iter().sum::<u32>() as usize {
+        return false;
+    }
+    let cols = variant.shape.cols;
+    let mut placed: Vec<(u32, u32, u32)> = Vec::new();
+    for &index in solution {
+        if index as usize >= colour_grid.len() || colour_grid[index as usize] == 0 {
+            return false;
+        }
+        let cell = (index / cols, index % cols, colour_grid[index as usize]);
+        if check_clash(&placed, cell, variant) {
+            return false;
+        }
+        placed.push(cell);
+    }
+    return true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clash::Attack;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_star_battle_game() {
+        let variant = QueensVariant {
+            stars: 2,
+            ..QueensVariant::queens(8)
+        };
+        let mut rng = StdRng::seed_from_u64(3);
+        let colour_grid = generate_queens_game(&variant, &mut rng).unwrap();
+        assert!(regions_connected(&colour_grid, 8));
+        assert_eq!(count_queens_solutions(&colour_grid, &variant, 2), 1);
+        let solution = find_queens_solution(&colour_grid, &variant).unwrap();
+        let stars: Vec<u32> = (0..64).filter(|&i| solution[i as usize] == 1).collect();
+        assert!(check_queens_solution(&colour_grid, &stars, &variant));
+        // one star short of a solution
+        assert!(!check_queens_solution(&colour_grid, &stars[1..], &variant));
+    }
+
+    #[test]
+    fn test_check_clash_stars() {
+        let placed = vec![(0, 0, 1), (2, 3, 2)];
+        let two_stars = QueensVariant {
+            stars: 2,
+            ..QueensVariant::queens(8)
+        };
+        // a second star in the first row is fine with two stars, as long as it doesn't touch
+        assert!(!check_clash(&placed, (0, 5, 3), &two_stars));
+        assert!(check_clash(&placed, (0, 1, 3), &two_stars));
+        assert!(check_clash(&placed, (0, 5, 3), &QueensVariant::queens(8)));
+        // side by side stars touch
+        assert!(check_clash(&placed, (2, 4, 3), &two_stars));
+        // a column with room for two stars takes a second, on a board twice as tall as it is wide
+        let tall = QueensVariant {
+            shape: BoardShape {
+                rows: 16,
+                cols: 8,
+                void: vec![false; 128],
+            },
+            ..QueensVariant::queens(8)
+        };
+        assert!(!check_clash(&placed, (4, 0, 3), &tall));
+        assert!(check_clash(&placed, (4, 0, 3), &QueensVariant::queens(8)));
+    }
+
+    #[test]
+    fn test_ruled_queens_game() {
+        let variant = QueensVariant {
+            clash: ClashRules {
+                attack: Attack::Queen,
+                ..ClashRules::default()
+            },
+            constraints: RegionConstraints::default(),
+            ..QueensVariant::queens(8)
+        };
+        let mut rng = StdRng::seed_from_u64(2);
+        let colour_grid = generate_queens_game(&variant, &mut rng).unwrap();
+        assert_eq!(count_queens_solutions(&colour_grid, &variant, 2), 1);
+        let solution = find_queens_solution(&colour_grid, &variant).unwrap();
+        let queens: Vec<u32> = (0..64).filter(|&i| solution[i as usize] == 1).collect();
+        assert!(check_queens_solution(&colour_grid, &queens, &variant));
+        // two queens on a long diagonal only clash under the chess rules
+        assert!(check_clash(&[(0, 0, 1)], (5, 5, 2), &variant));
+        assert!(!check_clash(
+            &[(0, 0, 1)],
+            (5, 5, 2),
+            &QueensVariant::queens(8)
+        ));
+        // the chess rules aren't supported with more than one star
+        let two_stars = QueensVariant {
+            stars: 2,
+            ..variant
+        };
+        assert!(two_stars.check().is_err());
+        assert!(generate_queens_game(&two_stars, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_free_lines_queens_game() {
+        // a queen covers far fewer cells without its row and column, so only the diagonals of
+        // chess queens leave enough of the board attacked for a single solution
+        let variant = QueensVariant {
+            clash: ClashRules {
+                attack: Attack::Queen,
+                free_lines: true,
+                ..ClashRules::default()
+            },
+            constraints: RegionConstraints::default(),
+            ..QueensVariant::queens(5)
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        let colour_grid = generate_queens_game(&variant, &mut rng).unwrap();
+        assert_eq!(count_queens_solutions(&colour_grid, &variant, 2), 1);
+        let solution = find_queens_solution(&colour_grid, &variant).unwrap();
+        let queens: Vec<u32> = (0..25).filter(|&i| solution[i as usize] == 1).collect();
+        assert!(check_queens_solution(&colour_grid, &queens, &variant));
+        // queens in the same row only clash when the rows aren't free
+        assert!(!check_clash(&[(0, 0, 1)], (0, 3, 2), &variant));
+        assert!(check_clash(
+            &[(0, 0, 1)],
+            (0, 3, 2),
+            &QueensVariant::queens(5)
+        ));
+    }
+
+    #[test]
+    fn test_shaped_board_game() {
+        // a 7x8 board with the last column and the middle cell void plays like a 7x7 queens board
+        let mut void: Vec<bool> = (0..56).map(|i| i % 8 == 7).collect();
+        void[27] = true;
+        let shape = BoardShape {
+            rows: 7,
+            cols: 8,
+            void,
+        };
+        assert_eq!(shape.check(1), Ok(7));
+        let variant = QueensVariant {
+            shape: shape.clone(),
+            ..QueensVariant::queens(7)
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        let colour_grid = generate_queens_game(&variant, &mut rng).unwrap();
+        assert_eq!(BoardShape::of_grid(&colour_grid, 7, 8), shape);
+        assert_eq!(validate_queens_board(&colour_grid, &variant), Ok(()));
+        let solution = find_queens_solution(&colour_grid, &variant).unwrap();
+        let queens: Vec<u32> = (0..56).filter(|&i| solution[i as usize] == 1).collect();
+        assert!(check_queens_solution(&colour_grid, &queens, &variant));
+        // the region limits hold on shaped boards too
+        let limited = QueensVariant {
+            constraints: RegionConstraints {
+                min_region_size: 5,
+                max_region_size: Some(9),
+                allow_single_cells: false,
+            },
+            ..variant.clone()
+        };
+        let colour_grid = generate_queens_game(&limited, &mut rng).unwrap();
+        let mut sizes = vec![0; 7];
+        for &colour in colour_grid.iter().filter(|&&colour| colour != 0) {
+            sizes[colour as usize - 1] += 1;
+        }
+        assert!(limited.constraints.allows(&sizes));
+        // a grid with a different shape doesn't fit the variant
+        assert!(!variant.fits(&vec![1; 56]));
+        assert_eq!(count_queens_solutions(&vec![1; 56], &variant, 2), 0);
+        // 8 columns can't share 7 rows' worth of queens
+        assert!(BoardShape::square(7).check(1).is_ok());
+        assert!(BoardShape {
+            cols: 8,
+            void: vec![false; 56],
+            ..shape
+        }
+        .check(1)
+        .is_err());
+
+        // 8 rows of one star share them two to a column on a board 4 columns wide
+        let shape = BoardShape {
+            rows: 8,
+            cols: 4,
+            void: vec![false; 32],
+        };
+        assert_eq!(shape.check(1), Ok(8));
+        assert_eq!(shape.star_counts(1), (vec![1; 8], vec![2; 4]));
+        let variant = QueensVariant {
+            shape,
+            ..QueensVariant::queens(8)
+        };
+        let colour_grid = generate_queens_game(&variant, &mut rng).unwrap();
+        assert_eq!(validate_queens_board(&colour_grid, &variant), Ok(()));
+        let solution = find_queens_solution(&colour_grid, &variant).unwrap();
+        let stars: Vec<u32> = (0..32).filter(|&i| solution[i as usize] == 1).collect();
+        assert!(check_queens_solution(&colour_grid, &stars, &variant));
+    }
+}
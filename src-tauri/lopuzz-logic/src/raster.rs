@@ -0,0 +1,410 @@
+use crate::drawing::crown;
+use crate::png::encode_png;
+use crate::puzzle_file::PuzzleFile;
+use crate::svg::{parse_hex_colour, DEFAULT_PALETTE};
+use crate::utilities::GameKind;
+
+type Rgb = (u8, u8, u8);
+
+const BLACK: Rgb = (0, 0, 0);
+const WHITE: Rgb = (255, 255, 255);
+const GREY: Rgb = (150, 150, 150);
+/// The colour of solution values, matching the SVG export.
+const ANSWER_BLUE: Rgb = (0x3a, 0x5b, 0xbf);
+
+/// The narrowest cells that leave room for the digits.
+pub const MIN_CELL_SIZE: u32 = 8;
+/// The widest cells, so one call can't ask for a huge image.
+pub const MAX_CELL_SIZE: u32 = 128;
+/// The widest grid, not counting its outline, so large grids can't ask for a huge image either.
+pub const MAX_IMAGE_WIDTH: u32 = 4096;
+
+/// The digits 0 to 9 as 5x7 bitmaps, one byte per row with the leftmost pixel in the highest of the 5 bits.
+const DIGITS: [[u8; 7]; 10] = [
+    [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+    [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+    [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+    [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+    [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+    [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+    [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+    [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+];
+
+/// This struct holds the settings for drawing a board as PNG.
+/// - cell_size: the width of each cell in pixels.
+/// - palette: the fill for each region colour, reused in order if there are more colours than fills.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PngOptions {
+    pub cell_size: u32,
+    pub palette: Vec<String>,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        return PngOptions {
+            cell_size: 32,
+            palette: DEFAULT_PALETTE.iter().map(|c| c.to_string()).collect(),
+        };
+    }
+}
+
+/// This struct is an image being drawn, with the origin at the top left.
+struct Canvas {
+    width: u32,
+    height: u32,
+    rgb: Vec<u8>,
+}
+
+impl Canvas {
+    /// This function creates a canvas filled with one colour.
+    /// - width, height: the size of the image in pixels.
+    /// - background: the colour to fill it with.
+    fn new(width: u32, height: u32, background: Rgb) -> Canvas {
+        let rgb = [background.0, background.1, background.2].repeat((width * height) as usize);
+        return Canvas { width, height, rgb };
+    }
+
+    /// This function colours a single pixel, mixing with what is there by the given amount.
+    /// Pixels off the canvas are ignored.
+    /// - x, y: the pixel.
+    /// - colour: the colour.
+    /// - coverage: how much of the pixel the colour covers, from 0 to 1.
+    fn blend(&mut self, x: i64, y: i64, colour: Rgb, coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let index = ((y as u32 * self.width + x as u32) * 3) as usize;
+        for (i, part) in [colour.0, colour.1, colour.2].into_iter().enumerate() {
+            let old = self.rgb[index + i] as f32;
+            self.rgb[index + i] = (old + (part as f32 - old) * coverage).round() as u8;
+        }
+    }
+
+    /// This function fills a rectangle.
+    /// - x, y: the top left corner.
+    /// - width, height: the size of the rectangle.
+    /// - colour: the colour.
+    fn fill_rect(&mut self, x: i64, y: i64, width: i64, height: i64, colour: Rgb) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.blend(px, py, colour, 1.0);
+            }
+        }
+    }
+
+    /// This function fills a closed shape, smoothing its edges by sampling each pixel 4x4 times.
+    /// - points: the corners of the shape, in pixels.
+    /// - colour: the colour.
+    fn fill_polygon(&mut self, points: &[(f32, f32)], colour: Rgb) {
+        let min_x = points.iter().map(|p| p.0).fold(f32::MAX, f32::min).floor() as i64;
+        let max_x = points.iter().map(|p| p.0).fold(f32::MIN, f32::max).ceil() as i64;
+        let min_y = points.iter().map(|p| p.1).fold(f32::MAX, f32::min).floor() as i64;
+        let max_y = points.iter().map(|p| p.1).fold(f32::MIN, f32::max).ceil() as i64;
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                let mut inside = 0;
+                for sample in 0..16 {
+                    let sx = px as f32 + ((sample % 4) as f32 + 0.5) / 4.0;
+                    let sy = py as f32 + ((sample / 4) as f32 + 0.5) / 4.0;
+                    if contains(points, sx, sy) {
+                        inside += 1;
+                    }
+                }
+                if inside > 0 {
+                    self.blend(px, py, colour, inside as f32 / 16.0);
+                }
+            }
+        }
+    }
+
+    /// This function writes a number centred on a point using the bitmap digits.
+    /// - centre_x, centre_y: the middle of the text.
+    /// - height: roughly how tall the digits should be, in pixels.
+    /// - text: the digits to write. Anything else is skipped.
+    /// - colour: the colour.
+    /// - bold: whether to thicken the strokes.
+    fn draw_digits(
+        &mut self,
+        centre_x: i64,
+        centre_y: i64,
+        height: u32,
+        text: &str,
+        colour: Rgb,
+        bold: bool,
+    ) {
+        let scale = (height / 7).max(1) as i64;
+        let digits: Vec<usize> = text
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| d as usize)
+            .collect();
+        // each digit is 5 dots wide with a dot of space between digits
+        let width = (digits.len() as i64 * 6 - 1) * scale;
+        let left = centre_x - width / 2;
+        let top = centre_y - 7 * scale / 2;
+        let thickness = if bold {
+            scale + (scale / 2).max(1)
+        } else {
+            scale
+        };
+        for (n, &digit) in digits.iter().enumerate() {
+            for (row, bits) in DIGITS[digit].iter().enumerate() {
+                for col in 0..5 {
+                    if bits & (0x10 >> col) != 0 {
+                        self.fill_rect(
+                            left + (n as i64 * 6 + col) * scale,
+                            top + row as i64 * scale,
+                            thickness,
+                            scale,
+                            colour,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// This function checks whether a point is inside a closed shape, by counting edge crossings.
+/// - points: the corners of the shape.
+/// - x, y: the point.
+fn contains(points: &[(f32, f32)], x: f32, y: f32) -> bool {
+    let mut inside = false;
+    let mut previous = points[points.len() - 1];
+    for &point in points {
+        if (point.1 > y) != (previous.1 > y) {
+            let crossing =
+                point.0 + (y - point.1) * (previous.0 - point.0) / (previous.1 - point.1);
+            if x < crossing {
+                inside = !inside;
+            }
+        }
+        previous = point;
+    }
+    return inside;
+}
+
+/// This function draws a puzzle of either game as a PNG image, solving it under its rules first if
+/// the solved state is wanted.
+/// - puzzle: the puzzle.
+/// - with_solution: whether to draw the solution on top.
+/// - options: the drawing settings.
+pub fn render_puzzle_png(
+    puzzle: &PuzzleFile,
+    with_solution: bool,
+    options: &PngOptions,
+) -> Result<Vec<u8>, String> {
+    let (game, grid, size) = (puzzle.game, puzzle.board(), puzzle.size);
+    puzzle.check_drawable()?;
+    if size == 0 || grid.len() != (size * size) as usize {
+        return Err(format!(
+            "expected {} cells for a {}x{} grid but found {}",
+            size * size,
+            size,
+            size,
+            grid.len()
+        ));
+    }
+    if !(MIN_CELL_SIZE..=MAX_CELL_SIZE).contains(&options.cell_size) {
+        return Err(format!(
+            "cells must be from {} to {} pixels wide but got {}",
+            MIN_CELL_SIZE, MAX_CELL_SIZE, options.cell_size
+        ));
+    }
+    if size * options.cell_size > MAX_IMAGE_WIDTH {
+        return Err(format!(
+            "a {}x{} grid with {} pixel cells is wider than {} pixels",
+            size, size, options.cell_size, MAX_IMAGE_WIDTH
+        ));
+    }
+    let palette: Vec<Rgb> = options
+        .palette
+        .iter()
+        .map(|colour| parse_hex_colour(colour))
+        .collect::<Result<_, String>>()?;
+    let mut solution = None;
+    if with_solution {
+        solution = puzzle.find_solution();
+        if solution.is_none() {
+            return Err("the puzzle has no solution".to_string());
+        }
+    }
+    let canvas = match game {
+        GameKind::Queens => draw_queens(grid, solution.as_ref(), size, options.cell_size, &palette),
+        GameKind::Numbers => draw_numbers(grid, solution.as_ref(), size, options.cell_size),
+    };
+    return encode_png(canvas.width, canvas.height, &canvas.rgb);
+}
+
+/// This function gives the width of the border around the whole grid, which the image leaves room for.
+/// - cell: the width of each cell.
+fn outline_width(cell: u32) -> i64 {
+    return (cell as i64 / 12).max(2);
+}
+
+/// This function draws a queens board with its regions filled and outlined, like the SVG export.
+/// - colour_grid: the grid of colours.
+/// - solution: the grid with 1 where each queen goes, to draw the queens, if any.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+/// - palette: the fill for each region colour.
+fn draw_queens(
+    colour_grid: &Vec<u32>,
+    solution: Option<&Vec<u32>>,
+    size: u32,
+    cell: u32,
+    palette: &[Rgb],
+) -> Canvas {
+    let border = outline_width(cell);
+    let mut canvas = open_canvas(size, cell);
+    let c = cell as i64;
+    for index in 0..(size * size) as usize {
+        let (x, y) = cell_origin(index, size, cell, border);
+        let fill = if palette.is_empty() {
+            WHITE
+        } else {
+            palette[(colour_grid[index].max(1) - 1) as usize % palette.len()]
+        };
+        canvas.fill_rect(x, y, c, c, fill);
+    }
+    draw_thin_lines(&mut canvas, size, cell);
+
+    // thick lines wherever neighbouring cells are in different regions
+    let thick = (c / 16).max(2);
+    let colour = |row: u32, col: u32| colour_grid[(row * size + col) as usize];
+    for row in 0..size {
+        for col in 0..size {
+            let (x, y) = cell_origin((row * size + col) as usize, size, cell, border);
+            if col + 1 < size && colour(row, col) != colour(row, col + 1) {
+                canvas.fill_rect(x + c - thick / 2, y - thick / 2, thick, c + thick, BLACK);
+            }
+            if row + 1 < size && colour(row, col) != colour(row + 1, col) {
+                canvas.fill_rect(x - thick / 2, y + c - thick / 2, c + thick, thick, BLACK);
+            }
+        }
+    }
+    draw_outline(&mut canvas, size, cell);
+
+    if let Some(solution) = solution {
+        for (index, _) in solution.iter().enumerate().filter(|&(_, &mark)| mark == 1) {
+            let (x, y) = cell_origin(index, size, cell, border);
+            let (left, bottom) = (x as f32, (y + cell as i64) as f32);
+            canvas.fill_polygon(&crown(left, bottom, cell as f32, -1.0), BLACK);
+        }
+    }
+    return canvas;
+}
+
+/// This function draws a numbers board. Givens are bold and solution values are blue, like the SVG export.
+/// - givens: the grid of givens, with 0 for empty cells.
+/// - solution: the solved grid, to fill in the empty cells, if any.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+fn draw_numbers(givens: &Vec<u32>, solution: Option<&Vec<u32>>, size: u32, cell: u32) -> Canvas {
+    let border = outline_width(cell);
+    let mut canvas = open_canvas(size, cell);
+    draw_thin_lines(&mut canvas, size, cell);
+    draw_outline(&mut canvas, size, cell);
+    let c = cell as i64;
+    for index in 0..(size * size) as usize {
+        let (x, y) = cell_origin(index, size, cell, border);
+        if givens[index] != 0 {
+            let text = givens[index].to_string();
+            canvas.draw_digits(x + c / 2, y + c / 2, cell * 11 / 20, &text, BLACK, true);
+        } else if let Some(solution) = solution {
+            let text = solution[index].to_string();
+            canvas.draw_digits(
+                x + c / 2,
+                y + c / 2,
+                cell * 11 / 20,
+                &text,
+                ANSWER_BLUE,
+                false,
+            );
+        }
+    }
+    return canvas;
+}
+
+/// This function creates a white canvas big enough for the grid and its outline.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+fn open_canvas(size: u32, cell: u32) -> Canvas {
+    let width = size * cell + 2 * outline_width(cell) as u32;
+    return Canvas::new(width, width, WHITE);
+}
+
+/// This function finds the top left pixel of a cell.
+/// - index: the cell.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+/// - border: the space left for the outline.
+fn cell_origin(index: usize, size: u32, cell: u32, border: i64) -> (i64, i64) {
+    let index = index as u32;
+    return (
+        border + ((index % size) * cell) as i64,
+        border + ((index / size) * cell) as i64,
+    );
+}
+
+/// This function draws a thin line along every edge between cells.
+/// - canvas: the image being drawn.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+fn draw_thin_lines(canvas: &mut Canvas, size: u32, cell: u32) {
+    let border = outline_width(cell);
+    let width = (size * cell) as i64;
+    for i in 1..size as i64 {
+        let offset = border + i * cell as i64;
+        canvas.fill_rect(offset, border, 1, width, GREY);
+        canvas.fill_rect(border, offset, width, 1, GREY);
+    }
+}
+
+/// This function draws the thick border around the whole grid.
+/// - canvas: the image being drawn.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+fn draw_outline(canvas: &mut Canvas, size: u32, cell: u32) {
+    let border = outline_width(cell);
+    let full = (size * cell) as i64 + 2 * border;
+    canvas.fill_rect(0, 0, full, border, BLACK);
+    canvas.fill_rect(0, full - border, full, border, BLACK);
+    canvas.fill_rect(0, 0, border, full, BLACK);
+    canvas.fill_rect(full - border, 0, border, full, BLACK);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queen_overlay() {
+        let colour_grid: Vec<u32> = vec![
+            5, 5, 5, 2, 2, //
+            5, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 4, 4, 1, 2,
+        ];
+        let solution = PuzzleFile::queens(colour_grid.clone(), 5)
+            .find_solution()
+            .unwrap();
+        let canvas = draw_queens(&colour_grid, Some(&solution), 5, 32, &[WHITE]);
+        let border = outline_width(32);
+        let pixel = |index: usize, u: i64, v: i64| {
+            let (x, y) = cell_origin(index, 5, 32, border);
+            let i = (((y + v) * canvas.width as i64 + x + u) * 3) as usize;
+            return canvas.rgb[i];
+        };
+        // the base of the crown is inked in a queen's cell and not in any other cell
+        let queen = solution.iter().position(|&v| v == 1).unwrap();
+        let empty = solution.iter().position(|&v| v == 0).unwrap();
+        assert_eq!(pixel(queen, 16, 22), 0);
+        assert_eq!(pixel(empty, 16, 22), 255);
+        assert_eq!(canvas.width, 5 * 32 + 2 * border as u32);
+    }
+}
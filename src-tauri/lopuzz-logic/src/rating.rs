@@ -0,0 +1,388 @@
+use crate::numbers::candidates;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// The difficulty of a puzzle that can be solved by only placing forced cells.
+pub const EASY: u32 = 0;
+/// The difficulty of a puzzle that needs cross-checking between rows, columns and regions.
+pub const MEDIUM: u32 = 1;
+/// The difficulty of a puzzle that needs looking ahead at what a placement would rule out.
+pub const HARD: u32 = 2;
+/// The difficulty of a puzzle that can't be solved by the techniques we know, so needs trial and error.
+pub const TRIAL_AND_ERROR: u32 = 3;
+
+/// This struct is a single deduction made while solving.
+/// - level: the difficulty of the technique used.
+/// - description: what was deduced and why.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SolveStep {
+    pub level: u32,
+    pub description: String,
+}
+
+/// This struct is the result of solving a puzzle the way a person would.
+/// - steps: the deductions made, in order.
+/// - solved: whether the deductions were enough to solve the puzzle.
+/// - difficulty: the hardest technique needed, or `TRIAL_AND_ERROR` if we got stuck.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SolveReport {
+    pub steps: Vec<SolveStep>,
+    pub solved: bool,
+    pub difficulty: u32,
+}
+
+/// This function gives the name of a difficulty rating.
+/// - difficulty: the difficulty rating.
+pub fn difficulty_name(difficulty: u32) -> &'static str {
+    match difficulty {
+        EASY => "easy",
+        MEDIUM => "medium",
+        HARD => "hard",
+        _ => "trial and error",
+    }
+}
+
+impl SolveReport {
+    /// This function builds the report once a solver has finished or got stuck.
+    /// - steps: the deductions made.
+    /// - solved: whether the puzzle was solved.
+    pub fn new(steps: Vec<SolveStep>, solved: bool) -> SolveReport {
+        let mut difficulty = steps.iter().map(|step| step.level).max().unwrap_or(EASY);
+        if !solved {
+            difficulty = TRIAL_AND_ERROR;
+        }
+        return SolveReport {
+            steps,
+            solved,
+            difficulty,
+        };
+    }
+}
+
+/// What we know about a cell of a queens board.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mark {
+    Unknown,
+    Queen,
+    Empty,
+}
+
+/// This struct is a queens board part way through being solved.
+struct QueensSolveState<'a> {
+    colour_grid: &'a Vec<u32>,
+    size: u32,
+    marks: Vec<Mark>,
+    /// the cells in each row, then each column, then each region
+    units: Vec<(String, Vec<usize>)>,
+}
+
+impl QueensSolveState<'_> {
+    /// This function finds the cells that a queen on the given cell would rule out.
+    /// - index: the cell of the queen.
+    fn ruled_out_by(&self, index: usize) -> Vec<usize> {
+        let size = self.size as usize;
+        let (row, col) = (index / size, index % size);
+        let colour = self.colour_grid[index];
+        return (0..size * size)
+            .filter(|&other| other != index && self.marks[other] == Mark::Unknown)
+            .filter(|&other| {
+                let (r, c) = (other / size, other % size);
+                r == row
+                    || c == col
+                    || self.colour_grid[other] == colour
+                    || (r.abs_diff(row) <= 1 && c.abs_diff(col) <= 1)
+            })
+            .collect();
+    }
+
+    /// This function places a queen and rules out every cell it can see.
+    /// - index: the cell of the queen.
+    fn place(&mut self, index: usize) {
+        for other in self.ruled_out_by(index) {
+            self.marks[other] = Mark::Empty;
+        }
+        self.marks[index] = Mark::Queen;
+    }
+
+    /// This function gives the unknown cells of a unit, or None if the unit already has its queen.
+    /// - cells: the cells of the unit.
+    fn open_cells(&self, cells: &[usize]) -> Option<Vec<usize>> {
+        if cells.iter().any(|&i| self.marks[i] == Mark::Queen) {
+            return None;
+        }
+        return Some(
+            cells
+                .iter()
+                .copied()
+                .filter(|&i| self.marks[i] == Mark::Unknown)
+                .collect(),
+        );
+    }
+
+    /// This function describes a cell for the trace, counting rows and columns from 1.
+    /// - index: the cell.
+    fn cell_name(&self, index: usize) -> String {
+        let size = self.size as usize;
+        return format!("r{}c{}", index / size + 1, index % size + 1);
+    }
+}
+
+/// This function solves a queens board using only deductions, recording each step.
+/// - colour_grid: the grid of colours.
+/// - size: the size of the grid.
+pub fn solve_queens_logically(colour_grid: &Vec<u32>, size: u32) -> SolveReport {
+    let n = size as usize;
+    let mut units: Vec<(String, Vec<usize>)> = Vec::new();
+    for row in 0..n {
+        units.push((
+            format!("row {}", row + 1),
+            (0..n).map(|c| row * n + c).collect(),
+        ));
+    }
+    for col in 0..n {
+        units.push((
+            format!("column {}", col + 1),
+            (0..n).map(|r| r * n + col).collect(),
+        ));
+    }
+    let mut colours: Vec<u32> = colour_grid
+        .iter()
+        .copied()
+        .collect::<HashSet<u32>>()
+        .into_iter()
+        .collect();
+    colours.sort();
+    for colour in colours {
+        let cells = (0..n * n).filter(|&i| colour_grid[i] == colour).collect();
+        units.push((format!("region {}", colour), cells));
+    }
+    let mut board = QueensSolveState {
+        colour_grid,
+        size,
+        marks: vec![Mark::Unknown; n * n],
+        units,
+    };
+
+    let mut steps: Vec<SolveStep> = Vec::new();
+    loop {
+        if board.marks.iter().filter(|&&m| m == Mark::Queen).count() == n {
+            return SolveReport::new(steps, true);
+        }
+        if let Some(step) = queens_last_cell(&mut board)
+            .or_else(|| queens_confinement(&mut board))
+            .or_else(|| queens_look_ahead(&mut board))
+        {
+            steps.push(step);
+        } else {
+            return SolveReport::new(steps, false);
+        }
+    }
+}
+
+/// This function places a queen where a row, column or region has only one cell left.
+/// - board: the board being solved.
+fn queens_last_cell(board: &mut QueensSolveState) -> Option<SolveStep> {
+    let (name, index) = board.units.iter().find_map(|(name, cells)| {
+        let open = board.open_cells(cells)?;
+        if open.len() == 1 {
+            return Some((name.clone(), open[0]));
+        }
+        return None;
+    })?;
+    let description = format!(
+        "{} has only {} left, so it is a queen",
+        name,
+        board.cell_name(index)
+    );
+    board.place(index);
+    return Some(SolveStep {
+        level: EASY,
+        description,
+    });
+}
+
+/// This function finds a unit whose open cells all sit inside one other unit.
+/// The queen of that other unit must then be in those cells, so its remaining cells are ruled out.
+/// - board: the board being solved.
+fn queens_confinement(board: &mut QueensSolveState) -> Option<SolveStep> {
+    for (name, cells) in &board.units {
+        let Some(open) = board.open_cells(cells) else {
+            continue;
+        };
+        if open.is_empty() {
+            continue;
+        }
+        for (other_name, other_cells) in &board.units {
+            if other_name == name || !open.iter().all(|i| other_cells.contains(i)) {
+                continue;
+            }
+            let ruled_out: Vec<usize> = other_cells
+                .iter()
+                .copied()
+                .filter(|&i| board.marks[i] == Mark::Unknown && !open.contains(&i))
+                .collect();
+            if ruled_out.is_empty() {
+                continue;
+            }
+            let description = format!(
+                "{} can only have its queen in {}, so the rest of {} is ruled out",
+                name, other_name, other_name
+            );
+            for i in ruled_out {
+                board.marks[i] = Mark::Empty;
+            }
+            return Some(SolveStep {
+                level: MEDIUM,
+                description,
+            });
+        }
+    }
+    return None;
+}
+
+/// This function rules out a cell if a queen there would leave some row, column or region with nowhere for its queen.
+/// - board: the board being solved.
+fn queens_look_ahead(board: &mut QueensSolveState) -> Option<SolveStep> {
+    for index in 0..board.marks.len() {
+        if board.marks[index] != Mark::Unknown {
+            continue;
+        }
+        let ruled_out = board.ruled_out_by(index);
+        for (name, cells) in &board.units {
+            if cells.contains(&index) {
+                continue;
+            }
+            let Some(open) = board.open_cells(cells) else {
+                continue;
+            };
+            if open.iter().all(|i| ruled_out.contains(i)) {
+                let description = format!(
+                    "a queen on {} would leave nowhere for the queen of {}, so it is ruled out",
+                    board.cell_name(index),
+                    name
+                );
+                board.marks[index] = Mark::Empty;
+                return Some(SolveStep {
+                    level: HARD,
+                    description,
+                });
+            }
+        }
+    }
+    return None;
+}
+
+/// This function solves a numbers grid using only deductions, recording each step.
+/// - grid: the grid of givens, with 0 for empty cells.
+/// - size: the size of the grid.
+pub fn solve_numbers_logically(grid: &Vec<u32>, size: u32) -> SolveReport {
+    let n = size as usize;
+    let mut grid = grid.clone();
+    let mut steps: Vec<SolveStep> = Vec::new();
+    loop {
+        if grid.iter().all(|&value| value != 0) {
+            return SolveReport::new(steps, true);
+        }
+        let options: Vec<Vec<u32>> = (0..n * n)
+            .map(|i| {
+                if grid[i] == 0 {
+                    candidates(&grid, i / n, i % n, n)
+                } else {
+                    Vec::new()
+                }
+            })
+            .collect();
+
+        // a cell with only one value left
+        if let Some(i) = (0..n * n).find(|&i| grid[i] == 0 && options[i].len() == 1) {
+            grid[i] = options[i][0];
+            steps.push(SolveStep {
+                level: EASY,
+                description: format!("r{}c{} can only be {}", i / n + 1, i % n + 1, options[i][0]),
+            });
+            continue;
+        }
+
+        // a value with only one place left in a row or column
+        let mut found = None;
+        'units: for unit in 0..2 * n {
+            let cells: Vec<usize> = if unit < n {
+                (0..n).map(|c| unit * n + c).collect()
+            } else {
+                (0..n).map(|r| r * n + unit - n).collect()
+            };
+            for value in 1..=size {
+                if cells.iter().any(|&i| grid[i] == value) {
+                    continue;
+                }
+                let places: Vec<usize> = cells
+                    .iter()
+                    .copied()
+                    .filter(|&i| options[i].contains(&value))
+                    .collect();
+                if places.len() == 1 {
+                    let unit_name = if unit < n {
+                        format!("row {}", unit + 1)
+                    } else {
+                        format!("column {}", unit - n + 1)
+                    };
+                    found = Some((places[0], value, unit_name));
+                    break 'units;
+                }
+            }
+        }
+        let Some((i, value, unit_name)) = found else {
+            return SolveReport::new(steps, false);
+        };
+        grid[i] = value;
+        steps.push(SolveStep {
+            level: MEDIUM,
+            description: format!(
+                "{} can only go in r{}c{} in {}",
+                value,
+                i / n + 1,
+                i % n + 1,
+                unit_name
+            ),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_queens_logically() {
+        let colour_grid: Vec<u32> = vec![
+            5, 5, 5, 2, 2, //
+            5, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 4, 4, 1, 2,
+        ];
+        let report = solve_queens_logically(&colour_grid, 5);
+        assert!(report.solved);
+        // region 1 is a single cell, so the first step is to place its queen
+        assert_eq!(report.steps[0].level, EASY);
+        assert!(report.steps[0].description.contains("r5c4"));
+    }
+
+    #[test]
+    fn test_solve_numbers_logically() {
+        let solution: Vec<u32> = vec![
+            1, 2, 3, 4, 5, //
+            3, 4, 5, 1, 2, //
+            5, 1, 2, 3, 4, //
+            2, 3, 4, 5, 1, //
+            4, 5, 1, 2, 3,
+        ];
+        let mut grid = solution.clone();
+        grid[0] = 0;
+        grid[7] = 0;
+        let report = solve_numbers_logically(&grid, 5);
+        assert!(report.solved);
+        assert_eq!(report.difficulty, EASY);
+        assert_eq!(report.steps.len(), 2);
+    }
+}
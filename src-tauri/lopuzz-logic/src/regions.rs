@@ -0,0 +1,323 @@
+use crate::queens::{count_queens_solutions, split_board_regions, BoardShape, QueensVariant};
+use serde::{Deserialize, Serialize};
+
+/// This struct describes the shapes of the regions of a queens board.
+/// - sizes: the number of cells in each region, by colour from 1.
+/// - perimeters: the number of cell edges around each region, by colour from 1.
+/// - compactness: how close each region is to the most compact shape for its size, from 0 to 1.
+///   A square or near square scores 1, a long thin sliver scores low.
+/// - size_variance: the variance of the region sizes. 0 when every region is the same size.
+/// - mean_compactness: the average compactness of the regions.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegionMetrics {
+    pub sizes: Vec<u32>,
+    pub perimeters: Vec<u32>,
+    pub compactness: Vec<f32>,
+    pub size_variance: f32,
+    pub mean_compactness: f32,
+}
+
+/// This struct holds the limits on region shapes that a generated queens board must meet.
+/// - min_region_size: the fewest cells a region may have.
+/// - max_region_size: the most cells a region may have, if limited.
+/// - allow_single_cells: whether a region may be a single cell. Regions of one cell give
+///   their queen away straight away, so they are not allowed unless asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegionConstraints {
+    pub min_region_size: u32,
+    pub max_region_size: Option<u32>,
+    pub allow_single_cells: bool,
+}
+
+impl Default for RegionConstraints {
+    fn default() -> Self {
+        return RegionConstraints {
+            min_region_size: 1,
+            max_region_size: None,
+            allow_single_cells: false,
+        };
+    }
+}
+
+impl RegionConstraints {
+    /// This function gives the fewest cells a region may have, taking single cells into account.
+    fn smallest_allowed(&self) -> u32 {
+        if self.allow_single_cells {
+            return self.min_region_size;
+        }
+        return self.min_region_size.max(2);
+    }
+
+    /// This function gives the most cells a region may have.
+    fn largest_allowed(&self) -> u32 {
+        return self.max_region_size.unwrap_or(u32::MAX);
+    }
+
+    /// This function checks that a board of the given shape could meet the constraints at all.
+    /// - shape: the shape of the board.
+    /// - n_regions: the number of regions the board is split into.
+    pub fn check(&self, shape: &BoardShape, n_regions: u32) -> Result<(), String> {
+        let n_cells = shape.void.iter().filter(|&&void| !void).count() as u32;
+        if self.smallest_allowed() > self.largest_allowed() {
+            return Err(format!(
+                "regions can't have at least {} and at most {} cells",
+                self.smallest_allowed(),
+                self.largest_allowed()
+            ));
+        }
+        if self.smallest_allowed().saturating_mul(n_regions) > n_cells {
+            return Err(format!(
+                "{} regions of at least {} cells don't fit on a {}x{} board",
+                n_regions,
+                self.smallest_allowed(),
+                shape.rows,
+                shape.cols
+            ));
+        }
+        if self.largest_allowed().saturating_mul(n_regions) < n_cells {
+            return Err(format!(
+                "{} regions of at most {} cells can't cover a {}x{} board",
+                n_regions,
+                self.largest_allowed(),
+                shape.rows,
+                shape.cols
+            ));
+        }
+        return Ok(());
+    }
+
+    /// This function checks whether every region size is within the limits.
+    /// - sizes: the number of cells in each region.
+    pub fn allows(&self, sizes: &[u32]) -> bool {
+        return sizes
+            .iter()
+            .all(|&s| s >= self.smallest_allowed() && s <= self.largest_allowed());
+    }
+}
+
+/// This function measures the sizes and shapes of the regions of a queens board.
+/// Colours are expected to be numbered from 1.
+/// - colour_grid: the grid of colours.
+/// - size: the size of the grid.
+pub fn region_metrics(colour_grid: &Vec<u32>, size: u32) -> Result<RegionMetrics, String> {
+    if colour_grid.len() != (size * size) as usize {
+        return Err(format!(
+            "expected {} cells for a {}x{} grid but found {}",
+            size * size,
+            size,
+            size,
+            colour_grid.len()
+        ));
+    }
+    let n_colours = colour_grid.iter().copied().max().unwrap_or(0) as usize;
+    let mut sizes = vec![0; n_colours];
+    let mut perimeters = vec![0; n_colours];
+    let colour = |row: i32, col: i32| {
+        if row < 0 || col < 0 || row >= size as i32 || col >= size as i32 {
+            return 0;
+        }
+        return colour_grid[(row * size as i32 + col) as usize];
+    };
+    for row in 0..size as i32 {
+        for col in 0..size as i32 {
+            let own = colour(row, col);
+            if own == 0 {
+                continue;
+            }
+            sizes[own as usize - 1] += 1;
+            // every edge that isn't shared with a cell of the same colour is on the perimeter
+            for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                if colour(row + dr, col + dc) != own {
+                    perimeters[own as usize - 1] += 1;
+                }
+            }
+        }
+    }
+    let compactness: Vec<f32> = sizes
+        .iter()
+        .zip(&perimeters)
+        .map(|(&cells, &perimeter)| {
+            if perimeter == 0 {
+                return 0.0;
+            }
+            // the shortest perimeter a shape of this many cells can have on a grid
+            let smallest = 2.0 * (2.0 * (cells as f32).sqrt()).ceil();
+            return smallest / perimeter as f32;
+        })
+        .collect();
+
+    let count = n_colours.max(1) as f32;
+    let mean_size = sizes.iter().sum::<u32>() as f32 / count;
+    let size_variance = sizes
+        .iter()
+        .map(|&s| (s as f32 - mean_size).powi(2))
+        .sum::<f32>()
+        / count;
+    let mean_compactness = compactness.iter().sum::<f32>() / count;
+    return Ok(RegionMetrics {
+        sizes,
+        perimeters,
+        compactness,
+        size_variance,
+        mean_compactness,
+    });
+}
+
+/// This function counts the cells of each region, by colour from 1. Void cells, colour 0, aren't
+/// counted.
+/// - colour_grid: the grid of colours.
+fn region_sizes(colour_grid: &Vec<u32>) -> Vec<u32> {
+    let n_colours = colour_grid.iter().copied().max().unwrap_or(0) as usize;
+    let mut sizes = vec![0; n_colours];
+    for &colour in colour_grid.iter().filter(|&&colour| colour != 0) {
+        sizes[colour as usize - 1] += 1;
+    }
+    return sizes;
+}
+
+/// This function moves cells between neighbouring regions until every region size is within
+/// the variant's limits. A cell is only moved if both regions stay connected and the board still
+/// has exactly one solution. Gives whether the limits were met.
+/// - colour_grid: the grid of colours, with 0 for void cells, which is changed in place.
+/// - variant: the variant the board is played with, for its limits, shape and rules.
+pub fn rebalance_regions(colour_grid: &mut Vec<u32>, variant: &QueensVariant) -> bool {
+    // each move brings a region closer to the limits, so this is plenty
+    for _ in 0..colour_grid.len() * 2 {
+        let sizes = region_sizes(colour_grid);
+        if variant.constraints.allows(&sizes) {
+            return true;
+        }
+        if !move_cells(colour_grid, variant, &sizes) {
+            return false;
+        }
+    }
+    return false;
+}
+
+/// This function moves a cell that helps a region that is too small or too big. If moving it
+/// would give the board a second solution, a second cell may be moved to take that solution away.
+/// Gives whether a move was made.
+/// - colour_grid: the grid of colours.
+/// - variant: the variant the board is played with, for its limits, shape and rules.
+/// - sizes: the number of cells in each region.
+fn move_cells(colour_grid: &mut Vec<u32>, variant: &QueensVariant, sizes: &[u32]) -> bool {
+    let (constraints, shape) = (&variant.constraints, &variant.shape);
+    let connected = |grid: &Vec<u32>| split_board_regions(grid, shape.rows, shape.cols).is_empty();
+    let region_size = |colour: u32| sizes[colour as usize - 1];
+    let too_small = |colour: u32| region_size(colour) < constraints.smallest_allowed();
+    let too_big = |colour: u32| region_size(colour) > constraints.largest_allowed();
+    // moves that keep the regions connected but leave more than one solution
+    let mut ambiguous: Vec<(usize, u32)> = Vec::new();
+    for (index, to) in boundary_moves(colour_grid, shape) {
+        let from = colour_grid[index];
+        // a cell moves if that helps one of the two regions without pushing the other outside the limits
+        let helps = (too_small(to) && region_size(from) > constraints.smallest_allowed())
+            || (too_big(from) && region_size(to) < constraints.largest_allowed());
+        if !helps {
+            continue;
+        }
+        colour_grid[index] = to;
+        if connected(colour_grid) {
+            if count_queens_solutions(colour_grid, variant, 2) == 1 {
+                return true;
+            }
+            ambiguous.push((index, to));
+        }
+        colour_grid[index] = from;
+    }
+
+    for (index, to) in ambiguous {
+        let from = colour_grid[index];
+        colour_grid[index] = to;
+        let mut sizes = sizes.to_vec();
+        sizes[from as usize - 1] -= 1;
+        sizes[to as usize - 1] += 1;
+        for (second, second_to) in boundary_moves(colour_grid, shape) {
+            let second_from = colour_grid[second];
+            // the second move mustn't take any region outside the limits
+            if second == index
+                || sizes[second_from as usize - 1] <= constraints.smallest_allowed()
+                || sizes[second_to as usize - 1] >= constraints.largest_allowed()
+            {
+                continue;
+            }
+            colour_grid[second] = second_to;
+            if connected(colour_grid) && count_queens_solutions(colour_grid, variant, 2) == 1 {
+                return true;
+            }
+            colour_grid[second] = second_from;
+        }
+        colour_grid[index] = from;
+    }
+    return false;
+}
+
+/// This function finds every way of moving a cell into a neighbouring region, as (cell, new colour).
+/// Cells that are a whole region on their own are left where they are, and void cells aren't moved.
+/// - colour_grid: the grid of colours, with 0 for void cells.
+/// - shape: the shape of the board.
+fn boundary_moves(colour_grid: &Vec<u32>, shape: &BoardShape) -> Vec<(usize, u32)> {
+    let mut moves: Vec<(usize, u32)> = Vec::new();
+    for index in (0..colour_grid.len()).filter(|&index| !shape.void[index]) {
+        let from = colour_grid[index];
+        let mut alone = true;
+        let mut targets: Vec<u32> = Vec::new();
+        for next in shape.neighbours(index) {
+            let to = colour_grid[next];
+            if to == from {
+                alone = false;
+            } else if !targets.contains(&to) {
+                targets.push(to);
+            }
+        }
+        if !alone {
+            moves.extend(targets.into_iter().map(|to| (index, to)));
+        }
+    }
+    return moves;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_metrics() {
+        let colour_grid: Vec<u32> = vec![
+            1, 1, 2, //
+            1, 1, 2, //
+            3, 3, 2,
+        ];
+        let metrics = region_metrics(&colour_grid, 3).unwrap();
+        assert_eq!(metrics.sizes, vec![4, 3, 2]);
+        assert_eq!(metrics.perimeters, vec![8, 8, 6]);
+        // the square is as compact as four cells can be
+        assert_eq!(metrics.compactness[0], 1.0);
+        assert!((metrics.size_variance - 2.0 / 3.0).abs() < 1e-6);
+        assert!(region_metrics(&colour_grid[..3].to_vec(), 3).is_err());
+    }
+
+    #[test]
+    fn test_rebalance_regions() {
+        let mut colour_grid: Vec<u32> = vec![
+            5, 5, 5, 2, 2, //
+            5, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 4, 4, 1, 2,
+        ];
+        let variant = QueensVariant::queens(5);
+        let constraints = variant.constraints;
+        assert!(!constraints.allows(&region_metrics(&colour_grid, 5).unwrap().sizes));
+        assert!(rebalance_regions(&mut colour_grid, &variant));
+        assert!(constraints.allows(&region_metrics(&colour_grid, 5).unwrap().sizes));
+        assert_eq!(count_queens_solutions(&colour_grid, &variant, 2), 1);
+        assert!(RegionConstraints {
+            max_region_size: Some(4),
+            ..constraints
+        }
+        .check(&variant.shape, 5)
+        .is_err());
+    }
+}
@@ -0,0 +1,229 @@
+/// The characters used to draw a board.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextStyle {
+    /// Plain ASCII, for terminals and logs that can't show box-drawing characters.
+    Ascii,
+    /// Unicode box-drawing characters.
+    Unicode,
+}
+
+/// The mark the player has put on a queens cell, as used by the frontend.
+pub const QUEEN_MARK: u32 = 1;
+/// The mark for a cell the player has crossed out.
+pub const X_MARK: u32 = 2;
+
+/// This function draws a queens board, with borders between cells of different colours.
+/// - colour_grid: the grid of colours.
+/// - marks: the marks on each cell (`QUEEN_MARK`, `X_MARK` or 0), if any.
+/// - size: the size of the grid.
+/// - style: the characters to draw with.
+pub fn render_queens(
+    colour_grid: &Vec<u32>,
+    marks: Option<&Vec<u32>>,
+    size: u32,
+    style: TextStyle,
+) -> Result<String, String> {
+    check_cells(colour_grid, marks, size)?;
+    let colour = |row: u32, col: u32| colour_grid[(row * size + col) as usize];
+    // like the frontend's getBorders, the edge of the grid and edges between colours get a border
+    let horizontal_border =
+        |row: u32, col: u32| row == 0 || row == size || colour(row - 1, col) != colour(row, col);
+    let vertical_border =
+        |row: u32, col: u32| col == 0 || col == size || colour(row, col - 1) != colour(row, col);
+    let cell = |index: usize| {
+        let mark = marks.map_or(0, |marks| marks[index]);
+        let symbol = match (mark, style) {
+            (QUEEN_MARK, TextStyle::Ascii) => "Q",
+            (QUEEN_MARK, TextStyle::Unicode) => "♛",
+            (X_MARK, TextStyle::Ascii) => "x",
+            (X_MARK, TextStyle::Unicode) => "×",
+            (_, TextStyle::Ascii) => ".",
+            (_, TextStyle::Unicode) => "·",
+        };
+        return format!(" {} ", symbol);
+    };
+    return Ok(draw_grid(
+        size,
+        style,
+        cell,
+        horizontal_border,
+        vertical_border,
+    ));
+}
+
+/// This function draws a numbers board. Givens are shown in brackets so they stand out from entries.
+/// - givens: the grid of givens, with 0 for empty cells.
+/// - entries: the values the player has filled in, if any. Cells with a given are ignored.
+/// - size: the size of the grid.
+/// - style: the characters to draw with.
+pub fn render_numbers(
+    givens: &Vec<u32>,
+    entries: Option<&Vec<u32>>,
+    size: u32,
+    style: TextStyle,
+) -> Result<String, String> {
+    check_cells(givens, entries, size)?;
+    let cell = |index: usize| {
+        let entry = entries.map_or(0, |entries| entries[index]);
+        if givens[index] != 0 {
+            return format!("[{}]", givens[index]);
+        } else if entry != 0 {
+            return format!(" {} ", entry);
+        } else if style == TextStyle::Ascii {
+            return " . ".to_string();
+        }
+        return " · ".to_string();
+    };
+    return Ok(draw_grid(size, style, cell, |_, _| true, |_, _| true));
+}
+
+/// This function checks that a board and the marks drawn on it have a cell for every square of the grid.
+/// - grid: the colour grid or the givens.
+/// - marks: the marks or entries on each cell, if any.
+/// - size: the size of the grid.
+fn check_cells(grid: &Vec<u32>, marks: Option<&Vec<u32>>, size: u32) -> Result<(), String> {
+    let n_cells = (size * size) as usize;
+    for cells in [Some(grid), marks].into_iter().flatten() {
+        if cells.len() != n_cells {
+            return Err(format!(
+                "expected {} cells for a {}x{} grid but found {}",
+                n_cells,
+                size,
+                size,
+                cells.len()
+            ));
+        }
+    }
+    return Ok(());
+}
+
+/// This function draws a grid of cells, each three characters wide, with lines along the chosen edges.
+/// - size: the size of the grid.
+/// - style: the characters to draw with.
+/// - cell: the text of each cell.
+/// - horizontal_border: whether there is a line above the cell at (row, col). `row` goes up to `size` for the bottom edge.
+/// - vertical_border: whether there is a line left of the cell at (row, col). `col` goes up to `size` for the right edge.
+fn draw_grid(
+    size: u32,
+    style: TextStyle,
+    cell: impl Fn(usize) -> String,
+    horizontal_border: impl Fn(u32, u32) -> bool,
+    vertical_border: impl Fn(u32, u32) -> bool,
+) -> String {
+    let (horizontal, vertical) = match style {
+        TextStyle::Ascii => ("---", '|'),
+        TextStyle::Unicode => ("───", '│'),
+    };
+    let mut text = String::new();
+    for row in 0..=size {
+        // the line of junctions and horizontal edges above the row
+        for col in 0..=size {
+            let up = row > 0 && vertical_border(row - 1, col);
+            let down = row < size && vertical_border(row, col);
+            let left = col > 0 && horizontal_border(row, col - 1);
+            let right = col < size && horizontal_border(row, col);
+            text.push(junction(up, down, left, right, style));
+            if col < size {
+                if horizontal_border(row, col) {
+                    text.push_str(horizontal);
+                } else {
+                    text.push_str("   ");
+                }
+            }
+        }
+        text.push('\n');
+        if row == size {
+            break;
+        }
+        // the line of cells and the vertical edges between them
+        for col in 0..=size {
+            if vertical_border(row, col) {
+                text.push(vertical);
+            } else {
+                text.push(' ');
+            }
+            if col < size {
+                text.push_str(&cell((row * size + col) as usize));
+            }
+        }
+        text.push('\n');
+    }
+    return text;
+}
+
+/// This function picks the character where grid lines meet, given which directions have a line.
+/// - up, down, left, right: whether a line leaves the junction in that direction.
+/// - style: the characters to draw with.
+fn junction(up: bool, down: bool, left: bool, right: bool, style: TextStyle) -> char {
+    let vertical = up || down;
+    let horizontal = left || right;
+    if style == TextStyle::Ascii {
+        return match (vertical, horizontal) {
+            (true, true) => '+',
+            (true, false) => '|',
+            (false, true) => '-',
+            (false, false) => ' ',
+        };
+    }
+    return match (up, down, left, right) {
+        (false, false, false, false) => ' ',
+        (_, _, false, false) => '│',
+        (false, false, _, _) => '─',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        _ => '┼',
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_queens() {
+        let colour_grid: Vec<u32> = vec![
+            1, 1, 2, //
+            1, 2, 2, //
+            3, 3, 3,
+        ];
+        let marks: Vec<u32> = vec![QUEEN_MARK, X_MARK, 0, 0, 0, 0, 0, 0, 0];
+        let expected = "\
++-------+---+
+| Q   x | . |
+|   +---+   |
+| . | .   . |
++---+-------+
+| .   .   . |
++-----------+
+";
+        assert_eq!(
+            render_queens(&colour_grid, Some(&marks), 3, TextStyle::Ascii).unwrap(),
+            expected
+        );
+        // a grid that is too short for its size is an error rather than a panic
+        assert!(render_queens(&colour_grid[..3].to_vec(), None, 3, TextStyle::Ascii).is_err());
+    }
+
+    #[test]
+    fn test_render_numbers() {
+        let givens: Vec<u32> = vec![1, 0, 0, 0];
+        let entries: Vec<u32> = vec![0, 2, 0, 0];
+        let expected = "\
+┌───┬───┐
+│[1]│ 2 │
+├───┼───┤
+│ · │ · │
+└───┴───┘
+";
+        assert_eq!(
+            render_numbers(&givens, Some(&entries), 2, TextStyle::Unicode).unwrap(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,442 @@
+use crate::clash::{Attack, ClashRules};
+use crate::numbers::NumbersRules;
+use crate::puzzle_file::{PuzzleFile, PuzzleRules};
+use crate::queens::{BoardShape, QueensVariant};
+use crate::utilities::GameKind;
+
+/// The current version of the share code format. Bump this whenever the layout changes, and keep
+/// reading the older versions.
+pub const SHARE_CODE_VERSION: u8 = 2;
+
+/// The largest grid a share code can hold, so a pasted code can't ask for a huge puzzle to be
+/// checked.
+pub const MAX_SHARE_SIZE: u32 = 16;
+
+/// The URL-safe base64 alphabet used for share codes.
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// This function turns a puzzle into a share code, with the rules, star count and shape it is
+/// played with. The code is laid out as `version, game, rows, cols, stars, clash rules, numbers
+/// rules, packed cells, checksum` and base64 encoded. Sudoku boxes follow the numbers rules,
+/// packed like the cells.
+/// - puzzle: the puzzle to share.
+pub fn encode_puzzle(puzzle: &PuzzleFile) -> Result<String, String> {
+    puzzle.check_form()?;
+    if !puzzle.rules.cages.is_empty() || !puzzle.rules.inequalities.is_empty() {
+        return Err("puzzles with cages or inequalities can't be shared yet".to_string());
+    }
+    let (rows, cols) = puzzle.dimensions();
+    let longest = rows.max(cols);
+    if longest > MAX_SHARE_SIZE {
+        return Err(format!("{}x{} grids can't be shared", rows, cols));
+    }
+    let grid = puzzle.board();
+    if let Some(value) = grid.iter().find(|&&value| value > longest) {
+        return Err(format!("the value {} is too large for the grid", value));
+    }
+    let stars = u8::try_from(puzzle.stars).map_err(|_| "too many stars to share".to_string())?;
+
+    let mut bytes = vec![
+        SHARE_CODE_VERSION,
+        game_to_byte(puzzle.game),
+        rows as u8,
+        cols as u8,
+        stars,
+        clash_to_byte(&puzzle.rules.clash),
+    ];
+    match &puzzle.rules.numbers {
+        NumbersRules::Latin => bytes.push(0),
+        NumbersRules::XLatin => bytes.push(1),
+        NumbersRules::AllDiagonals => bytes.push(2),
+        NumbersRules::Boxes(boxes) => {
+            bytes.push(3);
+            bytes.extend(pack_cells(boxes, bits_per_cell(puzzle.size)));
+        }
+    }
+    bytes.extend(pack_cells(grid, bits_per_cell(longest)));
+    let checksum = fletcher16(&bytes);
+    bytes.extend(checksum.to_be_bytes());
+    return Ok(to_base64(&bytes));
+}
+
+/// This function reads a share code, checking that it is intact and that the puzzle in it is valid
+/// under its rules. Codes from version 1, which held only the game, size and grid, are played with
+/// the standard rules.
+/// - code: the share code.
+pub fn decode_puzzle(code: &str) -> Result<PuzzleFile, String> {
+    let bytes = from_base64(code.trim())?;
+    if bytes.len() < 5 {
+        return Err("the share code is too short".to_string());
+    }
+    let (body, checksum) = bytes.split_at(bytes.len() - 2);
+    if fletcher16(body).to_be_bytes() != checksum {
+        return Err("the share code is damaged (checksum mismatch)".to_string());
+    }
+    let puzzle = match body[0] {
+        1 => decode_version_1(&body[1..])?,
+        SHARE_CODE_VERSION => decode_version_2(&body[1..])?,
+        version => return Err(format!("unsupported share code version {}", version)),
+    };
+    puzzle.validate()?;
+    return Ok(puzzle);
+}
+
+/// This function reads the body of a version 1 share code: `game, size, packed cells`.
+/// - body: the bytes after the version, without the checksum.
+fn decode_version_1(body: &[u8]) -> Result<PuzzleFile, String> {
+    let game = byte_to_game(body[0])?;
+    let size = body[1] as u32;
+    let grid = unpack_grid(&body[2..], size, size)?;
+    return shared_puzzle(game, grid, (size, size), 1, PuzzleRules::default());
+}
+
+/// This function reads the body of a version 2 share code:
+/// `game, rows, cols, stars, clash rules, numbers rules, packed cells`.
+/// - body: the bytes after the version, without the checksum.
+fn decode_version_2(body: &[u8]) -> Result<PuzzleFile, String> {
+    if body.len() < 6 {
+        return Err("the share code is too short".to_string());
+    }
+    let game = byte_to_game(body[0])?;
+    let (rows, cols, stars) = (body[1] as u32, body[2] as u32, body[3] as u32);
+    let mut rules = PuzzleRules {
+        clash: byte_to_clash(body[4])?,
+        ..PuzzleRules::default()
+    };
+    let mut cells = &body[6..];
+    rules.numbers = match body[5] {
+        0 => NumbersRules::Latin,
+        1 => NumbersRules::XLatin,
+        2 => NumbersRules::AllDiagonals,
+        3 => {
+            // the boxes are only read for square grids, so a bad size is caught with the cells
+            let bits = bits_per_cell(cols);
+            let n_cells = (cols * cols) as usize;
+            let n_bytes = (n_cells * bits as usize).div_ceil(8).min(cells.len());
+            let boxes = unpack_cells(&cells[..n_bytes], bits, n_cells)?;
+            cells = &cells[n_bytes..];
+            NumbersRules::Boxes(boxes)
+        }
+        byte => return Err(format!("unknown numbers rules {} in share code", byte)),
+    };
+    let grid = unpack_grid(cells, rows, cols)?;
+    return shared_puzzle(game, grid, (rows, cols), stars, rules);
+}
+
+/// This function unpacks the grid of a share code, checking its size and values.
+/// - bytes: the packed cells.
+/// - rows: the number of rows.
+/// - cols: the number of columns.
+fn unpack_grid(bytes: &[u8], rows: u32, cols: u32) -> Result<Vec<u32>, String> {
+    let longest = rows.max(cols);
+    if rows == 0 || cols == 0 {
+        return Err("the share code has an empty grid".to_string());
+    }
+    if longest > MAX_SHARE_SIZE {
+        return Err(format!(
+            "the share code's {}x{} grid is too large",
+            rows, cols
+        ));
+    }
+    let grid = unpack_cells(bytes, bits_per_cell(longest), (rows * cols) as usize)?;
+    if let Some(value) = grid.iter().find(|&&value| value > longest) {
+        return Err(format!("the value {} is too large for the grid", value));
+    }
+    return Ok(grid);
+}
+
+/// This function puts together the puzzle held in a share code. Queens boards with void cells,
+/// which are 0, or a rectangular grid are given their shape.
+/// - game: the game the puzzle is for.
+/// - grid: the colour grid for queens, or the grid of givens for numbers.
+/// - (rows, cols): the number of rows and columns.
+/// - stars: the number of stars in each row and region, for queens.
+/// - rules: the variant rules the puzzle is played with.
+fn shared_puzzle(
+    game: GameKind,
+    grid: Vec<u32>,
+    (rows, cols): (u32, u32),
+    stars: u32,
+    rules: PuzzleRules,
+) -> Result<PuzzleFile, String> {
+    let mut puzzle = match game {
+        GameKind::Queens => {
+            let variant = QueensVariant {
+                shape: BoardShape::of_grid(&grid, rows, cols),
+                stars,
+                ..QueensVariant::queens(rows)
+            };
+            PuzzleFile::queens_variant_board(grid, &variant)
+        }
+        GameKind::Numbers => {
+            if rows != cols {
+                return Err("numbers grids must be square".to_string());
+            }
+            PuzzleFile {
+                stars,
+                ..PuzzleFile::numbers(grid, cols)
+            }
+        }
+    };
+    puzzle.rules = rules;
+    return Ok(puzzle);
+}
+
+/// This function gives the byte used to store the clash rules in a share code: the attack in the
+/// lowest two bits, then whether the board wraps, then whether the lines are free.
+/// - rules: the clash rules.
+fn clash_to_byte(rules: &ClashRules) -> u8 {
+    let attack = match rules.attack {
+        Attack::King => 0,
+        Attack::Queen => 1,
+        Attack::Knight => 2,
+    };
+    return attack | (rules.wrap as u8) << 2 | (rules.free_lines as u8) << 3;
+}
+
+/// This function reads the clash rules stored in a share code.
+/// - byte: the stored byte.
+fn byte_to_clash(byte: u8) -> Result<ClashRules, String> {
+    let attack = match byte & 3 {
+        0 => Attack::King,
+        1 => Attack::Queen,
+        2 => Attack::Knight,
+        _ => return Err(format!("unknown clash rules {} in share code", byte)),
+    };
+    if byte >> 4 != 0 {
+        return Err(format!("unknown clash rules {} in share code", byte));
+    }
+    return Ok(ClashRules {
+        attack,
+        wrap: byte & 4 != 0,
+        free_lines: byte & 8 != 0,
+    });
+}
+
+/// This function gives the byte used to store a game in a share code.
+/// - game: the game.
+fn game_to_byte(game: GameKind) -> u8 {
+    match game {
+        GameKind::Queens => 0,
+        GameKind::Numbers => 1,
+    }
+}
+
+/// This function reads the game stored in a share code.
+/// - byte: the stored byte.
+fn byte_to_game(byte: u8) -> Result<GameKind, String> {
+    match byte {
+        0 => Ok(GameKind::Queens),
+        1 => Ok(GameKind::Numbers),
+        _ => Err(format!("unknown game {} in share code", byte)),
+    }
+}
+
+/// This function finds the number of bits needed to store values from 0 to size.
+/// - size: the size of the grid.
+fn bits_per_cell(size: u32) -> u32 {
+    return 32 - size.leading_zeros();
+}
+
+/// This function packs the cells into as few bytes as possible, most significant bit first.
+/// - grid: the cells to pack.
+/// - bits: the number of bits per cell.
+fn pack_cells(grid: &Vec<u32>, bits: u32) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut buffered_bits = 0;
+    for &value in grid {
+        buffer = (buffer << bits) | value;
+        buffered_bits += bits;
+        while buffered_bits >= 8 {
+            buffered_bits -= 8;
+            bytes.push((buffer >> buffered_bits) as u8);
+        }
+        buffer &= (1 << buffered_bits) - 1;
+    }
+    if buffered_bits > 0 {
+        bytes.push((buffer << (8 - buffered_bits)) as u8);
+    }
+    return bytes;
+}
+
+/// This function unpacks the cells packed by `pack_cells`.
+/// - bytes: the packed bytes.
+/// - bits: the number of bits per cell.
+/// - n_cells: the number of cells to unpack.
+fn unpack_cells(bytes: &[u8], bits: u32, n_cells: usize) -> Result<Vec<u32>, String> {
+    if bytes.len() != (n_cells * bits as usize).div_ceil(8) {
+        return Err("the share code has the wrong number of cells".to_string());
+    }
+    let mut grid: Vec<u32> = Vec::with_capacity(n_cells);
+    let mut buffer: u32 = 0;
+    let mut buffered_bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        buffered_bits += 8;
+        while buffered_bits >= bits && grid.len() < n_cells {
+            buffered_bits -= bits;
+            grid.push(buffer >> buffered_bits);
+            buffer &= (1 << buffered_bits) - 1;
+        }
+    }
+    return Ok(grid);
+}
+
+/// This function computes a Fletcher-16 checksum, which catches typos and truncated codes.
+/// - bytes: the bytes to check.
+fn fletcher16(bytes: &[u8]) -> u16 {
+    let mut sum1: u16 = 0;
+    let mut sum2: u16 = 0;
+    for &byte in bytes {
+        sum1 = (sum1 + byte as u16) % 255;
+        sum2 = (sum2 + sum1) % 255;
+    }
+    return (sum2 << 8) | sum1;
+}
+
+/// This function encodes bytes as unpadded URL-safe base64.
+/// - bytes: the bytes to encode.
+fn to_base64(bytes: &[u8]) -> String {
+    let mut code = String::new();
+    for chunk in bytes.chunks(3) {
+        let mut block = [0u8; 3];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let joined = (block[0] as u32) << 16 | (block[1] as u32) << 8 | block[2] as u32;
+        // 3 bytes become 4 characters, a partial chunk needs one more character than it has bytes
+        for i in 0..chunk.len() + 1 {
+            code.push(ALPHABET[((joined >> (18 - 6 * i)) & 63) as usize] as char);
+        }
+    }
+    return code;
+}
+
+/// This function decodes unpadded URL-safe base64.
+/// - code: the text to decode.
+fn from_base64(code: &str) -> Result<Vec<u8>, String> {
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut buffered_bits = 0;
+    for character in code.chars() {
+        let Some(value) = ALPHABET.iter().position(|&a| a as char == character) else {
+            return Err(format!("'{}' is not allowed in a share code", character));
+        };
+        buffer = (buffer << 6) | value as u32;
+        buffered_bits += 6;
+        if buffered_bits >= 8 {
+            buffered_bits -= 8;
+            bytes.push((buffer >> buffered_bits) as u8);
+            buffer &= (1 << buffered_bits) - 1;
+        }
+    }
+    return Ok(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::parse_queens_layout;
+    use crate::queens::generate_queens_game;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_round_trip() {
+        let queens: Vec<u32> = vec![
+            5, 5, 5, 2, 2, //
+            5, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 4, 4, 1, 2,
+        ];
+        let puzzle = PuzzleFile::queens(queens, 5);
+        assert_eq!(decode_puzzle(&encode_puzzle(&puzzle).unwrap()), Ok(puzzle));
+
+        let numbers = PuzzleFile::numbers(vec![0; 25], 5);
+        let code = encode_puzzle(&numbers).unwrap();
+        assert!(code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(decode_puzzle(&code), Ok(numbers));
+    }
+
+    #[test]
+    fn test_variants_keep_their_rules() {
+        // chess queens that may share rows and columns
+        let variant = QueensVariant {
+            clash: ClashRules {
+                attack: Attack::Queen,
+                free_lines: true,
+                ..ClashRules::default()
+            },
+            ..QueensVariant::queens(5)
+        };
+        let colour_grid = generate_queens_game(&variant, &mut StdRng::seed_from_u64(0)).unwrap();
+        let mut puzzle = PuzzleFile::queens_variant_board(colour_grid, &variant);
+        puzzle.rules.clash = variant.clash;
+        let decoded = decode_puzzle(&encode_puzzle(&puzzle).unwrap()).unwrap();
+        assert_eq!(decoded.queens_variant().clash, variant.clash);
+        assert_eq!(decoded, puzzle);
+
+        // a board with a void cell keeps its shape
+        let layout = parse_queens_layout("AAB# / ACBB / CCCD / DDDD").unwrap();
+        let variant = QueensVariant {
+            shape: BoardShape::of_grid(&layout.colour_grid, 4, 4),
+            ..QueensVariant::queens(4)
+        };
+        let puzzle = PuzzleFile::queens_variant_board(layout.colour_grid, &variant);
+        assert_eq!(decode_puzzle(&encode_puzzle(&puzzle).unwrap()), Ok(puzzle));
+
+        // a Latin square is checked under its own rules, and sudoku boxes come along too
+        let mut numbers = PuzzleFile::numbers(vec![0; 16], 4);
+        numbers.givens[0] = 1;
+        numbers.rules.numbers = NumbersRules::Latin;
+        let decoded = decode_puzzle(&encode_puzzle(&numbers).unwrap()).unwrap();
+        assert_eq!(decoded.rules.numbers, NumbersRules::Latin);
+        numbers.rules.numbers = NumbersRules::Boxes(vec![
+            1, 1, 2, 2, //
+            1, 1, 2, 2, //
+            3, 3, 4, 4, //
+            3, 3, 4, 4,
+        ]);
+        let decoded = decode_puzzle(&encode_puzzle(&numbers).unwrap()).unwrap();
+        assert_eq!(decoded.rules, numbers.rules);
+
+        numbers.rules.inequalities = vec![crate::futoshiki::Inequality {
+            smaller: 0,
+            larger: 1,
+        }];
+        assert!(encode_puzzle(&numbers).is_err());
+    }
+
+    #[test]
+    fn test_version_1_codes() {
+        let queens: Vec<u32> = vec![
+            5, 5, 5, 2, 2, //
+            5, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 5, 3, 2, 2, //
+            4, 4, 4, 1, 2,
+        ];
+        let mut bytes = vec![1, game_to_byte(GameKind::Queens), 5];
+        bytes.extend(pack_cells(&queens, bits_per_cell(5)));
+        bytes.extend(fletcher16(&bytes).to_be_bytes());
+        let decoded = decode_puzzle(&to_base64(&bytes)).unwrap();
+        assert_eq!(decoded, PuzzleFile::queens(queens, 5));
+    }
+
+    #[test]
+    fn test_damaged_codes_are_rejected() {
+        let code = encode_puzzle(&PuzzleFile::numbers(vec![0; 25], 5)).unwrap();
+        let mut damaged: Vec<char> = code.chars().collect();
+        damaged[4] = if damaged[4] == 'A' { 'B' } else { 'A' };
+        assert!(decode_puzzle(&damaged.into_iter().collect::<String>()).is_err());
+        assert!(decode_puzzle(&code[..code.len() - 2]).is_err());
+        assert!(decode_puzzle("not a code!").is_err());
+
+        // an empty 8x8 grid can't be filled under the default rules, and takes too long to check
+        let code = encode_puzzle(&PuzzleFile::numbers(vec![0; 64], 8)).unwrap();
+        assert!(decode_puzzle(&code).is_err());
+        assert!(encode_puzzle(&PuzzleFile::numbers(vec![0; 17 * 17], 17)).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::numbers::{generate_ruled_numbers_grid, remove_ruled_values, NumbersRules};
+use crate::queens::{split_regions, BoardShape};
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use serde::Serialize;
+
+/// The number of jigsaw layouts to try before giving up, as some layouts can't be filled at all
+/// and others take too long to fill.
+const MAX_JIGSAW_ATTEMPTS: u32 = 50;
+
+/// This struct is a sudoku puzzle along with its boxes.
+/// - givens: the grid of givens, with 0 for empty cells.
+/// - boxes: the box of each cell, numbered from 1.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SudokuBoard {
+    pub givens: Vec<u32>,
+    pub boxes: Vec<u32>,
+}
+
+/// This function finds the rows and columns of each box of a sudoku, as close to square as they
+/// can be, like 3x3 for a 9x9 grid and 2x3 for a 6x6 grid.
+/// - size: the size of the grid.
+pub fn box_shape(size: u32) -> Result<(u32, u32), String> {
+    let height = (1..=size)
+        .filter(|&height| size.is_multiple_of(height) && height * height <= size)
+        .max()
+        .unwrap_or(1);
+    if height == 1 && size > 1 {
+        return Err(format!(
+            "a {}x{} grid can't be split into rectangular boxes",
+            size, size
+        ));
+    }
+    return Ok((height, size / height));
+}
+
+/// This function gives the usual rectangular boxes of a sudoku.
+/// - size: the size of the grid.
+pub fn standard_boxes(size: u32) -> Result<Vec<u32>, String> {
+    let (height, width) = box_shape(size)?;
+    let boxes = (0..size * size)
+        .map(|index| {
+            let (row, col) = (index / size, index % size);
+            // there are `height` boxes across, as each is `size / height` wide
+            return (row / height) * height + col / width + 1;
+        })
+        .collect();
+    return Ok(boxes);
+}
+
+/// This function makes irregular jigsaw boxes by swapping cells between neighbouring boxes,
+/// starting from the usual boxes, or from the rows where the size has no rectangular boxes.
+/// Every box keeps `size` cells and stays connected.
+/// - size: the size of the grid.
+/// - rng: the random number generator to use.
+pub fn jigsaw_boxes<R: Rng>(size: u32, rng: &mut R) -> Vec<u32> {
+    let mut boxes = standard_boxes(size)
+        .unwrap_or_else(|_| (0..size * size).map(|index| index / size + 1).collect());
+    let shape = BoardShape::square(size);
+    for _ in 0..size * size * 8 {
+        let cell = rng.random_range(0..boxes.len());
+        let own = boxes[cell];
+        let others: Vec<u32> = shape
+            .neighbours(cell)
+            .into_iter()
+            .map(|next| boxes[next])
+            .filter(|&other| other != own)
+            .collect();
+        let Some(&other) = others.choose(rng) else {
+            continue;
+        };
+        // a cell of the other box comes back the other way so both keep their size
+        let returns: Vec<usize> = (0..boxes.len())
+            .filter(|&index| boxes[index] == other)
+            .filter(|&index| shape.neighbours(index).iter().any(|&n| boxes[n] == own))
+            .collect();
+        let Some(&back) = returns.choose(rng) else {
+            continue;
+        };
+        boxes[cell] = other;
+        boxes[back] = own;
+        if !split_regions(&boxes, size).is_empty() {
+            boxes[cell] = own;
+            boxes[back] = other;
+        }
+    }
+    return boxes;
+}
+
+/// This function checks that the boxes of a sudoku cover the grid, with `size` boxes of `size`
+/// cells each, numbered from 1, and every box connected.
+/// - boxes: the box of each cell.
+/// - size: the size of the grid.
+pub fn validate_boxes(boxes: &Vec<u32>, size: u32) -> Result<(), String> {
+    if boxes.len() != (size * size) as usize {
+        return Err(format!(
+            "expected boxes for {} cells of a {}x{} grid but found {}",
+            size * size,
+            size,
+            size,
+            boxes.len()
+        ));
+    }
+    let mut counts = vec![0; size as usize];
+    for &number in boxes {
+        if number == 0 || number > size {
+            return Err(format!(
+                "box {} is out of range, as there are {} boxes",
+                number, size
+            ));
+        }
+        counts[number as usize - 1] += 1;
+    }
+    for (number, &count) in counts.iter().enumerate() {
+        if count != size {
+            return Err(format!(
+                "box {} has {} cells but every box needs {}",
+                number + 1,
+                count,
+                size
+            ));
+        }
+    }
+    if !split_regions(boxes, size).is_empty() {
+        return Err("every box must be connected".to_string());
+    }
+    return Ok(());
+}
+
+/// This function generates a sudoku with a unique solution, with the usual boxes or jigsaw boxes.
+/// - size: the size of the grid.
+/// - difficulty: the difficulty setting.
+/// - jigsaw: whether the boxes are irregular.
+/// - rng: the random number generator to use.
+pub fn generate_sudoku<R: Rng>(
+    size: u32,
+    difficulty: u32,
+    jigsaw: bool,
+    rng: &mut R,
+) -> Result<SudokuBoard, String> {
+    let attempts = if jigsaw { MAX_JIGSAW_ATTEMPTS } else { 1 };
+    for _ in 0..attempts {
+        let boxes = if jigsaw {
+            jigsaw_boxes(size, rng)
+        } else {
+            standard_boxes(size)?
+        };
+        let rules = NumbersRules::Boxes(boxes.clone());
+        let solution = generate_ruled_numbers_grid(size, &rules, rng);
+        if solution.contains(&0) {
+            continue;
+        }
+        let givens = remove_ruled_values(&solution, difficulty, size, &rules, rng);
+        return Ok(SudokuBoard { givens, boxes });
+    }
+    return Err(format!("couldn't generate a {}x{} sudoku", size, size));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numbers::{
+        check_solution_ruled_numbers, count_ruled_numbers_solutions, find_ruled_numbers_solution,
+    };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_boxes() {
+        assert_eq!(box_shape(9), Ok((3, 3)));
+        assert_eq!(box_shape(6), Ok((2, 3)));
+        assert!(box_shape(7).is_err());
+        let boxes = standard_boxes(6).unwrap();
+        assert_eq!(boxes[..6], [1, 1, 1, 2, 2, 2]);
+        assert_eq!(boxes[12..18], [3, 3, 3, 4, 4, 4]);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let jigsaw = jigsaw_boxes(7, &mut rng);
+        assert_eq!(validate_boxes(&jigsaw, 7), Ok(()));
+        let mut broken = boxes.clone();
+        broken.swap(0, 35);
+        assert!(validate_boxes(&broken, 6).is_err());
+    }
+
+    #[test]
+    fn test_generate_sudoku() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for jigsaw in [false, true] {
+            let board = generate_sudoku(6, 1, jigsaw, &mut rng).unwrap();
+            let rules = NumbersRules::Boxes(board.boxes);
+            assert_eq!(
+                count_ruled_numbers_solutions(&board.givens, 6, &rules, 2),
+                1
+            );
+            let solution = find_ruled_numbers_solution(&board.givens, 6, &rules).unwrap();
+            assert!(check_solution_ruled_numbers(&solution, 6, &rules));
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use crate::puzzle_file::PuzzleFile;
+use crate::utilities::GameKind;
+use std::fmt::Write;
+
+/// The region colours used by the queens board in the app, in order of colour number.
+pub const DEFAULT_PALETTE: [&str; 10] = [
+    "#ffadad", "#ffd6a5", "#fdffb6", "#95e283", "#86dbe4", "#a0c4ff", "#e8a5f8", "#f39cf5",
+    "#f7ca8f", "#6a8cef",
+];
+
+/// This struct holds the settings for drawing a board as SVG.
+/// - cell_size: the width of each cell in pixels.
+/// - palette: the fill for each region colour, reused in order if there are more colours than fills.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    pub cell_size: u32,
+    pub palette: Vec<String>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        return SvgOptions {
+            cell_size: 48,
+            palette: DEFAULT_PALETTE.iter().map(|c| c.to_string()).collect(),
+        };
+    }
+}
+
+/// This function reads a colour written as `#rrggbb`, for drawing the palette in other formats.
+/// - colour: the colour.
+pub fn parse_hex_colour(colour: &str) -> Result<(u8, u8, u8), String> {
+    let hex = colour.strip_prefix('#').unwrap_or(colour);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("expected a colour like #a0c4ff but got {}", colour));
+    }
+    let part = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .map_err(|_| format!("expected a colour like #a0c4ff but got {}", colour))
+    };
+    return Ok((part(0)?, part(2)?, part(4)?));
+}
+
+/// This function draws a puzzle of either game as SVG, solving it under its rules first if the
+/// solution is wanted.
+/// - puzzle: the puzzle.
+/// - with_solution: whether to draw the solution on top.
+/// - options: the drawing settings.
+pub fn render_puzzle_svg(
+    puzzle: &PuzzleFile,
+    with_solution: bool,
+    options: &SvgOptions,
+) -> Result<String, String> {
+    let (game, grid, size) = (puzzle.game, puzzle.board(), puzzle.size);
+    puzzle.check_drawable()?;
+    if grid.len() != (size * size) as usize {
+        return Err(format!(
+            "expected {} cells for a {}x{} grid but found {}",
+            size * size,
+            size,
+            size,
+            grid.len()
+        ));
+    }
+    // the fills are written straight into the markup, so only plain colours are allowed
+    for colour in &options.palette {
+        parse_hex_colour(colour)?;
+    }
+    let mut solution = None;
+    if with_solution {
+        solution = puzzle.find_solution();
+        if solution.is_none() {
+            return Err("the puzzle has no solution".to_string());
+        }
+    }
+    match game {
+        GameKind::Queens => {
+            return Ok(render_queens_svg(grid, solution.as_ref(), size, options));
+        }
+        GameKind::Numbers => {
+            return Ok(render_numbers_svg(grid, solution.as_ref(), size, options));
+        }
+    }
+}
+
+/// This function draws a queens board as SVG, with a thick border around each region.
+/// - colour_grid: the grid of colours.
+/// - solution: the grid with 1 where each queen goes, to draw the queens, if any.
+/// - size: the size of the grid.
+/// - options: the drawing settings.
+pub fn render_queens_svg(
+    colour_grid: &Vec<u32>,
+    solution: Option<&Vec<u32>>,
+    size: u32,
+    options: &SvgOptions,
+) -> String {
+    let cell = options.cell_size;
+    let mut svg = open_svg(size, cell);
+    for (index, &colour) in colour_grid.iter().enumerate() {
+        let (x, y) = cell_origin(index, size, cell);
+        let fill = region_fill(colour, &options.palette);
+        let _ = writeln!(
+            svg,
+            r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            x, y, cell, cell, fill
+        );
+    }
+    draw_thin_lines(&mut svg, size, cell);
+
+    // thick lines wherever neighbouring cells are in different regions
+    let colour = |row: u32, col: u32| colour_grid[(row * size + col) as usize];
+    for row in 0..size {
+        for col in 0..size {
+            if col + 1 < size && colour(row, col) != colour(row, col + 1) {
+                let x = (col + 1) * cell;
+                draw_thick_line(&mut svg, x, row * cell, x, (row + 1) * cell);
+            }
+            if row + 1 < size && colour(row, col) != colour(row + 1, col) {
+                let y = (row + 1) * cell;
+                draw_thick_line(&mut svg, col * cell, y, (col + 1) * cell, y);
+            }
+        }
+    }
+    draw_outline(&mut svg, size, cell);
+
+    if let Some(solution) = solution {
+        for (index, _) in solution.iter().enumerate().filter(|&(_, &mark)| mark == 1) {
+            draw_text(&mut svg, index, size, cell, "♛", "#000000", false);
+        }
+    }
+    svg.push_str("</svg>\n");
+    return svg;
+}
+
+/// This function draws a numbers board as SVG. Givens are bold, solution values are lighter.
+/// - givens: the grid of givens, with 0 for empty cells.
+/// - solution: the solved grid, to fill in the empty cells, if any.
+/// - size: the size of the grid.
+/// - options: the drawing settings.
+pub fn render_numbers_svg(
+    givens: &Vec<u32>,
+    solution: Option<&Vec<u32>>,
+    size: u32,
+    options: &SvgOptions,
+) -> String {
+    let cell = options.cell_size;
+    let mut svg = open_svg(size, cell);
+    let _ = writeln!(
+        svg,
+        r##"  <rect x="0" y="0" width="{}" height="{}" fill="#ffffff"/>"##,
+        size * cell,
+        size * cell
+    );
+    draw_thin_lines(&mut svg, size, cell);
+    draw_outline(&mut svg, size, cell);
+    for index in 0..(size * size) as usize {
+        if givens[index] != 0 {
+            draw_text(
+                &mut svg,
+                index,
+                size,
+                cell,
+                &givens[index].to_string(),
+                "#000000",
+                true,
+            );
+        } else if let Some(solution) = solution {
+            draw_text(
+                &mut svg,
+                index,
+                size,
+                cell,
+                &solution[index].to_string(),
+                "#3a5bbf",
+                false,
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    return svg;
+}
+
+/// This function starts an SVG document big enough for the grid and its outline.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+fn open_svg(size: u32, cell: u32) -> String {
+    let width = size * cell;
+    // leave room for half of the outline's stroke on every side
+    return format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{w}\" viewBox=\"-2 -2 {w} {w}\">\n",
+        w = width + 4
+    );
+}
+
+/// This function finds the top left corner of a cell.
+/// - index: the cell.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+fn cell_origin(index: usize, size: u32, cell: u32) -> (u32, u32) {
+    let index = index as u32;
+    return ((index % size) * cell, (index / size) * cell);
+}
+
+/// This function gives the fill for a region colour, written out again as `#rrggbb` so nothing
+/// but a colour reaches the markup. Colours are numbered from 1, and fills that aren't colours
+/// are drawn white.
+/// - colour: the region colour.
+/// - palette: the fills to choose from.
+fn region_fill(colour: u32, palette: &[String]) -> String {
+    if palette.is_empty() {
+        return "#ffffff".to_string();
+    }
+    let fill = &palette[(colour.max(1) - 1) as usize % palette.len()];
+    let (r, g, b) = parse_hex_colour(fill).unwrap_or((255, 255, 255));
+    return format!("#{:02x}{:02x}{:02x}", r, g, b);
+}
+
+/// This function draws a thin line along every edge between cells.
+/// - svg: the document being drawn.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+fn draw_thin_lines(svg: &mut String, size: u32, cell: u32) {
+    let width = size * cell;
+    for i in 1..size {
+        let _ = writeln!(
+            svg,
+            r##"  <path d="M{p} 0V{w}M0 {p}H{w}" stroke="#00000055" stroke-width="1"/>"##,
+            p = i * cell,
+            w = width
+        );
+    }
+}
+
+/// This function draws a thick line, used for region borders.
+/// - svg: the document being drawn.
+/// - x1, y1, x2, y2: the ends of the line.
+fn draw_thick_line(svg: &mut String, x1: u32, y1: u32, x2: u32, y2: u32) {
+    let _ = writeln!(
+        svg,
+        r##"  <line x1="{}" y1="{}" x2="{}" y2="{}" stroke="#000000" stroke-width="3" stroke-linecap="square"/>"##,
+        x1, y1, x2, y2
+    );
+}
+
+/// This function draws the thick border around the whole grid.
+/// - svg: the document being drawn.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+fn draw_outline(svg: &mut String, size: u32, cell: u32) {
+    let _ = writeln!(
+        svg,
+        r##"  <rect x="0" y="0" width="{w}" height="{w}" fill="none" stroke="#000000" stroke-width="4"/>"##,
+        w = size * cell
+    );
+}
+
+/// This function writes text in the middle of a cell.
+/// - svg: the document being drawn.
+/// - index: the cell.
+/// - size: the size of the grid.
+/// - cell: the width of each cell.
+/// - text: the text to write.
+/// - fill: the colour of the text.
+/// - bold: whether the text is bold.
+fn draw_text(
+    svg: &mut String,
+    index: usize,
+    size: u32,
+    cell: u32,
+    text: &str,
+    fill: &str,
+    bold: bool,
+) {
+    let (x, y) = cell_origin(index, size, cell);
+    let weight = if bold { "bold" } else { "normal" };
+    let _ = writeln!(
+        svg,
+        r#"  <text x="{}" y="{}" font-family="sans-serif" font-size="{}" font-weight="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+        x + cell / 2,
+        y + cell / 2,
+        cell * 3 / 5,
+        weight,
+        fill,
+        text
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_borders() {
+        let colour_grid: Vec<u32> = vec![1, 1, 2, 2];
+        let solution: Vec<u32> = vec![1, 0, 0, 0];
+        let svg = render_queens_svg(&colour_grid, Some(&solution), 2, &SvgOptions::default());
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(DEFAULT_PALETTE[0]) && svg.contains(DEFAULT_PALETTE[1]));
+        // the two regions meet along the middle, so both cells of that edge get a thick line
+        assert_eq!(svg.matches("<line").count(), 2);
+        assert_eq!(svg.matches("♛").count(), 1);
+    }
+
+    #[test]
+    fn test_palette_is_checked() {
+        let options = SvgOptions {
+            palette: vec![r#"red"/><script>alert(1)</script><rect fill=""#.to_string()],
+            ..SvgOptions::default()
+        };
+        let colour_grid: Vec<u32> = vec![1, 1, 2, 2];
+        let puzzle = PuzzleFile::queens(colour_grid.clone(), 2);
+        assert!(render_puzzle_svg(&puzzle, false, &options).is_err());
+        // drawn directly, the bad fill still never reaches the markup
+        let svg = render_queens_svg(&colour_grid, None, 2, &options);
+        assert!(!svg.contains("script"));
+        assert!(parse_hex_colour("#+f+f+f").is_err());
+    }
+}
@@ -0,0 +1,490 @@
+use crate::queens::BoardShape;
+use crate::rating::{SolveReport, SolveStep, EASY, HARD, MEDIUM};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The value of a cell with a sun in it.
+pub const SUN: u32 = 1;
+/// The value of a cell with a moon in it.
+pub const MOON: u32 = 2;
+
+/// This enum is the kind of clue on the edge between two cells.
+/// - Equal: the two cells hold the same symbol.
+/// - Opposite: the two cells hold different symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    Equal,
+    Opposite,
+}
+
+/// This struct is a clue on the edge between two cells next to each other.
+/// - cell1: the index of the first cell.
+/// - cell2: the index of the second cell.
+/// - kind: whether the cells are the same or different.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeClue {
+    pub cell1: u32,
+    pub cell2: u32,
+    pub kind: EdgeKind,
+}
+
+/// This struct is a tango puzzle.
+/// - givens: the grid of givens, with 0 for empty cells, `SUN` and `MOON`.
+/// - clues: the edge clues.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TangoBoard {
+    pub givens: Vec<u32>,
+    pub clues: Vec<EdgeClue>,
+}
+
+/// This function gives the other symbol.
+/// - value: `SUN` or `MOON`.
+pub fn opposite(value: u32) -> u32 {
+    if value == SUN {
+        return MOON;
+    }
+    return SUN;
+}
+
+/// This function checks a partly filled grid breaks no rule: no line has more than half of
+/// either symbol or three of the same in a row, and every clue between filled cells holds.
+/// - grid: the grid, with 0 for empty cells.
+/// - clues: the edge clues.
+/// - size: the size of the grid.
+pub fn tango_consistent(grid: &Vec<u32>, clues: &Vec<EdgeClue>, size: u32) -> bool {
+    for line in BoardShape::square(size).lines() {
+        for symbol in [SUN, MOON] {
+            if line.iter().filter(|&&cell| grid[cell] == symbol).count() > size as usize / 2 {
+                return false;
+            }
+        }
+        let three_in_a_row = line.windows(3).any(|cells| {
+            grid[cells[0]] != 0
+                && grid[cells[0]] == grid[cells[1]]
+                && grid[cells[1]] == grid[cells[2]]
+        });
+        if three_in_a_row {
+            return false;
+        }
+    }
+    return clues.iter().all(|clue| {
+        let (value1, value2) = (grid[clue.cell1 as usize], grid[clue.cell2 as usize]);
+        if value1 == 0 || value2 == 0 {
+            return true;
+        }
+        return (value1 == value2) == (clue.kind == EdgeKind::Equal);
+    });
+}
+
+/// This function generates a tango puzzle with a unique solution that needs no technique harder
+/// than the given difficulty.
+/// - size: the size of the grid, which must be even.
+/// - difficulty: the hardest technique the solver may need, from `rating`.
+/// - rng: the random number generator to use.
+pub fn generate_tango<R: Rng>(
+    size: u32,
+    difficulty: u32,
+    rng: &mut R,
+) -> Result<TangoBoard, String> {
+    if size == 0 || !size.is_multiple_of(2) {
+        return Err(format!("tango grids need an even size, not {}", size));
+    }
+    let mut grid = vec![0; (size * size) as usize];
+    fill_tango_grid(&mut grid, size, 0, rng);
+
+    // about one clue per row, on random edges
+    let mut edges = BoardShape::square(size).edges();
+    edges.shuffle(rng);
+    let clues = edges
+        .into_iter()
+        .take(size as usize)
+        .map(|(cell1, cell2)| {
+            let kind = if grid[cell1 as usize] == grid[cell2 as usize] {
+                EdgeKind::Equal
+            } else {
+                EdgeKind::Opposite
+            };
+            return EdgeClue { cell1, cell2, kind };
+        })
+        .collect();
+    let mut board = TangoBoard {
+        givens: grid.clone(),
+        clues,
+    };
+
+    // take out each given that the puzzle can do without at this difficulty. A puzzle solved
+    // by deductions alone has only the one solution, so only trial and error needs counting.
+    let mut cells: Vec<usize> = (0..grid.len()).collect();
+    cells.shuffle(rng);
+    for cell in cells {
+        board.givens[cell] = 0;
+        let report = solve_tango_logically(&board, size);
+        let unique = report.solved || count_tango_solutions(&board, size, 2) == 1;
+        if !unique || report.difficulty > difficulty {
+            board.givens[cell] = grid[cell];
+        }
+    }
+    return Ok(board);
+}
+
+/// This function fills the grid one cell at a time recursively, with the symbols in a random order.
+/// - grid: the current grid state.
+/// - size: the size of the grid.
+/// - index: the cell to fill next.
+/// - rng: the random number generator to use.
+fn fill_tango_grid<R: Rng>(grid: &mut Vec<u32>, size: u32, index: usize, rng: &mut R) -> bool {
+    if index == grid.len() {
+        return true;
+    }
+    let mut symbols = [SUN, MOON];
+    symbols.shuffle(rng);
+    for symbol in symbols {
+        grid[index] = symbol;
+        if tango_consistent(grid, &Vec::new(), size) && fill_tango_grid(grid, size, index + 1, rng)
+        {
+            return true;
+        }
+    }
+    grid[index] = 0;
+    return false;
+}
+
+/// This function counts the ways the puzzle can be completed, stopping once the limit is reached.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - limit: the number of solutions after which we stop looking.
+pub fn count_tango_solutions(board: &TangoBoard, size: u32, limit: u32) -> u32 {
+    return tango_solutions(board, size, limit).len() as u32;
+}
+
+/// This function finds a completion of the puzzle.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+pub fn find_tango_solution(board: &TangoBoard, size: u32) -> Option<Vec<u32>> {
+    return tango_solutions(board, size, 1).pop();
+}
+
+/// This function finds up to `limit` completions of the puzzle.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - limit: the number of solutions after which we stop looking.
+fn tango_solutions(board: &TangoBoard, size: u32, limit: u32) -> Vec<Vec<u32>> {
+    let mut solutions: Vec<Vec<u32>> = Vec::new();
+    if validate_tango(board, size).is_err() || !tango_consistent(&board.givens, &board.clues, size)
+    {
+        return solutions;
+    }
+    let mut grid = board.givens.clone();
+    complete_tango(
+        &mut grid,
+        &board.clues,
+        size,
+        0,
+        limit as usize,
+        &mut solutions,
+    );
+    return solutions;
+}
+
+/// This function recursively fills the empty cells in order, collecting each complete grid.
+/// - grid: the current grid state.
+/// - clues: the edge clues.
+/// - size: the size of the grid.
+/// - index: the cell to fill next.
+/// - limit: the number of solutions after which we stop looking.
+/// - solutions: the complete grids found so far.
+fn complete_tango(
+    grid: &mut Vec<u32>,
+    clues: &Vec<EdgeClue>,
+    size: u32,
+    index: usize,
+    limit: usize,
+    solutions: &mut Vec<Vec<u32>>,
+) {
+    if solutions.len() >= limit {
+        return;
+    }
+    if index == grid.len() {
+        solutions.push(grid.clone());
+        return;
+    }
+    if grid[index] != 0 {
+        complete_tango(grid, clues, size, index + 1, limit, solutions);
+        return;
+    }
+    for symbol in [SUN, MOON] {
+        grid[index] = symbol;
+        if tango_consistent(grid, clues, size) {
+            complete_tango(grid, clues, size, index + 1, limit, solutions);
+        }
+    }
+    grid[index] = 0;
+}
+
+/// This function checks that a tango puzzle fits its grid: an even size, only suns, moons and
+/// empty cells, and clues between cells next to each other.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+pub fn validate_tango(board: &TangoBoard, size: u32) -> Result<(), String> {
+    if size == 0 || !size.is_multiple_of(2) {
+        return Err(format!("tango grids need an even size, not {}", size));
+    }
+    if board.givens.len() != (size * size) as usize {
+        return Err(format!(
+            "expected {} cells for a {}x{} grid but found {}",
+            size * size,
+            size,
+            size,
+            board.givens.len()
+        ));
+    }
+    if let Some(value) = board.givens.iter().find(|&&value| value > MOON) {
+        return Err(format!("{} isn't a sun or a moon", value));
+    }
+    let shape = BoardShape::square(size);
+    for clue in &board.clues {
+        shape.check_edge(clue.cell1, clue.cell2)?;
+    }
+    return Ok(());
+}
+
+/// This function checks if a full grid is a solution to a tango puzzle.
+/// - tango_grid: the grid of suns and moons.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+pub fn check_tango_solution(tango_grid: &Vec<u32>, board: &TangoBoard, size: u32) -> bool {
+    if validate_tango(board, size).is_err() || tango_grid.len() != board.givens.len() {
+        return false;
+    }
+    let full = tango_grid
+        .iter()
+        .all(|&value| value == SUN || value == MOON);
+    let givens_kept = board
+        .givens
+        .iter()
+        .zip(tango_grid)
+        .all(|(&given, &value)| given == 0 || given == value);
+    return full && givens_kept && tango_consistent(tango_grid, &board.clues, size);
+}
+
+/// This function gives the name of a tango symbol.
+/// - value: `SUN` or `MOON`.
+fn symbol_name(value: u32) -> &'static str {
+    if value == SUN {
+        return "sun";
+    }
+    return "moon";
+}
+
+/// This function solves a tango puzzle using only deductions, recording each step.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+pub fn solve_tango_logically(board: &TangoBoard, size: u32) -> SolveReport {
+    let mut grid = board.givens.clone();
+    let mut steps: Vec<SolveStep> = Vec::new();
+    loop {
+        if !grid.contains(&0) {
+            let solved = tango_consistent(&grid, &board.clues, size);
+            return SolveReport::new(steps, solved);
+        }
+        let found =
+            tango_deduction(&grid, board, size).or_else(|| tango_look_ahead(&grid, board, size));
+        let Some((index, value, step)) = found else {
+            return SolveReport::new(steps, false);
+        };
+        grid[index] = value;
+        steps.push(step);
+    }
+}
+
+/// This function finds a cell forced by a clue or by the three in a row rule, or failing that by
+/// a line that already has half its cells of one symbol.
+/// - grid: the grid part way through being solved.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+fn tango_deduction(
+    grid: &Vec<u32>,
+    board: &TangoBoard,
+    size: u32,
+) -> Option<(usize, u32, SolveStep)> {
+    let name = |index: usize| {
+        format!(
+            "r{}c{}",
+            index / size as usize + 1,
+            index % size as usize + 1
+        )
+    };
+    for clue in &board.clues {
+        let (cell1, cell2) = (clue.cell1 as usize, clue.cell2 as usize);
+        for (from, to) in [(cell1, cell2), (cell2, cell1)] {
+            if grid[from] == 0 || grid[to] != 0 {
+                continue;
+            }
+            let value = match clue.kind {
+                EdgeKind::Equal => grid[from],
+                EdgeKind::Opposite => opposite(grid[from]),
+            };
+            let description = format!(
+                "{} is a {} because of its clue with {}",
+                name(to),
+                symbol_name(value),
+                name(from)
+            );
+            return Some((
+                to,
+                value,
+                SolveStep {
+                    level: EASY,
+                    description,
+                },
+            ));
+        }
+    }
+    let lines = BoardShape::square(size).lines();
+    for line in &lines {
+        for cells in line.windows(3) {
+            let empty: Vec<usize> = cells
+                .iter()
+                .copied()
+                .filter(|&cell| grid[cell] == 0)
+                .collect();
+            if empty.len() != 1 {
+                continue;
+            }
+            let filled: Vec<u32> = cells
+                .iter()
+                .map(|&cell| grid[cell])
+                .filter(|&value| value != 0)
+                .collect();
+            if filled[0] == filled[1] {
+                let value = opposite(filled[0]);
+                let description = format!(
+                    "{} is a {} so there aren't three {}s in a row",
+                    name(empty[0]),
+                    symbol_name(value),
+                    symbol_name(filled[0])
+                );
+                return Some((
+                    empty[0],
+                    value,
+                    SolveStep {
+                        level: EASY,
+                        description,
+                    },
+                ));
+            }
+        }
+    }
+    for line in &lines {
+        for symbol in [SUN, MOON] {
+            let full =
+                line.iter().filter(|&&cell| grid[cell] == symbol).count() == size as usize / 2;
+            let Some(&cell) = line.iter().find(|&&cell| grid[cell] == 0) else {
+                continue;
+            };
+            if full {
+                let value = opposite(symbol);
+                let description = format!(
+                    "{} is a {} as its line already has all its {}s",
+                    name(cell),
+                    symbol_name(value),
+                    symbol_name(symbol)
+                );
+                return Some((
+                    cell,
+                    value,
+                    SolveStep {
+                        level: MEDIUM,
+                        description,
+                    },
+                ));
+            }
+        }
+    }
+    return None;
+}
+
+/// This function finds a cell where one symbol leads, by the simpler deductions, to a broken
+/// rule, so the cell must hold the other symbol.
+/// - grid: the grid part way through being solved.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+fn tango_look_ahead(
+    grid: &Vec<u32>,
+    board: &TangoBoard,
+    size: u32,
+) -> Option<(usize, u32, SolveStep)> {
+    for index in 0..grid.len() {
+        if grid[index] != 0 {
+            continue;
+        }
+        for symbol in [SUN, MOON] {
+            let mut trial = grid.clone();
+            trial[index] = symbol;
+            while tango_consistent(&trial, &board.clues, size) {
+                let Some((next, value, _)) = tango_deduction(&trial, board, size) else {
+                    break;
+                };
+                trial[next] = value;
+            }
+            if !tango_consistent(&trial, &board.clues, size) {
+                let value = opposite(symbol);
+                let description = format!(
+                    "a {} on r{}c{} leads to a broken rule, so it is a {}",
+                    symbol_name(symbol),
+                    index / size as usize + 1,
+                    index % size as usize + 1,
+                    symbol_name(value)
+                );
+                return Some((
+                    index,
+                    value,
+                    SolveStep {
+                        level: HARD,
+                        description,
+                    },
+                ));
+            }
+        }
+    }
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_tango_rules() {
+        let clues = vec![EdgeClue {
+            cell1: 0,
+            cell2: 1,
+            kind: EdgeKind::Opposite,
+        }];
+        let mut grid = vec![0; 16];
+        grid[0] = SUN;
+        grid[1] = SUN;
+        assert!(!tango_consistent(&grid, &clues, 4));
+        assert!(tango_consistent(&grid, &Vec::new(), 4));
+        grid[2] = SUN;
+        assert!(!tango_consistent(&grid, &Vec::new(), 4));
+        assert!(generate_tango(5, EASY, &mut StdRng::seed_from_u64(0)).is_err());
+    }
+
+    #[test]
+    fn test_generate_tango() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for difficulty in [EASY, HARD] {
+            let board = generate_tango(6, difficulty, &mut rng).unwrap();
+            assert_eq!(count_tango_solutions(&board, 6, 2), 1);
+            let report = solve_tango_logically(&board, 6);
+            assert!(report.solved && report.difficulty <= difficulty);
+            let solution = find_tango_solution(&board, 6).unwrap();
+            assert!(check_tango_solution(&solution, &board, 6));
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// This struct keeps track of the time spent on a single solve.
+/// The time spent paused (e.g. while the window is blurred) is not counted.
+#[derive(Debug, Clone)]
+pub struct SolveTimer {
+    started: Instant,
+    paused_at: Option<Instant>,
+    paused_total: Duration,
+    finished: Option<Duration>,
+}
+
+impl SolveTimer {
+    /// This function starts a new timer.
+    /// - now: the time at which the solve started.
+    pub fn start(now: Instant) -> SolveTimer {
+        return SolveTimer {
+            started: now,
+            paused_at: None,
+            paused_total: Duration::ZERO,
+            finished: None,
+        };
+    }
+
+    /// This function pauses the timer. Pausing an already paused or finished timer does nothing.
+    /// - now: the time at which the timer was paused.
+    pub fn pause(&mut self, now: Instant) {
+        if self.paused_at.is_none() && self.finished.is_none() {
+            self.paused_at = Some(now);
+        }
+    }
+
+    /// This function resumes the timer, adding the paused interval to the total paused time.
+    /// - now: the time at which the timer was resumed.
+    pub fn resume(&mut self, now: Instant) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_total += now.saturating_duration_since(paused_at);
+        }
+    }
+
+    /// This function finds the time spent solving so far, excluding any paused intervals.
+    /// - now: the current time.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        if let Some(finished) = self.finished {
+            return finished;
+        }
+        // if we are paused, the clock stopped when we paused
+        let end = self.paused_at.unwrap_or(now);
+        return end
+            .saturating_duration_since(self.started)
+            .saturating_sub(self.paused_total);
+    }
+
+    /// This function stops the timer and returns the official solve time.
+    /// Once finished, the solve time no longer changes.
+    /// - now: the time at which the solve was finished.
+    pub fn finish(&mut self, now: Instant) -> Duration {
+        let elapsed = self.elapsed(now);
+        self.paused_at = None;
+        self.finished = Some(elapsed);
+        return elapsed;
+    }
+}
+
+/// A check of a submitted solution against the puzzle that was handed out, which it holds.
+pub type SolutionChecker = Box<dyn Fn(&Vec<u32>) -> bool + Send>;
+
+/// This struct is a single game being played.
+/// - timer: the time spent solving.
+/// - checker: checks a submitted solution against the puzzle the session was started with.
+struct SolveSession {
+    timer: SolveTimer,
+    checker: SolutionChecker,
+}
+
+/// This struct holds the session for each game currently being played.
+#[derive(Default)]
+pub struct SolveSessions {
+    sessions: HashMap<String, SolveSession>,
+}
+
+impl SolveSessions {
+    /// This function starts a new session for a game, replacing any previous one.
+    /// - game: the name of the game.
+    /// - checker: checks a submitted solution against the puzzle that was handed out.
+    pub fn start(&mut self, game: &str, checker: SolutionChecker) {
+        let session = SolveSession {
+            timer: SolveTimer::start(Instant::now()),
+            checker,
+        };
+        self.sessions.insert(game.to_string(), session);
+    }
+
+    /// This function pauses the session for a game, if there is one.
+    /// - game: the name of the game.
+    pub fn pause(&mut self, game: &str) {
+        if let Some(session) = self.sessions.get_mut(game) {
+            session.timer.pause(Instant::now());
+        }
+    }
+
+    /// This function resumes the session for a game, if there is one.
+    /// - game: the name of the game.
+    pub fn resume(&mut self, game: &str) {
+        if let Some(session) = self.sessions.get_mut(game) {
+            session.timer.resume(Instant::now());
+        }
+    }
+
+    /// This function checks a solution against the puzzle the session was started with, and
+    /// stops the timer if it is valid. A game without a session has nothing to solve, so every
+    /// solution for it is invalid.
+    /// - game: the name of the game.
+    /// - solution: the solution sent back by the player.
+    pub fn submit(&mut self, game: &str, solution: &Vec<u32>) -> SolveResult {
+        let Some(session) = self.sessions.get_mut(game) else {
+            return SolveResult {
+                valid: false,
+                solve_time_ms: None,
+            };
+        };
+        let valid = (session.checker)(solution);
+        let mut solve_time_ms = None;
+        if valid {
+            solve_time_ms = Some(session.timer.finish(Instant::now()).as_millis() as u64);
+        }
+        return SolveResult {
+            valid,
+            solve_time_ms,
+        };
+    }
+}
+
+/// This struct is the result of checking a solution.
+/// - valid: whether the solution is valid.
+/// - solve_time_ms: the official solve time, only given for valid solutions.
+#[derive(Debug, Clone, Serialize)]
+pub struct SolveResult {
+    pub valid: bool,
+    pub solve_time_ms: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paused_time_is_not_counted() {
+        let start = Instant::now();
+        let mut timer = SolveTimer::start(start);
+        timer.pause(start + Duration::from_secs(10));
+        // pausing twice should not move the pause start
+        timer.pause(start + Duration::from_secs(20));
+        timer.resume(start + Duration::from_secs(40));
+        assert_eq!(
+            timer.elapsed(start + Duration::from_secs(50)),
+            Duration::from_secs(20)
+        );
+        // while paused, the clock doesn't move
+        timer.pause(start + Duration::from_secs(50));
+        assert_eq!(
+            timer.elapsed(start + Duration::from_secs(90)),
+            Duration::from_secs(20)
+        );
+    }
+
+    #[test]
+    fn test_finish_freezes_time() {
+        let start = Instant::now();
+        let mut timer = SolveTimer::start(start);
+        assert_eq!(
+            timer.finish(start + Duration::from_secs(5)),
+            Duration::from_secs(5)
+        );
+        timer.resume(start + Duration::from_secs(6));
+        assert_eq!(
+            timer.elapsed(start + Duration::from_secs(100)),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn test_submit_checks_the_issued_puzzle() {
+        let mut sessions = SolveSessions::default();
+        assert!(!sessions.submit("numbers", &vec![1]).valid);
+        let issued: Vec<u32> = vec![1, 2, 2, 1];
+        sessions.start("numbers", Box::new(move |grid| *grid == issued));
+        // a board the server didn't hand out doesn't stop the clock
+        let faked = sessions.submit("numbers", &vec![1]);
+        assert!(!faked.valid);
+        assert_eq!(faked.solve_time_ms, None);
+        let solved = sessions.submit("numbers", &vec![1, 2, 2, 1]);
+        assert!(solved.valid);
+        assert!(solved.solve_time_ms.is_some());
+    }
+}
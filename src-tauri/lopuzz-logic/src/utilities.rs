@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// The games that can be played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameKind {
+    Queens,
+    Numbers,
+}
+
+impl GameKind {
+    /// This function gives the name of the game, as used by the frontend.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameKind::Queens => "queens",
+            GameKind::Numbers => "numbers",
+        }
+    }
+}
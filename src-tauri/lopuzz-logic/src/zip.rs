@@ -0,0 +1,441 @@
+use crate::queens::BoardShape;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The number of backbite moves per cell used to shuffle the path away from its starting zigzag.
+const SHUFFLES_PER_CELL: u32 = 20;
+/// The most cells the uniqueness check may visit when deciding on waypoints. Past this the
+/// waypoint is kept.
+const MAX_CHECK_STEPS: u32 = 50_000;
+
+/// This struct is a wall between two cells next to each other that the path can't cross.
+/// - cell1: the index of the first cell.
+/// - cell2: the index of the second cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wall {
+    pub cell1: u32,
+    pub cell2: u32,
+}
+
+/// This struct is a zip puzzle: a path through every cell that visits the numbered cells in order.
+/// - numbers: the waypoint number of each cell, from 1, with 0 for cells without one.
+/// - walls: the walls between cells.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZipBoard {
+    pub numbers: Vec<u32>,
+    pub walls: Vec<Wall>,
+}
+
+/// This function generates a random path through every cell of the grid, starting from a zigzag
+/// through the rows and reshaping it with backbite moves: joining an end of the path to one of
+/// its neighbours and reversing the part of the path after that neighbour.
+/// - size: the size of the grid.
+/// - rng: the random number generator to use.
+pub fn generate_zip_path<R: Rng>(size: u32, rng: &mut R) -> Vec<u32> {
+    let mut path: Vec<u32> = (0..size * size)
+        .map(|index| {
+            let (row, col) = (index / size, index % size);
+            let col = if row % 2 == 0 { col } else { size - 1 - col };
+            return row * size + col;
+        })
+        .collect();
+    if path.len() < 3 {
+        return path;
+    }
+    let shape = BoardShape::square(size);
+    for _ in 0..size * size * SHUFFLES_PER_CELL {
+        if rng.random_bool(0.5) {
+            path.reverse();
+        }
+        let end = path[path.len() - 1];
+        let before = path[path.len() - 2];
+        let options: Vec<usize> = shape
+            .neighbours(end as usize)
+            .into_iter()
+            .filter(|&next| next as u32 != before)
+            .collect();
+        let Some(&next) = options.choose(rng) else {
+            continue;
+        };
+        let position = path.iter().position(|&cell| cell as usize == next).unwrap();
+        path[position + 1..].reverse();
+    }
+    return path;
+}
+
+/// This function generates a zip puzzle whose path is the only one through its waypoints, using
+/// as few waypoints as it can.
+/// - size: the size of the grid.
+/// - with_walls: whether to put walls between some cells the path doesn't go between.
+/// - rng: the random number generator to use.
+pub fn generate_zip<R: Rng>(size: u32, with_walls: bool, rng: &mut R) -> Result<ZipBoard, String> {
+    if size < 2 {
+        return Err(format!("a {}x{} grid is too small for a path", size, size));
+    }
+    let path = generate_zip_path(size, rng);
+    let mut walls: Vec<Wall> = Vec::new();
+    if with_walls {
+        let mut edges: Vec<Wall> = BoardShape::square(size)
+            .edges()
+            .into_iter()
+            .map(|(cell1, cell2)| Wall { cell1, cell2 })
+            .collect();
+        // walls can only go where the path doesn't cross
+        edges.retain(|wall| {
+            return !path.windows(2).any(|step| {
+                (step[0] == wall.cell1 && step[1] == wall.cell2)
+                    || (step[0] == wall.cell2 && step[1] == wall.cell1)
+            });
+        });
+        edges.shuffle(rng);
+        walls = edges.into_iter().take(size as usize).collect();
+    }
+
+    // the ends are always waypoints. Add the cell where another path strays until none do.
+    let mut waypoints = vec![false; path.len()];
+    waypoints[0] = true;
+    waypoints[path.len() - 1] = true;
+    loop {
+        let board = ZipBoard {
+            numbers: number_waypoints(&path, &waypoints),
+            walls: walls.clone(),
+        };
+        let (solutions, finished) = zip_solutions(&board, size, 2, MAX_CHECK_STEPS);
+        let other = solutions.iter().find(|&solution| *solution != path);
+        let strays = match other {
+            // another path can reach a waypoint at a different step, so skip those
+            Some(other) => (0..path.len()).find(|&at| other[at] != path[at] && !waypoints[at]),
+            None if finished => break,
+            None => None,
+        };
+        // failing that, split the longest stretch without a waypoint
+        let position = strays.or_else(|| longest_gap_middle(&waypoints));
+        let Some(position) = position else {
+            return Err(format!(
+                "couldn't show the {}x{} path is the only one",
+                size, size
+            ));
+        };
+        waypoints[position] = true;
+    }
+
+    let mut positions: Vec<usize> = (1..path.len() - 1).filter(|&at| waypoints[at]).collect();
+    positions.shuffle(rng);
+    for position in positions {
+        waypoints[position] = false;
+        let board = ZipBoard {
+            numbers: number_waypoints(&path, &waypoints),
+            walls: walls.clone(),
+        };
+        let (solutions, finished) = zip_solutions(&board, size, 2, MAX_CHECK_STEPS);
+        if !finished || solutions.len() != 1 {
+            waypoints[position] = true;
+        }
+    }
+    return Ok(ZipBoard {
+        numbers: number_waypoints(&path, &waypoints),
+        walls,
+    });
+}
+
+/// This function numbers the waypoints in the order the path visits them.
+/// - path: the cells in the order they're visited.
+/// - waypoints: whether each step of the path is a waypoint.
+fn number_waypoints(path: &Vec<u32>, waypoints: &Vec<bool>) -> Vec<u32> {
+    let mut numbers = vec![0; path.len()];
+    let mut number = 0;
+    for (position, &cell) in path.iter().enumerate() {
+        if waypoints[position] {
+            number += 1;
+            numbers[cell as usize] = number;
+        }
+    }
+    return numbers;
+}
+
+/// This function finds the step in the middle of the longest stretch of the path between waypoints.
+/// - waypoints: whether each step of the path is a waypoint.
+fn longest_gap_middle(waypoints: &Vec<bool>) -> Option<usize> {
+    let positions: Vec<usize> = (0..waypoints.len()).filter(|&at| waypoints[at]).collect();
+    let (start, end) = positions
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .max_by_key(|&(start, end)| end - start)?;
+    if end - start < 2 {
+        return None;
+    }
+    return Some((start + end) / 2);
+}
+
+/// This function lists the cells each cell can step to, leaving out those behind a wall.
+/// - walls: the walls between cells.
+/// - size: the size of the grid.
+fn zip_links(walls: &Vec<Wall>, size: u32) -> Vec<Vec<usize>> {
+    let shape = BoardShape::square(size);
+    return (0..(size * size) as usize)
+        .map(|cell| {
+            return shape
+                .neighbours(cell)
+                .into_iter()
+                .filter(|&next| {
+                    return !walls.iter().any(|wall| {
+                        let (cell1, cell2) = (wall.cell1 as usize, wall.cell2 as usize);
+                        (cell1, cell2) == (cell, next) || (cell1, cell2) == (next, cell)
+                    });
+                })
+                .collect();
+        })
+        .collect();
+}
+
+/// This function counts the paths that solve the puzzle, stopping once the limit is reached.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - limit: the number of solutions after which we stop looking.
+pub fn count_zip_solutions(board: &ZipBoard, size: u32, limit: u32) -> u32 {
+    return zip_solutions(board, size, limit, u32::MAX).0.len() as u32;
+}
+
+/// This function finds a path that solves the puzzle.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+pub fn find_zip_solution(board: &ZipBoard, size: u32) -> Option<Vec<u32>> {
+    return zip_solutions(board, size, 1, u32::MAX).0.pop();
+}
+
+/// This function finds up to `limit` paths that solve the puzzle, and whether the search
+/// finished within the steps it was given.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+/// - limit: the number of solutions after which we stop looking.
+/// - steps: the most cells the search may visit.
+fn zip_solutions(board: &ZipBoard, size: u32, limit: u32, steps: u32) -> (Vec<Vec<u32>>, bool) {
+    if validate_zip(board, size).is_err() {
+        return (Vec::new(), true);
+    }
+    let start = board
+        .numbers
+        .iter()
+        .position(|&number| number == 1)
+        .unwrap();
+    let last = *board.numbers.iter().max().unwrap();
+    let mut search = ZipSearch {
+        links: zip_links(&board.walls, size),
+        numbers: &board.numbers,
+        last_cell: board
+            .numbers
+            .iter()
+            .position(|&number| number == last)
+            .unwrap(),
+        visited: vec![false; board.numbers.len()],
+        path: vec![start],
+        next_number: 2,
+        limit: limit as usize,
+        solutions: Vec::new(),
+        steps_left: steps,
+    };
+    search.visited[start] = true;
+    search.search(start);
+    return (search.solutions, search.steps_left > 0);
+}
+
+/// This struct holds the state of a search for the paths through a zip puzzle.
+/// - links: the cells each cell can step to.
+/// - numbers: the waypoint number of each cell.
+/// - last_cell: the cell with the last waypoint, where the path must end.
+/// - visited: whether the path has been through each cell.
+/// - path: the cells visited so far, in order.
+/// - next_number: the number of the next waypoint to visit.
+/// - limit: the number of solutions after which we stop looking.
+/// - solutions: the full paths found so far.
+/// - steps_left: the number of cells that can still be visited.
+struct ZipSearch<'a> {
+    links: Vec<Vec<usize>>,
+    numbers: &'a Vec<u32>,
+    last_cell: usize,
+    visited: Vec<bool>,
+    path: Vec<usize>,
+    next_number: u32,
+    limit: usize,
+    solutions: Vec<Vec<u32>>,
+    steps_left: u32,
+}
+
+impl ZipSearch<'_> {
+    /// This function extends the path from its head in every way that could still finish.
+    /// - head: the last cell of the path.
+    fn search(&mut self, head: usize) {
+        if self.solutions.len() >= self.limit || self.steps_left == 0 {
+            return;
+        }
+        self.steps_left -= 1;
+        if self.path.len() == self.visited.len() {
+            self.solutions
+                .push(self.path.iter().map(|&cell| cell as u32).collect());
+            return;
+        }
+        for option in 0..self.links[head].len() {
+            let next = self.links[head][option];
+            let number = self.numbers[next];
+            if self.visited[next] || (number != 0 && number != self.next_number) {
+                continue;
+            }
+            self.visited[next] = true;
+            self.path.push(next);
+            if number != 0 {
+                self.next_number += 1;
+            }
+            if self.can_finish(next) {
+                self.search(next);
+            }
+            if number != 0 {
+                self.next_number -= 1;
+            }
+            self.path.pop();
+            self.visited[next] = false;
+        }
+    }
+
+    /// This function checks the cells left can still be joined up from the head: they must all
+    /// be reachable, and any cell with only one way in must be the end of the path.
+    /// - head: the last cell of the path.
+    fn can_finish(&self, head: usize) -> bool {
+        let left = self.visited.len() - self.path.len();
+        if left == 0 {
+            return head == self.last_cell;
+        }
+        if head == self.last_cell {
+            return false;
+        }
+        for cell in 0..self.visited.len() {
+            if self.visited[cell] {
+                continue;
+            }
+            let ways_in = self.links[cell]
+                .iter()
+                .filter(|&&next| !self.visited[next] || next == head)
+                .count();
+            if ways_in < 2 && cell != self.last_cell {
+                return false;
+            }
+        }
+        let mut reached = vec![false; self.visited.len()];
+        let mut stack = vec![head];
+        let mut count = 0;
+        while let Some(cell) = stack.pop() {
+            for &next in &self.links[cell] {
+                if !self.visited[next] && !reached[next] {
+                    reached[next] = true;
+                    count += 1;
+                    stack.push(next);
+                }
+            }
+        }
+        return count == left;
+    }
+}
+
+/// This function checks that a zip puzzle fits its grid: its waypoints are numbered 1 up to at
+/// least 2 with each number used once, and its walls are between cells next to each other.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+pub fn validate_zip(board: &ZipBoard, size: u32) -> Result<(), String> {
+    if board.numbers.len() != (size * size) as usize {
+        return Err(format!(
+            "expected {} cells for a {}x{} grid but found {}",
+            size * size,
+            size,
+            size,
+            board.numbers.len()
+        ));
+    }
+    let last = board.numbers.iter().copied().max().unwrap_or(0);
+    if last < 2 {
+        return Err("a path needs at least two waypoints".to_string());
+    }
+    for number in 1..=last {
+        let count = board.numbers.iter().filter(|&&n| n == number).count();
+        if count != 1 {
+            return Err(format!("waypoint {} is used {} times", number, count));
+        }
+    }
+    let shape = BoardShape::square(size);
+    for wall in &board.walls {
+        shape.check_edge(wall.cell1, wall.cell2)?;
+    }
+    return Ok(());
+}
+
+/// This function checks if a path solves a zip puzzle: it goes through every cell once, steps
+/// between cells next to each other without crossing a wall, and visits the waypoints in order.
+/// - path: the cells in the order they're visited.
+/// - board: the puzzle.
+/// - size: the size of the grid.
+pub fn check_zip_solution(path: &Vec<u32>, board: &ZipBoard, size: u32) -> bool {
+    if validate_zip(board, size).is_err() || path.len() != board.numbers.len() {
+        return false;
+    }
+    let mut seen = vec![false; path.len()];
+    for &cell in path {
+        if cell as usize >= seen.len() || seen[cell as usize] {
+            return false;
+        }
+        seen[cell as usize] = true;
+    }
+    let links = zip_links(&board.walls, size);
+    if path
+        .windows(2)
+        .any(|step| !links[step[0] as usize].contains(&(step[1] as usize)))
+    {
+        return false;
+    }
+    // the waypoints must come up as 1, 2, 3... and the path must end on the last one
+    let visited_numbers: Vec<u32> = path
+        .iter()
+        .map(|&cell| board.numbers[cell as usize])
+        .filter(|&number| number != 0)
+        .collect();
+    let in_order = visited_numbers
+        .iter()
+        .enumerate()
+        .all(|(position, &number)| number == position as u32 + 1);
+    let ends_on_last = board.numbers[path[0] as usize] == 1
+        && board.numbers[path[path.len() - 1] as usize] == visited_numbers.len() as u32;
+    return in_order && ends_on_last;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_zip_path() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let path = generate_zip_path(6, &mut rng);
+        let mut waypoints = vec![false; path.len()];
+        waypoints[0] = true;
+        waypoints[35] = true;
+        let board = ZipBoard {
+            numbers: number_waypoints(&path, &waypoints),
+            walls: Vec::new(),
+        };
+        assert!(check_zip_solution(&path, &board, 6));
+        // going backwards visits the waypoints in the wrong order
+        let backwards: Vec<u32> = path.iter().rev().copied().collect();
+        assert!(!check_zip_solution(&backwards, &board, 6));
+    }
+
+    #[test]
+    fn test_generate_zip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for with_walls in [false, true] {
+            let board = generate_zip(6, with_walls, &mut rng).unwrap();
+            assert_eq!(count_zip_solutions(&board, 6, 2), 1);
+            let path = find_zip_solution(&board, 6).unwrap();
+            assert!(check_zip_solution(&path, &board, 6));
+        }
+    }
+}
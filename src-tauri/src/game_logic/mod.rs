@@ -1,3 +0,0 @@
-pub mod numbers;
-pub mod queens;
-pub mod utilities;
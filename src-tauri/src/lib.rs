@@ -1,190 +1,724 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
-mod game_logic;
-use crate::game_logic::numbers::generate_numbers_grid;
-use crate::game_logic::numbers::remove_values;
-use crate::game_logic::queens::check_clash;
-use crate::game_logic::queens::colour_grid_recursively;
-use crate::game_logic::queens::generate_grid;
-use crate::game_logic::queens::get_neighbours;
-use crate::game_logic::utilities::print_grid;
-
-use rand::SeedableRng;
-use rand::{rng, seq::SliceRandom};
-use std::collections::HashMap;
-use std::collections::HashSet;
-use std::collections::VecDeque;
+pub use lopuzz_logic as game_logic;
+
+use crate::game_logic::booklet::{render_booklet, BookletOptions};
+use crate::game_logic::cages::{check_cage_solution, generate_cage_game, Cage};
+use crate::game_logic::clash::ClashRules;
+use crate::game_logic::futoshiki::{
+    check_futoshiki_solution, generate_futoshiki, violated_inequalities, FutoshikiBoard, Inequality,
+};
+use crate::game_logic::hitori::{check_hitori_solution, generate_hitori};
+use crate::game_logic::kakuro::{check_kakuro_solution, generate_kakuro, KakuroBoard};
+use crate::game_logic::layout::parse_queens_layout;
+use crate::game_logic::nonogram::{
+    check_nonogram_solution, generate_nonogram, nonogram_from_image, NonogramBoard, NonogramImage,
+};
+use crate::game_logic::numbers::{generate_ruled_numbers_grid, remove_ruled_values, NumbersRules};
+use crate::game_logic::packs::{
+    find_packs, summarise_packs, PackListing, PackProgress, PackPuzzle,
+};
+use crate::game_logic::palette::QueensBoard;
+use crate::game_logic::puzzle_file::PuzzleFile;
+use crate::game_logic::queens::{
+    check_queens_solution, generate_queens_game, BoardShape, QueensVariant,
+};
+use crate::game_logic::raster::{render_puzzle_png, PngOptions, MAX_CELL_SIZE, MIN_CELL_SIZE};
+use crate::game_logic::regions::{region_metrics, RegionConstraints, RegionMetrics};
+use crate::game_logic::sudoku::{generate_sudoku, SudokuBoard};
+use crate::game_logic::svg::{render_puzzle_svg, SvgOptions};
+use crate::game_logic::tango::{check_tango_solution, generate_tango, TangoBoard};
+use crate::game_logic::timer::{SolutionChecker, SolveResult, SolveSessions};
+use crate::game_logic::utilities::GameKind;
+use crate::game_logic::zip::{check_zip_solution, generate_zip, ZipBoard};
+
+use rand::rng;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .manage(Mutex::new(SolveSessions::default()))
+        .manage(Mutex::new(PackProgressLock))
         .invoke_handler(tauri::generate_handler![
             create_queens_game,
             compare_solutions_queens,
+            import_queens_layout,
+            queens_region_metrics,
+            create_star_battle_game,
+            compare_solutions_star_battle,
             create_numbers_game,
-            compare_solutions_numbers
+            create_sudoku_game,
+            create_cage_game,
+            compare_solutions_cages,
+            create_futoshiki_game,
+            compare_solutions_futoshiki,
+            find_violated_inequalities,
+            compare_solutions_numbers,
+            create_tango_game,
+            compare_solutions_tango,
+            create_zip_game,
+            compare_solutions_zip,
+            create_nonogram_game,
+            compare_solutions_nonogram,
+            create_kakuro_game,
+            compare_solutions_kakuro,
+            create_hitori_game,
+            compare_solutions_hitori,
+            pause_timer,
+            resume_timer,
+            encode_puzzle,
+            decode_puzzle,
+            import_puzzle,
+            export_puzzle,
+            list_packs,
+            next_pack_puzzle,
+            mark_pack_puzzle_solved,
+            export_puzzle_svg,
+            export_pack_booklet,
+            render_puzzle_thumbnail
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
 #[tauri::command]
-/// This function creates a queens game, giving a coloured grid with a unique single solution.
+/// This function creates a queens game, giving a coloured grid with a unique single solution,
+/// along with a palette slot for each region so that neighbouring regions are drawn differently.
+/// Starts the solve timer for the queens game.
 /// - grid_size: the size of the grid to generate.
-fn create_queens_game(grid_size: u32) -> Vec<u32> {
-    loop {
-        println!("new grid");
-        let queens_grid = generate_grid(grid_size);
+/// - constraints: the limits on region sizes, if not the defaults.
+/// - rules: which queens clash with one another, for variants like chess queens or wrapping boards.
+fn create_queens_game(
+    grid_size: u32,
+    constraints: Option<RegionConstraints>,
+    rules: Option<ClashRules>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<QueensBoard, String> {
+    let variant = QueensVariant::queens(grid_size);
+    let variant = QueensVariant {
+        clash: rules.unwrap_or(variant.clash),
+        constraints: constraints.unwrap_or(variant.constraints),
+        ..variant
+    };
+    let colour_grid = generate_queens_game(&variant, &mut rng())?;
+    let checker = queens_checker(colour_grid.clone(), variant);
+    sessions.lock().unwrap().start("queens", checker);
+    return QueensBoard::new(colour_grid, grid_size);
+}
 
-        if queens_grid.iter().sum::<u32>() == 0 {
-            return queens_grid;
-        }
-        // queue is used to keep track of what cells to colour in next
-        let mut queue: VecDeque<(u32, u32)> = VecDeque::new();
-        // seen is used to keep track of what cells have already been seen so we don't have duplicates in the queue
-        let mut seen: HashSet<(u32, u32)> = HashSet::new();
-        // colour_grid is the grid of colours. If a cell is uncoloured it has a value 0. Otherwise its number relates to its colour
-        let mut colour_grid = vec![0; (grid_size * grid_size) as usize];
-        let mut counter: usize = 0;
-        // working solution is the current solution of queens. Used for keeping track of any invalid solutions.
-        let mut working_solution: Vec<(u32, u32, u32)> = Vec::new();
-
-        // colour counter keeps track of the number of cells of each colour
-        let mut colour_counter: HashMap<u32, u32> = HashMap::new();
-
-        let mut impossible_routes = 0;
-        // for randomising colours
-        let mut colours: Vec<u32> = (1..grid_size + 1).collect();
-        colours.shuffle(&mut rng());
-        // find all queens and give them each a different colour value
-        for row in 0..grid_size {
-            for col in 0..grid_size {
-                if queens_grid[(row * grid_size + col) as usize] == 1 {
-                    get_neighbours(
-                        &colour_grid,
-                        &mut queue,
-                        &mut seen,
-                        row,
-                        col,
-                        grid_size,
-                        &colour_counter,
-                    );
-                    colour_counter.insert(colours[counter], 1);
-                    colour_grid[(row * grid_size + col) as usize] = colours[counter];
-                    working_solution.push((row, col, colours[counter]));
-                    counter += 1;
-                }
-            }
-        }
+#[tauri::command]
+/// This function measures the sizes and shapes of the regions of a queens board.
+/// - colour_grid: the grid of the colours.
+/// - size: the size of the grid.
+fn queens_region_metrics(colour_grid: Vec<u32>, size: u32) -> Result<RegionMetrics, String> {
+    return region_metrics(&colour_grid, size);
+}
 
-        // we start colouring recursively. Using the queue containing the neighbours of the queens
-        if colour_grid_recursively(
-            &mut colour_grid,
-            queue,
-            seen,
-            grid_size,
-            &mut working_solution,
-            &mut colour_counter,
-            &mut impossible_routes,
-        ) {
-            return colour_grid;
-        }
-    }
+#[tauri::command]
+/// This function creates a queens game from a hand-drawn letter layout like `AABBC / ADDBC / ...`,
+/// checking it has one region per row, connected regions and exactly one solution.
+/// The layout may be rectangular or have void cells marked `#` or `.`, which are 0 in the board.
+/// Gives the board, in the same form as `create_star_battle_game`, so check the solution with
+/// `compare_solutions_star_battle`.
+/// Starts the solve timer for the star battle game.
+/// - layout: the letter layout, with rows separated by `/` or new lines.
+fn import_queens_layout(
+    layout: String,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<QueensBoard, String> {
+    let layout = parse_queens_layout(&layout)?;
+    let variant = QueensVariant {
+        shape: BoardShape::of_grid(&layout.colour_grid, layout.rows, layout.cols),
+        ..QueensVariant::queens(layout.rows)
+    };
+    let checker = queens_checker(layout.colour_grid.clone(), variant);
+    sessions.lock().unwrap().start("star_battle", checker);
+    return QueensBoard::new(layout.colour_grid, layout.cols);
 }
 
 #[tauri::command]
-/// This function checks if the solution sent back is valid for the queens game.
-/// - colour_grid: the grid of the colours.
+/// This function checks if the solution sent back is valid for the queens game that was handed out.
 /// - solution: the indices of each queen.
-/// - size: the size of the grid.
-fn compare_solutions_queens(colour_grid: Vec<u32>, solution: Vec<u32>, size: u32) -> bool {
-    for i in 1..size as usize {
-        let cell1 = (
-            solution[i] / size,
-            solution[i] % size,
-            colour_grid[solution[i] as usize],
-        );
-        for j in 0..i {
-            let cell2 = (
-                solution[j] / size,
-                solution[j] % size,
-                colour_grid[solution[j] as usize],
-            );
-            if check_clash(cell1, cell2) {
-                return false;
-            }
-        }
+/// If valid, the official solve time is returned alongside.
+fn compare_solutions_queens(
+    solution: Vec<u32>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> SolveResult {
+    return sessions.lock().unwrap().submit("queens", &solution);
+}
+
+#[tauri::command]
+/// This function creates a game of any queens variant: star battle with `stars` stars in every
+/// row, column and region, a board of any shape with void cells that are 0 in the board, chess
+/// queens and the like, or limits on region sizes. A rectangular board has its columns share the
+/// rows' stars evenly. Combinations that aren't supported are rejected.
+/// Gives the board in the same form as `create_queens_game`.
+/// Starts the solve timer for the star battle game.
+/// - variant: the shape, star count, clash rules and region limits of the board.
+fn create_star_battle_game(
+    variant: QueensVariant,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<QueensBoard, String> {
+    let colour_grid = generate_queens_game(&variant, &mut rng())?;
+    let cols = variant.shape.cols;
+    let checker = queens_checker(colour_grid.clone(), variant);
+    sessions.lock().unwrap().start("star_battle", checker);
+    return QueensBoard::new(colour_grid, cols);
+}
+
+#[tauri::command]
+/// This function checks if the solution sent back is valid for the game that was handed out by
+/// `create_star_battle_game` or `import_queens_layout`.
+/// - solution: the indices of each star.
+/// If valid, the official solve time is returned alongside.
+fn compare_solutions_star_battle(
+    solution: Vec<u32>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> SolveResult {
+    return sessions.lock().unwrap().submit("star_battle", &solution);
+}
+
+#[tauri::command]
+/// This function creates a numbers game, giving a partially filled grid with a unique solution.
+/// Starts the solve timer for the numbers game.
+/// - grid_size: the size of the grid.
+/// - difficulty: the difficulty setting.
+/// - rules: the lines besides rows and columns that can't repeat a value, if not every diagonal.
+fn create_numbers_game(
+    grid_size: u32,
+    difficulty: u32,
+    rules: Option<NumbersRules>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<Vec<u32>, String> {
+    let mut rng = rng();
+    let rules = rules.unwrap_or_default();
+    rules.validate(grid_size)?;
+    let solution = generate_ruled_numbers_grid(grid_size, &rules, &mut rng);
+    if solution.contains(&0) {
+        return Err(format!(
+            "there is no {}x{} grid that meets these rules",
+            grid_size, grid_size
+        ));
     }
-    return true;
+    let grid = remove_ruled_values(&solution, difficulty, grid_size, &rules, &mut rng);
+    let mut puzzle = PuzzleFile::numbers(grid.clone(), grid_size);
+    puzzle.rules.numbers = rules;
+    sessions
+        .lock()
+        .unwrap()
+        .start("numbers", puzzle_checker(puzzle));
+
+    return Ok(grid);
 }
 
 #[tauri::command]
-/// This function checks if the solution sent bak is valid.
+/// This function creates a sudoku, a numbers game with boxes instead of diagonals, giving the
+/// givens and the box of each cell. Check the solution with `compare_solutions_numbers`.
+/// Starts the solve timer for the numbers game.
 /// - grid_size: the size of the grid.
 /// - difficulty: the difficulty setting.
-fn create_numbers_game(grid_size: u32, difficulty: u32) -> Vec<u32> {
-    let grid = remove_values(&generate_numbers_grid(grid_size), difficulty, grid_size);
+/// - jigsaw: whether the boxes are irregular rather than the usual rectangles.
+fn create_sudoku_game(
+    grid_size: u32,
+    difficulty: u32,
+    jigsaw: bool,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<SudokuBoard, String> {
+    let board = generate_sudoku(grid_size, difficulty, jigsaw, &mut rng())?;
+    let mut puzzle = PuzzleFile::numbers(board.givens.clone(), grid_size);
+    puzzle.rules.numbers = NumbersRules::Boxes(board.boxes.clone());
+    sessions
+        .lock()
+        .unwrap()
+        .start("numbers", puzzle_checker(puzzle));
+    return Ok(board);
+}
 
-    return grid;
+#[tauri::command]
+/// This function creates a KenKen-style numbers game, where the clues are cages of cells with
+/// arithmetic targets instead of given digits.
+/// Starts the solve timer for the numbers game.
+/// - grid_size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value, if not every diagonal.
+fn create_cage_game(
+    grid_size: u32,
+    rules: Option<NumbersRules>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<Vec<Cage>, String> {
+    let rules = rules.unwrap_or_default();
+    let cages = generate_cage_game(grid_size, &rules, &mut rng())?;
+    let issued = cages.clone();
+    let checker: SolutionChecker =
+        Box::new(move |grid| check_cage_solution(grid, &issued, grid_size, &rules));
+    sessions.lock().unwrap().start("numbers", checker);
+    return Ok(cages);
 }
 
 #[tauri::command]
-/// This function checks if the solution sent back is valid for the numbers game.
-/// - number_grid: the grid of the colours.
-/// - size: the size of the grid.
-fn compare_solutions_numbers(numbers_grid: Vec<u32>, size: u32) -> bool {
-    // check each row
-    print_grid(numbers_grid.clone(), size);
-    for row in 0..size {
-        let mut seen: HashSet<u32> = HashSet::new();
-        for i in 0..size {
-            if !seen.insert(numbers_grid[(row * size + i) as usize]) {
-                return false;
-            }
-        }
+/// This function checks if the solution sent back meets every cage of the KenKen-style game that
+/// was handed out.
+/// - numbers_grid: the grid of numbers.
+/// If valid, the official solve time is returned alongside.
+fn compare_solutions_cages(
+    numbers_grid: Vec<u32>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> SolveResult {
+    return sessions.lock().unwrap().submit("numbers", &numbers_grid);
+}
+
+#[tauri::command]
+/// This function creates a Futoshiki-style numbers game, with inequality clues between some
+/// cells next to each other and few or no given digits.
+/// Starts the solve timer for the numbers game.
+/// - grid_size: the size of the grid.
+/// - rules: the lines besides rows and columns that can't repeat a value, if not every diagonal.
+fn create_futoshiki_game(
+    grid_size: u32,
+    rules: Option<NumbersRules>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<FutoshikiBoard, String> {
+    let rules = rules.unwrap_or_default();
+    let board = generate_futoshiki(grid_size, &rules, &mut rng())?;
+    let issued = board.clone();
+    let checker: SolutionChecker =
+        Box::new(move |grid| check_futoshiki_solution(grid, &issued, grid_size, &rules));
+    sessions.lock().unwrap().start("numbers", checker);
+    return Ok(board);
+}
+
+#[tauri::command]
+/// This function checks if the solution sent back keeps the givens and every inequality of the
+/// Futoshiki-style game that was handed out.
+/// - numbers_grid: the grid of numbers.
+/// If valid, the official solve time is returned alongside.
+fn compare_solutions_futoshiki(
+    numbers_grid: Vec<u32>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> SolveResult {
+    return sessions.lock().unwrap().submit("numbers", &numbers_grid);
+}
+
+#[tauri::command]
+/// This function finds the inequalities the player's grid breaks so far, to highlight them.
+/// - numbers_grid: the grid of numbers, with 0 for empty cells.
+/// - inequalities: the inequality clues of the game.
+fn find_violated_inequalities(
+    numbers_grid: Vec<u32>,
+    inequalities: Vec<Inequality>,
+) -> Result<Vec<Inequality>, String> {
+    let cells = numbers_grid.len() as u32;
+    let out_of_grid = inequalities
+        .iter()
+        .any(|inequality| inequality.smaller.max(inequality.larger) >= cells);
+    if out_of_grid {
+        return Err("an inequality is outside the grid".to_string());
     }
+    return Ok(violated_inequalities(&numbers_grid, &inequalities));
+}
+
+#[tauri::command]
+/// This function checks if the solution sent back is valid for the numbers game that was handed out.
+/// - numbers_grid: the grid of numbers.
+/// If valid, the official solve time is returned alongside.
+fn compare_solutions_numbers(
+    numbers_grid: Vec<u32>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> SolveResult {
+    return sessions.lock().unwrap().submit("numbers", &numbers_grid);
+}
+
+#[tauri::command]
+/// This function creates a tango game, giving the givens and edge clues of a puzzle with a unique
+/// solution. Starts the solve timer for the tango game.
+/// - grid_size: the size of the grid, which must be even.
+/// - difficulty: the hardest technique needed to solve it.
+fn create_tango_game(
+    grid_size: u32,
+    difficulty: u32,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<TangoBoard, String> {
+    let board = generate_tango(grid_size, difficulty, &mut rng())?;
+    let issued = board.clone();
+    let checker: SolutionChecker =
+        Box::new(move |grid| check_tango_solution(grid, &issued, grid_size));
+    sessions.lock().unwrap().start("tango", checker);
+    return Ok(board);
+}
+
+#[tauri::command]
+/// This function checks if the solution sent back is valid for the tango game that was handed out.
+/// - tango_grid: the grid of suns and moons.
+/// If valid, the official solve time is returned alongside.
+fn compare_solutions_tango(
+    tango_grid: Vec<u32>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> SolveResult {
+    return sessions.lock().unwrap().submit("tango", &tango_grid);
+}
+
+#[tauri::command]
+/// This function creates a zip game, giving the numbered waypoints and walls of a puzzle with a
+/// unique path. Starts the solve timer for the zip game.
+/// - grid_size: the size of the grid.
+/// - walls: whether to put walls between some cells.
+fn create_zip_game(
+    grid_size: u32,
+    walls: bool,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<ZipBoard, String> {
+    let board = generate_zip(grid_size, walls, &mut rng())?;
+    let issued = board.clone();
+    let checker: SolutionChecker =
+        Box::new(move |path| check_zip_solution(path, &issued, grid_size));
+    sessions.lock().unwrap().start("zip", checker);
+    return Ok(board);
+}
+
+#[tauri::command]
+/// This function checks if the path sent back is valid for the zip game that was handed out.
+/// - path: the cells in the order they're visited.
+/// If valid, the official solve time is returned alongside.
+fn compare_solutions_zip(path: Vec<u32>, sessions: State<'_, Mutex<SolveSessions>>) -> SolveResult {
+    return sessions.lock().unwrap().submit("zip", &path);
+}
+
+#[tauri::command]
+/// This function creates a nonogram game, giving the row and column clues of a puzzle that can
+/// be solved line by line. Starts the solve timer for the nonogram game.
+/// - grid_size: the size of the grid.
+/// - image: an image to draw the solution from, if not a random one.
+fn create_nonogram_game(
+    grid_size: u32,
+    image: Option<NonogramImage>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<NonogramBoard, String> {
+    let board = match image {
+        None => generate_nonogram(grid_size, grid_size, &mut rng())?,
+        Some(image) => nonogram_from_image(&image, grid_size, grid_size)?,
+    };
+    let issued = board.clone();
+    let checker: SolutionChecker = Box::new(move |grid| check_nonogram_solution(grid, &issued));
+    sessions.lock().unwrap().start("nonogram", checker);
+    return Ok(board);
+}
+
+#[tauri::command]
+/// This function checks if the grid sent back meets every clue of the nonogram game that was
+/// handed out.
+/// - nonogram_grid: the grid, with 1 for filled cells and 0 for empty ones.
+/// If valid, the official solve time is returned alongside.
+fn compare_solutions_nonogram(
+    nonogram_grid: Vec<u32>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> SolveResult {
+    return sessions.lock().unwrap().submit("nonogram", &nonogram_grid);
+}
+
+#[tauri::command]
+/// This function creates a kakuro game, giving the blocks and run sums of a puzzle with a unique
+/// solution. Starts the solve timer for the kakuro game.
+/// - grid_size: the size of the grid, including the first row and column of blocks.
+fn create_kakuro_game(
+    grid_size: u32,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<KakuroBoard, String> {
+    let board = generate_kakuro(grid_size, &mut rng())?;
+    let issued = board.clone();
+    let checker: SolutionChecker =
+        Box::new(move |grid| check_kakuro_solution(grid, &issued, grid_size));
+    sessions.lock().unwrap().start("kakuro", checker);
+    return Ok(board);
+}
+
+#[tauri::command]
+/// This function checks if the grid sent back meets every sum of the kakuro game that was handed out.
+/// - kakuro_grid: the digit in each cell, with 0 for blocks.
+/// If valid, the official solve time is returned alongside.
+fn compare_solutions_kakuro(
+    kakuro_grid: Vec<u32>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> SolveResult {
+    return sessions.lock().unwrap().submit("kakuro", &kakuro_grid);
+}
+
+#[tauri::command]
+/// This function creates a hitori game, giving a grid of numbers with a unique shading. Starts the
+/// solve timer for the hitori game.
+/// - grid_size: the size of the grid.
+fn create_hitori_game(
+    grid_size: u32,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<Vec<u32>, String> {
+    let numbers = generate_hitori(grid_size, &mut rng())?;
+    let issued = numbers.clone();
+    let checker: SolutionChecker =
+        Box::new(move |shading| check_hitori_solution(shading, &issued, grid_size));
+    sessions.lock().unwrap().start("hitori", checker);
+    return Ok(numbers);
+}
+
+#[tauri::command]
+/// This function checks if the shading sent back solves the hitori game that was handed out.
+/// - shading: the shading, with 1 for shaded cells and 0 for unshaded ones.
+/// If valid, the official solve time is returned alongside.
+fn compare_solutions_hitori(
+    shading: Vec<u32>,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> SolveResult {
+    return sessions.lock().unwrap().submit("hitori", &shading);
+}
+
+#[tauri::command]
+/// This function pauses the solve timer, e.g. when the window loses focus.
+/// - game: the name of the game being played.
+fn pause_timer(game: String, sessions: State<'_, Mutex<SolveSessions>>) {
+    sessions.lock().unwrap().pause(&game);
+}
+
+#[tauri::command]
+/// This function resumes the solve timer, e.g. when the window regains focus.
+/// - game: the name of the game being played.
+fn resume_timer(game: String, sessions: State<'_, Mutex<SolveSessions>>) {
+    sessions.lock().unwrap().resume(&game);
+}
+
+/// This function makes the checker for a board of any queens variant that was handed out, which
+/// is sent back as the indices of each star.
+/// - colour_grid: the grid of the colours, with 0 for void cells.
+/// - variant: the variant the board is played with.
+fn queens_checker(colour_grid: Vec<u32>, variant: QueensVariant) -> SolutionChecker {
+    return Box::new(move |solution| check_queens_solution(&colour_grid, solution, &variant));
+}
 
-    // check each column
-    for col in 0..size {
-        let mut seen: HashSet<u32> = HashSet::new();
-        for i in 0..size {
-            if !seen.insert(numbers_grid[(i * size + col) as usize]) {
-                return false;
-            }
+/// This function makes the checker for a puzzle that was handed out from a file, a pack or a
+/// share code. Queens are sent back as the indices of each queen, numbers as the filled grid.
+/// - puzzle: the puzzle.
+fn puzzle_checker(puzzle: PuzzleFile) -> SolutionChecker {
+    match puzzle.game {
+        GameKind::Queens => {
+            return queens_checker(puzzle.grid.clone(), puzzle.queens_variant());
         }
+        GameKind::Numbers => return Box::new(move |grid| puzzle.check_solution(grid)),
+    }
+}
+
+#[tauri::command]
+/// This function turns a puzzle into a share code that can be sent to other players, with the
+/// rules, star count and shape it is played with.
+/// - puzzle: the puzzle to share.
+fn encode_puzzle(puzzle: PuzzleFile) -> Result<String, String> {
+    return game_logic::share::encode_puzzle(&puzzle);
+}
+
+#[tauri::command]
+/// This function reads a share code, validating the puzzle under its own rules before it is loaded.
+/// Starts the solve timer for the shared puzzle's game.
+/// - code: the share code.
+fn decode_puzzle(
+    code: String,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<PuzzleFile, String> {
+    let puzzle = game_logic::share::decode_puzzle(&code)?;
+    let checker = puzzle_checker(puzzle.clone());
+    sessions.lock().unwrap().start(puzzle.game.name(), checker);
+    return Ok(puzzle);
+}
+
+#[tauri::command]
+/// This function loads a puzzle from a JSON puzzle file, upgrading older files to the current schema.
+/// - path: the path of the file.
+fn import_puzzle(path: String) -> Result<PuzzleFile, String> {
+    return game_logic::puzzle_file::load_puzzle(Path::new(&path));
+}
+
+#[tauri::command]
+/// This function saves a puzzle to a JSON puzzle file.
+/// - path: the path of the file.
+/// - puzzle: the puzzle to save.
+fn export_puzzle(path: String, puzzle: PuzzleFile) -> Result<(), String> {
+    return game_logic::puzzle_file::save_puzzle(Path::new(&path), &puzzle);
+}
+
+/// This function finds the directories packs are loaded from: the bundled packs, then the user's own.
+/// - app: the app handle.
+fn pack_directories(app: &AppHandle) -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+    if let Ok(resources) = app.path().resource_dir() {
+        directories.push(resources.join("packs"));
+    }
+    if let Ok(data) = app.path().app_data_dir() {
+        directories.push(data.join("packs"));
+    }
+    return directories;
+}
+
+/// This struct is held while the pack progress file is read, changed and written back, so two
+/// puzzles solved at once can't lose one another's update.
+struct PackProgressLock;
+
+/// This function finds the file the player's pack progress is kept in.
+/// - app: the app handle.
+fn pack_progress_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let data = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    return Ok(data.join("pack_progress.json"));
+}
+
+#[tauri::command]
+/// This function lists the bundled and user packs along with the player's progress through each,
+/// and why any packs that couldn't be read were left out.
+fn list_packs(app: AppHandle) -> Result<PackListing, String> {
+    let (packs, skipped) = find_packs(&pack_directories(&app));
+    let progress = PackProgress::load(&pack_progress_path(&app)?)?;
+    return Ok(PackListing {
+        packs: summarise_packs(&packs, &progress),
+        skipped,
+    });
+}
+
+#[tauri::command]
+/// This function fetches the first puzzle in a pack that hasn't been solved yet.
+/// Gives nothing once every puzzle in the pack is solved.
+/// Starts the solve timer for the pack's game.
+/// - pack_id: the id of the pack.
+fn next_pack_puzzle(
+    pack_id: String,
+    app: AppHandle,
+    sessions: State<'_, Mutex<SolveSessions>>,
+) -> Result<Option<PackPuzzle>, String> {
+    let (packs, _) = find_packs(&pack_directories(&app));
+    let Some(pack) = packs.iter().find(|pack| pack.manifest.id == pack_id) else {
+        return Err(format!("there is no pack called {}", pack_id));
+    };
+    let progress = PackProgress::load(&pack_progress_path(&app)?)?;
+    let Some(index) = progress.next_unsolved(pack) else {
+        return Ok(None);
+    };
+    let puzzle = pack.puzzle(index)?;
+    let checker = puzzle_checker(puzzle.clone());
+    sessions.lock().unwrap().start(puzzle.game.name(), checker);
+    return Ok(Some(PackPuzzle {
+        pack_id,
+        index,
+        puzzle,
+    }));
+}
+
+#[tauri::command]
+/// This function records a pack puzzle as solved, once the solution has been checked against it.
+/// - pack_id: the id of the pack.
+/// - index: the position of the puzzle in the pack.
+/// - solution: the solved grid, with 1 for each queen for queens or the filled grid for numbers.
+fn mark_pack_puzzle_solved(
+    pack_id: String,
+    index: usize,
+    solution: Vec<u32>,
+    progress_lock: State<'_, Mutex<PackProgressLock>>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let (packs, _) = find_packs(&pack_directories(&app));
+    let Some(pack) = packs.iter().find(|pack| pack.manifest.id == pack_id) else {
+        return Err(format!("there is no pack called {}", pack_id));
+    };
+    let puzzle = pack.puzzle(index)?;
+    if !puzzle.check_solution(&solution) {
+        return Err(format!(
+            "that doesn't solve puzzle {} of {}",
+            index, pack_id
+        ));
     }
+    let path = pack_progress_path(&app)?;
+    let _lock = progress_lock.lock().unwrap();
+    let mut progress = PackProgress::load(&path)?;
+    progress.mark_solved(&pack_id, index);
+    return progress.save(&path);
+}
 
-    // check each diagonal
-    let mut seen: HashMap<i32, HashSet<u32>> = HashMap::new();
-    for i in -(size as i32)..(size as i32) {
-        seen.insert(i, HashSet::new());
+#[tauri::command]
+/// This function draws a puzzle as SVG, optionally with its solution, for printing or posting.
+/// Gives back the SVG, and also writes it to a file if a path is given.
+/// - game: the game the puzzle is for.
+/// - grid: the colour grid for queens, or the grid of givens for numbers.
+/// - size: the size of the grid.
+/// - with_solution: whether to draw the solution on top.
+/// - palette: the fill for each region colour, if not the app's own colours.
+/// - path: the file to write the SVG to, if any.
+fn export_puzzle_svg(
+    game: GameKind,
+    grid: Vec<u32>,
+    size: u32,
+    with_solution: bool,
+    palette: Option<Vec<String>>,
+    path: Option<String>,
+) -> Result<String, String> {
+    let mut options = SvgOptions::default();
+    if let Some(palette) = palette {
+        options.palette = palette;
     }
-    for row in 0..(size as i32) {
-        for col in 0..(size as i32) {
-            let diff = row - col;
-            if let Some(thing) = seen.get_mut(&diff) {
-                if !thing.insert(numbers_grid[(row * (size as i32) + col) as usize]) {
-                    return false;
-                }
-            }
-        }
+    let puzzle = PuzzleFile::from_board(game, grid, size);
+    let svg = render_puzzle_svg(&puzzle, with_solution, &options)?;
+    if let Some(path) = path {
+        std::fs::write(&path, &svg).map_err(|e| format!("{}: {}", path, e))?;
     }
+    return Ok(svg);
+}
 
-    // check each other diagonal
-    let mut seen: HashMap<i32, HashSet<u32>> = HashMap::new();
-    for i in -(size as i32)..(size as i32) {
-        seen.insert(i, HashSet::new());
+#[tauri::command]
+/// This function lays out every puzzle in a pack as a printable PDF booklet, with the solutions at the back.
+/// - pack_id: the id of the pack.
+/// - puzzles_per_page: how many puzzles go on each page, if not the default.
+/// - path: the file to write the PDF to.
+fn export_pack_booklet(
+    pack_id: String,
+    puzzles_per_page: Option<u32>,
+    path: String,
+    app: AppHandle,
+) -> Result<(), String> {
+    let (packs, _) = find_packs(&pack_directories(&app));
+    let Some(pack) = packs.iter().find(|pack| pack.manifest.id == pack_id) else {
+        return Err(format!("there is no pack called {}", pack_id));
+    };
+    let puzzles: Vec<PuzzleFile> = (0..pack.manifest.puzzles.len())
+        .map(|index| pack.puzzle(index))
+        .collect::<Result<_, String>>()?;
+    let mut options = BookletOptions {
+        title: pack.manifest.title.clone(),
+        ..BookletOptions::default()
+    };
+    if let Some(puzzles_per_page) = puzzles_per_page {
+        options.puzzles_per_page = puzzles_per_page;
     }
-    for row in 0..(size as i32) {
-        for col in 0..(size as i32) {
-            let diff = row + col;
-            if let Some(thing) = seen.get_mut(&diff) {
-                if !thing.insert(numbers_grid[(row * (size as i32) + col) as usize]) {
-                    return false;
-                }
-            }
-        }
+    let pdf = render_booklet(&puzzles, &options)?;
+    return std::fs::write(&path, pdf).map_err(|e| format!("{}: {}", path, e));
+}
+
+#[tauri::command]
+/// This function draws a puzzle as a PNG image, for thumbnails and share cards.
+/// Gives back the bytes of the image, which the frontend can wrap in a Blob to show.
+/// - game: the game the puzzle is for.
+/// - grid: the colour grid for queens, or the grid of givens for numbers.
+/// - size: the size of the grid.
+/// - with_solution: whether to draw the solved board.
+/// - cell_size: the width of each cell in pixels, if not the default, kept within the sizes the PNG export allows.
+/// - palette: the fill for each region colour, if not the app's own colours.
+fn render_puzzle_thumbnail(
+    game: GameKind,
+    grid: Vec<u32>,
+    size: u32,
+    with_solution: bool,
+    cell_size: Option<u32>,
+    palette: Option<Vec<String>>,
+) -> Result<Vec<u8>, String> {
+    let mut options = PngOptions::default();
+    if let Some(cell_size) = cell_size {
+        options.cell_size = cell_size.clamp(MIN_CELL_SIZE, MAX_CELL_SIZE);
+    }
+    if let Some(palette) = palette {
+        options.palette = palette;
     }
-    return true;
+    let puzzle = PuzzleFile::from_board(game, grid, size);
+    return render_puzzle_png(&puzzle, with_solution, &options);
 }